@@ -0,0 +1,42 @@
+use common::canvas::{Canvas, Color};
+use common::demo::Demo;
+use common::software::SoftwareCanvas;
+use common::Result;
+
+/* COLOR_WINDOW of the default Windows theme. */
+const WINDOW_COLOR: Color = Color::rgb(1., 1., 1.);
+
+/// Nothing but the background: the first demo is about creating a window.
+pub struct SimpleWindowDemo {
+    canvas: SoftwareCanvas,
+}
+
+impl SimpleWindowDemo {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        Ok(SimpleWindowDemo {
+            canvas: SoftwareCanvas::new(width, height)?,
+        })
+    }
+}
+
+impl Demo for SimpleWindowDemo {
+    fn name(&self) -> &str {
+        "Simple window"
+    }
+
+    fn update(&mut self, _dt: f64) {}
+
+    fn render(&mut self) -> Result<()> {
+        self.canvas.begin_draw();
+        self.canvas.clear(WINDOW_COLOR);
+        self.canvas.end_draw()
+    }
+
+    fn canvas(&self) -> &SoftwareCanvas {
+        &self.canvas
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+}
@@ -0,0 +1,4 @@
+//! The empty window of this demo as a gallery page; the executable itself is
+//! plain Win32.
+
+pub mod demo;
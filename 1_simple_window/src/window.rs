@@ -0,0 +1,146 @@
+use common::confirm::{Answer, Confirm};
+use common::menu::{command, submenu, Item, Menu};
+use winapp::{App, MenuBar, TrayIcon, Window, WindowBuilder};
+use windows::{
+    core::*, Win32::Foundation::*, Win32::Graphics::Gdi::GetSysColorBrush,
+    Win32::Graphics::Gdi::COLOR_WINDOW, Win32::UI::WindowsAndMessaging::*,
+};
+
+const CLASS_NAME: &str = "WinAPI madness";
+
+/* Menu command ids. */
+const EXIT: u16 = 1;
+const ALWAYS_ON_TOP: u16 = 2;
+const ABOUT: u16 = 3;
+const SHOW: u16 = 4;
+const HIDE: u16 = 5;
+
+struct SomeWindow;
+
+impl App for SomeWindow {
+    fn on_message(
+        &mut self,
+        window: &Window,
+        message: u32,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            WM_PAINT => {
+                window.validate();
+                Some(LRESULT(0))
+            }
+            WM_CLOSE => {
+                let question = Confirm::new("Want to quit?", "Are you sure?")
+                    .buttons(&[("Quit", Answer::Yes), ("Cancel", Answer::Cancel)])
+                    .dont_ask_again(&format!("{CLASS_NAME}/close"));
+
+                /* The default handling of WM_CLOSE destroys the window. A
+                dialog that fails to show does not keep it open. */
+                match winapp::confirm(window, &question) {
+                    Ok(Answer::Yes) => None,
+                    Ok(_) => Some(LRESULT(0)),
+                    Err(error) => {
+                        eprintln!("{error}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+fn menu() -> MenuBar {
+    let menu = Menu::new(vec![
+        submenu(
+            "&File",
+            vec![command(EXIT, "E&xit").shortcut("Ctrl+Q").into()],
+        ),
+        submenu(
+            "&View",
+            vec![command(ALWAYS_ON_TOP, "&Always on top")
+                .shortcut("Ctrl+T")
+                .into()],
+        ),
+        submenu(
+            "&Help",
+            vec![command(ABOUT, "&About").shortcut("F1").into()],
+        ),
+    ]);
+
+    MenuBar::new(menu)
+        .on(EXIT, |window| {
+            window.close();
+            Ok(())
+        })
+        .on(ALWAYS_ON_TOP, |window| {
+            let on_top = !window.is_menu_checked(ALWAYS_ON_TOP);
+            let after = match on_top {
+                true => HWND_TOPMOST,
+                false => HWND_NOTOPMOST,
+            };
+
+            unsafe {
+                SetWindowPos(window.handle(), after, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE).ok()?;
+            }
+
+            window.set_menu_checked(ALWAYS_ON_TOP, on_top);
+            Ok(())
+        })
+        .on(ABOUT, |window| {
+            let about = Confirm::new(
+                "About",
+                "WinAPI madness\n\nThe first of the Win32 demos: a window, its \
+                 class and its message loop, with a menu.",
+            )
+            .buttons(&[("OK", Answer::Ok)]);
+
+            winapp::confirm(window, &about).map(|_| ())
+        })
+}
+
+/* The tray menu has its own Exit: the menu bar's handlers are busy while
+one of them runs, e.g. with About open. */
+fn tray() -> TrayIcon {
+    let menu = Menu::new(vec![
+        command(SHOW, "&Show window").into(),
+        command(HIDE, "&Hide to tray").into(),
+        Item::Separator,
+        command(EXIT, "E&xit").into(),
+    ]);
+
+    let menu = MenuBar::new(menu)
+        .on(SHOW, |window| {
+            window.restore();
+            Ok(())
+        })
+        .on(EXIT, |window| {
+            window.close();
+            Ok(())
+        })
+        .on(HIDE, |window| {
+            window.hide();
+            if let Err(error) = window.notify(
+                "Still running",
+                "Double-click the tray icon to bring the window back.",
+            ) {
+                eprintln!("could not show the notification: {error}");
+            }
+            Ok(())
+        });
+
+    TrayIcon::new("This is a sample window", menu)
+}
+
+pub fn run() -> Result<()> {
+    WindowBuilder::new(CLASS_NAME)
+        .title("This is a sample window")
+        .size(500, 500)
+        .cursor(IDC_HELP)
+        .background(unsafe { GetSysColorBrush(COLOR_WINDOW) })
+        .menu(menu())
+        .tray(tray())
+        .remember_placement()
+        .run(|_| Ok(SomeWindow))
+}
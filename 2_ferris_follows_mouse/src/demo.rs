@@ -0,0 +1,204 @@
+use common::canvas::{Canvas, Color, Rect};
+use common::demo::Demo;
+use common::input::InputEvent;
+use common::software::SoftwareCanvas;
+use common::{Error, Result};
+use tiny_skia::{ColorU8, Pixmap};
+
+/* The sprite GDI loads for the window, built into the demo. */
+static FERRIS_BMP: &[u8] = include_bytes!("../ferris.bmp");
+
+const BACKGROUND: Color = Color::rgb(1., 1., 1.);
+
+/* The window moves a tenth of the way to the cursor every 50 ms timer tick. */
+const TICK: f64 = 0.05;
+const KEEP_PER_TICK: f64 = 0.9;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/* Uncompressed 24 and 32 bit BMPs in BGR(A) order, which is what paint
+programs write. */
+fn decode_bmp(data: &[u8]) -> std::result::Result<Pixmap, &'static str> {
+    const BI_RGB: u32 = 0;
+    const BI_BITFIELDS: u32 = 3;
+    const BGRA_MASKS: [u32; 4] = [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000];
+
+    let header = || "truncated header";
+
+    if data.get(0..2) != Some(b"BM") {
+        return Err("not a BMP file");
+    }
+
+    let pixels_offset = read_u32(data, 10).ok_or_else(header)? as usize;
+    let width = read_u32(data, 18).ok_or_else(header)? as i32;
+    let height = read_u32(data, 22).ok_or_else(header)? as i32;
+    let bit_count = read_u16(data, 28).ok_or_else(header)?;
+    let compression = read_u32(data, 30).ok_or_else(header)?;
+
+    let has_alpha = match (bit_count, compression) {
+        (24, BI_RGB) | (32, BI_RGB) => false,
+        (32, BI_BITFIELDS) => {
+            let masks: Option<Vec<u32>> = (0..4).map(|i| read_u32(data, 54 + 4 * i)).collect();
+
+            if masks.ok_or_else(header)? != BGRA_MASKS {
+                return Err("unsupported channel masks");
+            }
+
+            true
+        }
+        _ => return Err("unsupported pixel format"),
+    };
+
+    let (width, bottom_up) = (width.unsigned_abs(), height > 0);
+    let height = height.unsigned_abs();
+    let mut pixmap = Pixmap::new(width, height).ok_or("invalid size")?;
+
+    let bytes_per_pixel = bit_count as usize / 8;
+    /* Rows are padded to four bytes. */
+    let stride = (width as usize * bytes_per_pixel + 3) & !3;
+
+    for y in 0..height as usize {
+        let row = if bottom_up {
+            height as usize - 1 - y
+        } else {
+            y
+        };
+        let start = pixels_offset + row * stride;
+        let source = data
+            .get(start..start + width as usize * bytes_per_pixel)
+            .ok_or("truncated pixel data")?;
+
+        for (x, bgra) in source.chunks_exact(bytes_per_pixel).enumerate() {
+            let alpha = if has_alpha { bgra[3] } else { 255 };
+            pixmap.pixels_mut()[y * width as usize + x] =
+                ColorU8::from_rgba(bgra[2], bgra[1], bgra[0], alpha).premultiply();
+        }
+    }
+
+    Ok(pixmap)
+}
+
+/// Ferris eases towards the cursor, with the bottom right corner of the
+/// sprite on it, as the window of the executable does on the desktop.
+pub struct FerrisDemo {
+    canvas: SoftwareCanvas,
+    ferris: Pixmap,
+    position: (f64, f64),
+    cursor: (f64, f64),
+}
+
+impl FerrisDemo {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        let ferris = decode_bmp(FERRIS_BMP)
+            .map_err(|message| Error::Image(format!("ferris.bmp: {message}")))?;
+
+        Ok(FerrisDemo {
+            canvas: SoftwareCanvas::new(width, height)?,
+            ferris,
+            position: (0., 0.),
+            cursor: (0., 0.),
+        })
+    }
+
+    fn target(&self) -> (f64, f64) {
+        (
+            self.cursor.0 - self.ferris.width() as f64,
+            self.cursor.1 - self.ferris.height() as f64,
+        )
+    }
+}
+
+impl Demo for FerrisDemo {
+    fn name(&self) -> &str {
+        "Ferris follows mouse"
+    }
+
+    fn update(&mut self, dt: f64) {
+        let keep = KEEP_PER_TICK.powf(dt / TICK);
+        let (target_x, target_y) = self.target();
+
+        self.position = (
+            target_x + (self.position.0 - target_x) * keep,
+            target_y + (self.position.1 - target_y) * keep,
+        );
+    }
+
+    fn render(&mut self) -> Result<()> {
+        let (x, y) = (self.position.0 as f32, self.position.1 as f32);
+        let destination = Rect {
+            left: x,
+            top: y,
+            right: x + self.ferris.width() as f32,
+            bottom: y + self.ferris.height() as f32,
+        };
+
+        self.canvas.begin_draw();
+        self.canvas.clear(BACKGROUND);
+        self.canvas
+            .draw_bitmap(&self.ferris, Some(&destination), 1., None);
+        self.canvas.end_draw()
+    }
+
+    fn canvas(&self) -> &SoftwareCanvas {
+        &self.canvas
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+
+    fn on_input(&mut self, event: InputEvent) {
+        if let InputEvent::MouseMove { x, y } = event {
+            self.cursor = (x as f64, y as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_ferris() {
+        let ferris = decode_bmp(FERRIS_BMP).unwrap();
+
+        assert_eq!((ferris.width(), ferris.height()), (88, 45));
+        assert_eq!(ferris.pixel(0, 0).unwrap().alpha(), 0, "transparent corner");
+        assert_eq!(ferris.pixel(44, 30).unwrap().alpha(), 255, "opaque body");
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert!(decode_bmp(b"GIF89a").is_err());
+        assert!(decode_bmp(b"BM").is_err());
+    }
+
+    #[test]
+    fn eases_towards_the_cursor() {
+        let mut demo = FerrisDemo {
+            canvas: SoftwareCanvas::new(10, 10).unwrap(),
+            ferris: Pixmap::new(10, 20).unwrap(),
+            position: (0., 0.),
+            cursor: (0., 0.),
+        };
+
+        demo.on_input(InputEvent::MouseMove { x: 110, y: 220 });
+        demo.update(TICK);
+        assert!((demo.position.0 - 10.).abs() < 1e-9);
+        assert!((demo.position.1 - 20.).abs() < 1e-9);
+
+        demo.update(100.);
+        assert!((demo.position.0 - 100.).abs() < 1e-6);
+        assert!((demo.position.1 - 200.).abs() < 1e-6);
+    }
+}
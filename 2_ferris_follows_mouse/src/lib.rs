@@ -0,0 +1,4 @@
+//! Ferris chasing the cursor inside a gallery page; the executable moves a
+//! layered Win32 window across the desktop instead.
+
+pub mod demo;
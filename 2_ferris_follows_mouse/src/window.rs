@@ -0,0 +1,212 @@
+use core::ffi::c_void;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use common::menu::{command, Item, Menu};
+use common::stats::FrameStats;
+use winapp::{App, MenuBar, Pacing, TrayIcon, Window, WindowBuilder};
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::{
+        CreatePatternBrush, DeleteObject, GetObjectA, UpdateWindow, BITMAP, HBITMAP, HBRUSH,
+        HGDIOBJ,
+    },
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+struct WindowsBitmap(HBITMAP);
+
+impl WindowsBitmap {
+    unsafe fn from_file<STRLIKE>(filename: STRLIKE) -> Result<WindowsBitmap>
+    where
+        STRLIKE: Into<PCSTR>,
+    {
+        let handle = unsafe { LoadImageA(None, filename, IMAGE_BITMAP, 0, 0, LR_LOADFROMFILE)? };
+        debug_assert!(handle != HANDLE(0)); /* I suppose that case is handled by Result type being
+                                            returned from LoadImageA */
+        Ok(WindowsBitmap(HBITMAP(handle.0)))
+    }
+
+    fn get(&self) -> HBITMAP {
+        self.0
+    }
+
+    fn info(&self) -> Result<BITMAP> {
+        let mut bitmap_info = BITMAP::default();
+        let bitmap_info_ptr = &mut bitmap_info as *mut BITMAP;
+
+        let bitmap_info_size: i32 = std::mem::size_of::<BITMAP>().try_into().unwrap();
+        match unsafe {
+            GetObjectA(
+                self.0,
+                bitmap_info_size,
+                Some(bitmap_info_ptr as *mut c_void),
+            )
+        } {
+            0 => Err(Error::new(
+                E_FAIL,
+                HSTRING::from("Cannot get info from bitmap"),
+            )),
+            _ => Ok(bitmap_info),
+        }
+    }
+}
+
+impl Drop for WindowsBitmap {
+    fn drop(&mut self) {
+        unsafe {
+            DeleteObject(HGDIOBJ::from(self.get()));
+        }
+    }
+}
+
+/* Tray menu command ids. */
+const PAUSE: u16 = 1;
+const EXIT: u16 = 2;
+
+struct FollowMouse {
+    /* Toggled from the tray menu. */
+    paused: Rc<Cell<bool>>,
+}
+
+impl App for FollowMouse {
+    fn on_message(
+        &mut self,
+        window: &Window,
+        message: u32,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            WM_PAINT => {
+                window.validate();
+                Some(LRESULT(0))
+            }
+            _ => None,
+        }
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        if self.paused.get() {
+            return Ok(());
+        }
+
+        unsafe {
+            let cursor_position = get_cursor_position()?;
+            let window_rectangle = get_window_rectangle(window.handle())?;
+
+            let width = window_rectangle.right - window_rectangle.left;
+            let height = window_rectangle.bottom - window_rectangle.top;
+
+            let newposx = cursor_position.x - width;
+            let newposy = cursor_position.y - height;
+
+            SetWindowPos(
+                window.handle(),
+                HWND_TOPMOST,
+                (newposx + 9 * window_rectangle.left) / 10,
+                (newposy + 9 * window_rectangle.top) / 10,
+                0,
+                0,
+                SWP_NOSIZE | SWP_NOZORDER,
+            )
+            .ok()
+        }
+    }
+}
+
+/* The window has neither a frame nor a taskbar button; the tray icon is the
+only way to stop it. */
+fn tray(paused: Rc<Cell<bool>>) -> TrayIcon {
+    let menu = Menu::new(vec![
+        command(PAUSE, "&Pause").into(),
+        Item::Separator,
+        command(EXIT, "E&xit").into(),
+    ]);
+
+    let menu = MenuBar::new(menu)
+        .on(PAUSE, move |window| {
+            paused.set(!paused.get());
+            window.set_menu_checked(PAUSE, paused.get());
+            Ok(())
+        })
+        .on(EXIT, |window| {
+            window.close();
+            Ok(())
+        });
+
+    TrayIcon::new("Mouse follower", menu)
+}
+
+pub fn run() -> Result<()> {
+    /* Load bitmap */
+    let bitmap = unsafe { WindowsBitmap::from_file(s!("ferris.bmp"))? };
+
+    let bitmap_info = bitmap.info()?;
+    println!("{}, {}", bitmap_info.bmWidth, bitmap_info.bmHeight);
+
+    let brush = unsafe { CreatePatternBrush(bitmap.get()) };
+    assert_ne!(brush, HBRUSH(0));
+
+    let paused = Rc::new(Cell::new(false));
+
+    WindowBuilder::new("ferris")
+        .title("Mouse follower")
+        .style(WS_POPUP | WS_VISIBLE)
+        .ex_style(WS_EX_TOPMOST | WS_EX_LAYERED | WS_EX_TOOLWINDOW)
+        .size(0, 0)
+        .background(brush)
+        .pacing(Pacing::Timer(50))
+        .tray(tray(paused.clone()))
+        .run(|window| {
+            let handle = window.handle();
+
+            unsafe {
+                ShowWindow(handle, SW_SHOW);
+                UpdateWindow(handle);
+                SetWindowPos(
+                    handle,
+                    HWND_TOPMOST,
+                    0,
+                    0,
+                    bitmap_info.bmWidth,
+                    bitmap_info.bmHeight,
+                    SWP_SHOWWINDOW,
+                );
+
+                SetLayeredWindowAttributes(handle, COLORREF(0), 255, LWA_ALPHA | LWA_COLORKEY);
+            }
+
+            if let Err(error) = window.notify(
+                "Ferris follows the mouse",
+                "Right-click the tray icon to pause or quit.",
+            ) {
+                eprintln!("could not show the notification: {error}");
+            }
+
+            Ok(FollowMouse { paused })
+        })
+}
+
+unsafe fn get_cursor_position() -> Result<POINT> {
+    let mut point = POINT::default();
+    match GetCursorPos(&mut point) {
+        BOOL(0) => Err(Error::new(
+            E_FAIL,
+            HSTRING::from("Cannot get cursor position"),
+        )),
+        BOOL(_) => Ok(point),
+    }
+}
+
+unsafe fn get_window_rectangle(hwnd: HWND) -> Result<RECT> {
+    let mut rectangle = RECT::default();
+    match GetWindowRect(hwnd, &mut rectangle) {
+        BOOL(0) => Err(Error::new(
+            E_FAIL,
+            HSTRING::from("Cannot get window rectangle (position)"),
+        )),
+        BOOL(_) => Ok(rectangle),
+    }
+}
@@ -0,0 +1,108 @@
+use common::canvas::Color;
+use serde::Deserialize;
+
+use crate::graphics::FPS;
+
+/// Loaded from `bouncing_balls.toml`; see `bouncing_balls.example.toml`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub width: i32,
+    pub height: i32,
+    pub speed: f64,
+    pub foreground: Color,
+    pub background: Color,
+    pub balls_count: i32,
+    pub ground_fraction: i32,
+    pub margin_fraction: i32,
+    pub raise_fraction: i32,
+    pub shuffle: f64,
+}
+
+const fn rgb<const R: u8, const G: u8, const B: u8>() -> Color {
+    Color::rgb(R as f32 / 255., G as f32 / 255., B as f32 / 255.)
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 1200,
+            height: 720,
+            speed: 1.,
+            foreground: rgb::<100, 255, 100>(),
+            background: rgb::<0, 0, 0>(),
+            balls_count: 8,
+            ground_fraction: 10,
+            margin_fraction: 5,
+            raise_fraction: 3,
+            shuffle: 5.,
+        }
+    }
+}
+
+impl common::config::Config for Config {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.width <= 0 || self.height <= 0 {
+            problems.push(format!(
+                "width and height must be positive, got {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        /* A bounce has to last at least two frames. */
+        if !(self.speed > 0. && self.speed <= FPS as f64 / 2.) {
+            problems.push(format!(
+                "speed must be in (0, {}], got {}",
+                FPS / 2,
+                self.speed
+            ));
+        }
+
+        if self.balls_count < 2 {
+            problems.push(format!(
+                "balls_count must be at least 2, got {}",
+                self.balls_count
+            ));
+        }
+
+        for (key, value, minimum) in [
+            ("ground_fraction", self.ground_fraction, 1),
+            ("raise_fraction", self.raise_fraction, 1),
+            /* Both margins together must leave room for the balls. */
+            ("margin_fraction", self.margin_fraction, 3),
+        ] {
+            if value < minimum {
+                problems.push(format!("{key} must be at least {minimum}, got {value}"));
+            }
+        }
+
+        if !self.shuffle.is_finite() {
+            problems.push(format!("shuffle must be a number, got {}", self.shuffle));
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config;
+
+    #[test]
+    fn example_file_matches_defaults() {
+        let example = include_str!("../bouncing_balls.example.toml");
+        assert_eq!(config::parse::<Config>(example), Ok(Config::default()));
+    }
+
+    #[test]
+    fn rejects_degenerate_values() {
+        let error = config::parse::<Config>("balls_count = 1\nmargin_fraction = 2").unwrap_err();
+
+        assert!(error.contains("balls_count"), "{error}");
+        assert!(error.contains("margin_fraction"), "{error}");
+        assert!(config::parse::<Config>("speed = 0").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use common::canvas::Canvas;
+use common::demo::Demo;
+use common::software::SoftwareCanvas;
+use common::Result;
+
+use crate::config::Config;
+use crate::graphics::{paint_animation, FPS};
+
+pub struct BallsDemo {
+    config: Config,
+    canvas: SoftwareCanvas,
+    time: f64,
+}
+
+impl BallsDemo {
+    pub fn new(config: Config) -> Result<Self> {
+        let canvas = SoftwareCanvas::new(config.width as u32, config.height as u32)?;
+
+        Ok(BallsDemo {
+            config,
+            canvas,
+            time: 0.,
+        })
+    }
+
+    /* One animation frame per timer tick of the window, as in recordings. */
+    pub fn frame(&self) -> u32 {
+        (self.time * FPS as f64).round() as u32
+    }
+}
+
+impl Demo for BallsDemo {
+    fn name(&self) -> &str {
+        "Bouncing balls"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.time += dt;
+    }
+
+    fn render(&mut self) -> Result<()> {
+        let frame = self.frame();
+        paint_animation(&mut self.canvas, &self.config, frame)
+    }
+
+    fn canvas(&self) -> &SoftwareCanvas {
+        &self.canvas
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::replay::{self, Frames, Replay};
+
+    #[test]
+    fn one_frame_per_timer_tick() {
+        let mut demo = BallsDemo::new(Config::default()).unwrap();
+
+        /* Late and early ticks alike draw the next frame. */
+        let trace: Vec<_> = (0..150)
+            .map(|tick| replay::timer(tick as f64 * 0.017 + (tick % 3) as f64 * 0.004, 1))
+            .chain([replay::paint(2.6)])
+            .collect();
+
+        let mut replay = Replay::new(
+            &mut demo,
+            Frames::PerTimer {
+                dt: 1. / FPS as f64,
+            },
+        );
+        replay.run(&trace).unwrap();
+
+        assert_eq!(demo.frame(), 150);
+    }
+}
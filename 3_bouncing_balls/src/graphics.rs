@@ -0,0 +1,162 @@
+use common::canvas::{Canvas, Ellipse, Point, Rect};
+use common::Result;
+
+use crate::config::Config;
+
+pub const FPS: u32 = 60;
+
+fn draw_straight_horizontal_line<C: Canvas>(canvas: &mut C, brush: &C::Brush, y: i32, width: i32) {
+    let rect = Rect {
+        top: y as f32,
+        left: 0.,
+        right: width as f32,
+        bottom: (y + 1) as f32,
+    };
+
+    canvas.fill_rectangle(&rect, brush);
+}
+
+// x in [-1, 1]
+fn y_circle(x: f64) -> f64 {
+    (1. - x * x).sqrt()
+}
+
+/* Same shape GDI `Ellipse` draws for a bounding box: filled with the
+background, outlined with a one pixel foreground pen. */
+fn draw_ball<C: Canvas>(
+    canvas: &mut C,
+    fill: &C::Brush,
+    pen: &C::Brush,
+    (left, top, right, bottom): (i32, i32, i32, i32),
+) {
+    let ellipse = Ellipse {
+        point: Point {
+            x: (left + right) as f32 / 2.,
+            y: (top + bottom) as f32 / 2.,
+        },
+        radius_x: (right - left) as f32 / 2. - 0.5,
+        radius_y: (bottom - top) as f32 / 2. - 0.5,
+    };
+
+    canvas.fill_ellipse(&ellipse, fill);
+    canvas.draw_ellipse(&ellipse, pen, 1.);
+}
+
+/// Where everything goes in a `width` x `height` frame.
+pub struct Scene {
+    /// Rows of the ground lines, from the ground line down, spreading out
+    /// towards the viewer.
+    pub ground: Vec<i32>,
+    /// Bounding boxes of the balls as `(left, top, right, bottom)`.
+    pub balls: Vec<(i32, i32, i32, i32)>,
+}
+
+pub fn layout(config: &Config, width: i32, height: i32, frame: u32) -> Scene {
+    let groundline = height - (height / config.ground_fraction);
+
+    let mut ground = Vec::new();
+    let mut y = groundline;
+    let mut step = 1;
+
+    while y < height {
+        ground.push(y);
+        y += step;
+        step += (step + 1) / 2;
+    }
+
+    let circles = config.balls_count;
+    let margin = width / config.margin_fraction;
+    let ellipse_diameter = (width - 2 * margin) / circles;
+
+    let mut balls = Vec::new();
+    let mut next_ellipse_start = margin;
+
+    let frames = (FPS as f64 / config.speed) as u32;
+    let frame_no = frame % frames;
+
+    for i in 0..circles {
+        let circle_shift = i as f64 / (circles - 1) as f64;
+        let animation_shift = frame_no as f64 / (frames - 1) as f64;
+        let vertical_shift = (circle_shift * config.shuffle + animation_shift) % 1.;
+
+        let max_raise_pixels = height / config.raise_fraction;
+        let raise_factor = y_circle(vertical_shift * 2. - 1.0);
+        let raise_pixels = (max_raise_pixels as f64 * raise_factor) as i32;
+        let rectangle_bottom = groundline - raise_pixels;
+
+        let left = next_ellipse_start;
+        let top = rectangle_bottom - ellipse_diameter;
+        let right = next_ellipse_start + ellipse_diameter;
+        let bottom = rectangle_bottom;
+
+        balls.push((left, top, right, bottom));
+        next_ellipse_start = right;
+    }
+
+    Scene { ground, balls }
+}
+
+pub fn paint_animation<C: Canvas>(canvas: &mut C, config: &Config, frame: u32) -> Result<()> {
+    let size = canvas.size();
+    let (width, height) = (size.width as i32, size.height as i32);
+
+    let background_brush = canvas.create_solid_brush(config.background)?;
+    let foreground_brush = canvas.create_solid_brush(config.foreground)?;
+
+    canvas.begin_draw();
+    canvas.clear(config.background);
+
+    let scene = layout(config, width, height, frame);
+
+    for y in scene.ground {
+        draw_straight_horizontal_line(canvas, &foreground_brush, y, width);
+    }
+
+    for ball in scene.balls {
+        draw_ball(canvas, &background_brush, &foreground_brush, ball);
+    }
+
+    canvas.end_draw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::display_list::{DisplayListCanvas, Operation};
+    use common::golden;
+    use common::software::SoftwareCanvas;
+    use std::path::Path;
+
+    #[test]
+    fn golden_frame() {
+        let config = Config::default();
+        let (width, height) = (config.width as u32, config.height as u32);
+        let mut canvas = SoftwareCanvas::new(width, height).unwrap();
+        paint_animation(&mut canvas, &config, 45).unwrap();
+
+        let reference = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/frame_45.png");
+        golden::assert_matches(&canvas, &reference, 2);
+    }
+
+    #[test]
+    fn balls_are_drawn_over_the_ground() {
+        let config = Config::default();
+        let mut canvas = DisplayListCanvas::new(config.width as u32, config.height as u32);
+        paint_animation(&mut canvas, &config, 45).unwrap();
+
+        let operations: Vec<_> = canvas.commands().iter().map(|c| &c.operation).collect();
+        let first_ball = operations
+            .iter()
+            .position(|operation| matches!(operation, Operation::FillEllipse { .. }))
+            .unwrap();
+
+        assert!(matches!(operations[0], Operation::Clear { .. }));
+        assert!(operations[1..first_ball]
+            .iter()
+            .all(|operation| matches!(operation, Operation::FillRectangle { .. })));
+        assert_eq!(
+            operations.len() - first_ball,
+            2 * config.balls_count as usize
+        );
+    }
+}
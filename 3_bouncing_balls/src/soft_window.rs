@@ -0,0 +1,79 @@
+use common::canvas::Canvas;
+use common::config::ConfigWatcher;
+use common::input::InputEvent;
+use common::playback::Playback;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::Result;
+use softapp::{App, Window, WindowBuilder};
+
+use bouncing_balls::config::Config;
+use bouncing_balls::graphics::{paint_animation, FPS};
+
+const TITLE: &str = "GDI Animation";
+
+/* Like the GDI window: one animation frame per tick, one pixel per DIP. */
+struct Animation {
+    canvas: SoftwareCanvas,
+    config: Config,
+    watcher: ConfigWatcher,
+    /* Counted in frames, and fractional when slowed down. */
+    frames: f64,
+    playback: Playback,
+}
+
+impl Animation {
+    /* Every paint creates its brushes from `config`, so swapping it is all a
+    reload takes, apart from the window size. */
+    fn reload_config(&mut self, window: &Window) {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return;
+        };
+
+        if (config.width, config.height) != (self.config.width, self.config.height) {
+            window.set_client_size(config.width, config.height);
+        }
+
+        self.config = config;
+    }
+}
+
+impl App for Animation {
+    fn canvas(&self) -> &SoftwareCanvas {
+        &self.canvas
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        self.reload_config(window);
+        self.frames += self.playback.advance(1.);
+        paint_animation(&mut self.canvas, &self.config, self.frames as u32)
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.playback.on_input(&event) {
+            window.set_title(&self.playback.title(TITLE));
+        }
+    }
+}
+
+pub fn run(config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new(TITLE)
+        .client_size(config.width, config.height)
+        .resizable(false)
+        .target_fps(FPS)
+        .run(|window| {
+            let (width, height) = window.client_size();
+
+            Ok(Animation {
+                canvas: SoftwareCanvas::new(width, height)?,
+                config,
+                watcher,
+                frames: 0.,
+                playback: Playback::new(1),
+            })
+        })
+}
@@ -0,0 +1,26 @@
+use common::Result;
+
+use bouncing_balls::config::Config;
+use bouncing_balls::graphics::{layout, FPS};
+
+/* The scene is laid out for the terminal rather than scaled down from the
+window, one dot per pixel. */
+pub fn run(config: &Config) -> Result<()> {
+    common::tty::animate(FPS, |canvas, frame| {
+        let (width, height) = (canvas.width() as i32, canvas.height() as i32);
+        let scene = layout(config, width, height, frame);
+
+        for y in scene.ground {
+            canvas.line((0., y as f32), (width as f32, y as f32));
+        }
+
+        for (left, top, right, bottom) in scene.balls {
+            let center = ((left + right) as f32 / 2., (top + bottom) as f32 / 2.);
+            canvas.ellipse(
+                center,
+                (right - left) as f32 / 2.,
+                (bottom - top) as f32 / 2.,
+            );
+        }
+    })
+}
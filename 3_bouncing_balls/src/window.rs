@@ -0,0 +1,99 @@
+use common::config::ConfigWatcher;
+use common::input::InputEvent;
+use common::playback::Playback;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use winapp::{blit, App, Pacing, Window, WindowBuilder};
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::{CreateSolidBrush, HDC},
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use bouncing_balls::config::Config;
+use bouncing_balls::graphics::{paint_animation, FPS};
+
+const TITLE: &str = "GDI Animation";
+
+fn colorref(color: common::canvas::Color) -> COLORREF {
+    let channel = |value: f32| (value * 255.).round() as u32;
+    COLORREF((channel(color.b) << 16) | (channel(color.g) << 8) | channel(color.r))
+}
+
+fn paint(window: &Window, hdc: HDC, config: &Config, frame: u32) -> Result<()> {
+    let (width, height) = window.client_size();
+
+    /* Nothing to draw into while the window is minimized. */
+    if let Ok(mut canvas) = SoftwareCanvas::new(width, height) {
+        paint_animation(&mut canvas, config, frame)?;
+        blit(hdc, &canvas);
+    }
+
+    Ok(())
+}
+
+struct Animation {
+    config: Config,
+    watcher: ConfigWatcher,
+    /* Counted in frames, and fractional when slowed down. */
+    frames: f64,
+    playback: Playback,
+}
+
+impl Animation {
+    /* Every paint creates its brushes from `config`, so swapping it is all a
+    reload takes, apart from the window size. */
+    fn reload_config(&mut self, window: &Window) -> Result<()> {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return Ok(());
+        };
+
+        if (config.width, config.height) != (self.config.width, self.config.height) {
+            window.set_client_size(config.width, config.height)?;
+        }
+
+        self.config = config;
+        Ok(())
+    }
+}
+
+impl App for Animation {
+    fn on_paint(&mut self, window: &Window, hdc: HDC) -> Result<()> {
+        paint(window, hdc, &self.config, self.frames as u32)
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        self.reload_config(window)?;
+        self.frames += self.playback.advance(1.);
+        /* The whole client area is repainted, no need to erase it first. */
+        window.invalidate();
+        Ok(())
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.playback.on_input(&event) {
+            window.set_title(&self.playback.title(TITLE));
+        }
+    }
+}
+
+pub fn run(config: Config, watcher: ConfigWatcher) -> Result<()> {
+    let background = unsafe { CreateSolidBrush(colorref(config.background)) };
+
+    WindowBuilder::new("GDI animation")
+        .title(TITLE)
+        .style(WS_OVERLAPPEDWINDOW | WS_VISIBLE & !WS_THICKFRAME)
+        .client_size(config.width, config.height)
+        .background(background)
+        .pacing(Pacing::Timer(1000 / FPS))
+        .remember_placement()
+        .run(|_| {
+            Ok(Animation {
+                config,
+                watcher,
+                frames: 0.,
+                playback: Playback::new(1),
+            })
+        })
+}
@@ -0,0 +1,97 @@
+use common::canvas::Color;
+use serde::Deserialize;
+
+/// Loaded from `plot_3d.toml`; see `plot_3d.example.toml`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub width: i32,
+    pub height: i32,
+    pub x_points: usize,
+    pub y_points: usize,
+    pub spread: f64,
+    pub x_shift: f64,
+    pub y_shift: f64,
+    pub line_color: Color,
+    pub line_width: f32,
+    pub background: Color,
+}
+
+/* Above this the mesh takes longer to draw than a frame lasts. */
+const MAX_POINTS: usize = 500;
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 800,
+            height: 600,
+            x_points: 51,
+            y_points: 51,
+            spread: 200.,
+            x_shift: 400.,
+            y_shift: 350.,
+            line_color: Color::rgb(1., 1., 1.),
+            line_width: 1.,
+            background: Color::rgb(0., 0., 0.),
+        }
+    }
+}
+
+impl common::config::Config for Config {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.width <= 0 || self.height <= 0 {
+            problems.push(format!(
+                "width and height must be positive, got {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        for (key, value) in [("x_points", self.x_points), ("y_points", self.y_points)] {
+            if !(2..=MAX_POINTS).contains(&value) {
+                problems.push(format!("{key} must be in 2..={MAX_POINTS}, got {value}"));
+            }
+        }
+
+        if !(self.spread > 0. && self.spread.is_finite()) {
+            problems.push(format!("spread must be positive, got {}", self.spread));
+        }
+
+        for (key, value) in [("x_shift", self.x_shift), ("y_shift", self.y_shift)] {
+            if !value.is_finite() {
+                problems.push(format!("{key} must be a number, got {value}"));
+            }
+        }
+
+        if !(self.line_width > 0. && self.line_width.is_finite()) {
+            problems.push(format!(
+                "line_width must be positive, got {}",
+                self.line_width
+            ));
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config;
+
+    #[test]
+    fn example_file_matches_defaults() {
+        let example = include_str!("../plot_3d.example.toml");
+        assert_eq!(config::parse::<Config>(example), Ok(Config::default()));
+    }
+
+    #[test]
+    fn rejects_degenerate_values() {
+        let error = config::parse::<Config>("x_points = 1\nspread = -3").unwrap_err();
+
+        assert!(error.contains("x_points"), "{error}");
+        assert!(error.contains("spread"), "{error}");
+        assert!(config::parse::<Config>("y_points = 100000").is_err());
+    }
+}
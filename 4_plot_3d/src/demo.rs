@@ -0,0 +1,46 @@
+use common::clock::{Clock, ManualClock};
+use common::demo::Demo;
+use common::software::SoftwareCanvas;
+use common::Result;
+
+use crate::config::Config;
+use crate::graphics::{rotation_angle, Graphics, ROTATION_PERIOD};
+
+pub struct PlotDemo {
+    graphics: Graphics<SoftwareCanvas>,
+    clock: ManualClock,
+}
+
+impl PlotDemo {
+    pub fn new(config: Config) -> Result<Self> {
+        let canvas = SoftwareCanvas::new(config.width as u32, config.height as u32)?;
+
+        Ok(PlotDemo {
+            graphics: Graphics::new(canvas, config)?,
+            clock: ManualClock::new(),
+        })
+    }
+}
+
+impl Demo for PlotDemo {
+    fn name(&self) -> &str {
+        "3D plot"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.clock.advance(dt);
+    }
+
+    fn render(&mut self) -> Result<()> {
+        let time = self.clock.get_time(ROTATION_PERIOD);
+        self.graphics.render(rotation_angle(time))
+    }
+
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.graphics.canvas()
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)
+    }
+}
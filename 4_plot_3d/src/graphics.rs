@@ -0,0 +1,269 @@
+use common::canvas::{Canvas, Point};
+use common::dpi::Dpi;
+use common::{Error, Result};
+
+use crate::config::Config;
+
+/* The rotation angle starts over after this many seconds. */
+pub const ROTATION_PERIOD: u32 = 48;
+
+pub fn rotation_angle(time: f64) -> f64 {
+    time * std::f64::consts::PI / 3.
+}
+
+/* The plotted surface, over the [-1, 1] square. */
+pub fn surface(x: f64, y: f64) -> f64 {
+    (10. * (x * x + y * y).sqrt()).cos() / 4.
+}
+
+pub struct Plot {
+    array: Vec<Vec<(f64, f64, f64)>>,
+    spread: f64,
+    x_shift: f64,
+    y_shift: f64,
+}
+
+/* map [0; points) -> [-1, 1] */
+fn to_unit(index: usize, points: usize) -> f64 {
+    ((index as i64 - (points / 2) as i64) as f64) / ((points / 2) as f64)
+}
+
+impl Plot {
+    pub fn get_initial_plot<F>(function: F, config: &Config) -> Plot
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        let array = (0..config.x_points)
+            .map(|i| {
+                let x = to_unit(i, config.x_points);
+
+                (0..config.y_points)
+                    .map(|j| {
+                        let y = to_unit(j, config.y_points);
+                        (x, y, function(x, y))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Plot {
+            array,
+            spread: config.spread,
+            x_shift: config.x_shift,
+            y_shift: config.y_shift,
+        }
+    }
+
+    pub fn x_points(&self) -> usize {
+        self.array.len()
+    }
+
+    pub fn y_points(&self) -> usize {
+        self.array[0].len()
+    }
+
+    fn rotate_around_z(x: f64, y: f64, alpha: f64) -> (f64, f64) /* x, y */ {
+        let x_prim = x * alpha.cos() - y * alpha.sin();
+        let y_prim = x * alpha.sin() + y * alpha.cos();
+        (x_prim, y_prim)
+    }
+
+    fn project_onto_plane(y: f64, z: f64, alpha: f64) -> f64 /* y */ {
+        y * alpha.cos() - z * alpha.sin()
+    }
+
+    pub fn get_pixel_value(&self, i: usize, j: usize, alpha: f64) -> (f32, f32) {
+        let (x, y, z) = self.array[i][j];
+        let (x, y) = Self::rotate_around_z(x, y, alpha / 8.);
+        let y = Self::project_onto_plane(y, z, alpha);
+
+        let x_pixel = x * self.spread + self.x_shift;
+        let y_pixel = y * self.spread + self.y_shift;
+
+        (x_pixel as f32, y_pixel as f32)
+    }
+
+    /// The wireframe at `alpha` as polylines in window pixels: one along y
+    /// for every x, then one along x for every y.
+    pub fn lines(&self, alpha: f64) -> impl Iterator<Item = Vec<(f32, f32)>> + '_ {
+        let (x_points, y_points) = (self.x_points(), self.y_points());
+
+        let along_y = (0..x_points).map(move |i| {
+            (0..y_points)
+                .map(|j| self.get_pixel_value(i, j, alpha))
+                .collect()
+        });
+        let along_x = (0..y_points).map(move |j| {
+            (0..x_points)
+                .map(|i| self.get_pixel_value(i, j, alpha))
+                .collect()
+        });
+
+        along_y.chain(along_x)
+    }
+}
+
+pub struct Graphics<C: Canvas> {
+    canvas: C,
+    config: Config,
+    plot: Plot,
+    /* Device dependent, recreated when the device is lost. */
+    brush: C::Brush,
+}
+
+impl<C: Canvas> Graphics<C> {
+    pub fn render(&mut self, alpha: f64) -> Result<()> {
+        self.draw(alpha);
+
+        match self.canvas.end_draw() {
+            /* The frame is lost; the next one draws with the new target. */
+            Err(Error::DeviceLost) => {
+                self.canvas.recreate_target()?;
+                self.brush = self.canvas.create_solid_brush(self.config.line_color)?;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn draw(&mut self, alpha: f64) {
+        self.canvas.begin_draw();
+        self.canvas.clear(self.config.background);
+
+        for line in self.plot.lines(alpha) {
+            let points: Vec<Point> = line.into_iter().map(|(x, y)| Point { x, y }).collect();
+
+            self.canvas
+                .draw_polyline(&points, &self.brush, self.config.line_width);
+        }
+    }
+
+    pub fn canvas(&self) -> &C {
+        &self.canvas
+    }
+
+    pub fn canvas_mut(&mut self) -> &mut C {
+        &mut self.canvas
+    }
+
+    /* The same scene drawn into another canvas, e.g. for a screenshot. */
+    pub fn snapshot<D: Canvas>(&self, canvas: D) -> Result<Graphics<D>> {
+        Graphics::new(canvas, self.config.clone())
+    }
+
+    pub fn new(mut canvas: C, config: Config) -> Result<Self> {
+        let brush = canvas.create_solid_brush(config.line_color)?;
+
+        Ok(Graphics {
+            canvas,
+            plot: Plot::get_initial_plot(surface, &config),
+            config,
+            brush,
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /* Rebuilds the mesh and the brush from a reloaded configuration. */
+    pub fn set_config(&mut self, config: Config) -> Result<()> {
+        self.brush = self.canvas.create_solid_brush(config.line_color)?;
+        self.plot = Plot::get_initial_plot(surface, &config);
+        self.config = config;
+        Ok(())
+    }
+
+    /* Only the window resizes the scene. */
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+
+    pub fn set_dpi(&mut self, dpi: Dpi) {
+        self.canvas.set_dpi(dpi);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::clock::{Clock, ManualClock};
+    use common::display_list::{DisplayListCanvas, Operation};
+    use common::golden;
+    use common::software::SoftwareCanvas;
+    use common::svg::SvgCanvas;
+    use std::f64::consts::PI;
+    use std::path::Path;
+
+    #[test]
+    fn rotation_follows_clock_and_wraps() {
+        let mut clock = ManualClock::new();
+        assert_eq!(rotation_angle(clock.get_time(ROTATION_PERIOD)), 0.);
+
+        clock.advance(3.);
+        assert!((rotation_angle(clock.get_time(ROTATION_PERIOD)) - PI).abs() < 1e-12);
+
+        clock.set(ROTATION_PERIOD as f64 + 3.);
+        assert!((rotation_angle(clock.get_time(ROTATION_PERIOD)) - PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn new_config_rebuilds_the_mesh() {
+        let canvas = SoftwareCanvas::new(800, 600).unwrap();
+        let mut graphics = Graphics::new(canvas, Config::default()).unwrap();
+
+        let config = Config {
+            x_points: 11,
+            y_points: 21,
+            spread: 100.,
+            ..Config::default()
+        };
+        graphics.set_config(config).unwrap();
+
+        assert_eq!(
+            (graphics.plot.x_points(), graphics.plot.y_points()),
+            (11, 21)
+        );
+        assert_eq!(graphics.plot.get_pixel_value(10, 20, 0.), (500., 450.));
+        graphics.render(0.7).unwrap();
+    }
+
+    #[test]
+    fn golden_plot() {
+        let mut graphics =
+            Graphics::new(SoftwareCanvas::new(800, 600).unwrap(), Config::default()).unwrap();
+        graphics.render(0.7).unwrap();
+
+        let reference =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/plot_alpha_0.7.png");
+        golden::assert_matches(graphics.canvas(), &reference, 2);
+    }
+
+    #[test]
+    fn svg_keeps_one_polyline_per_mesh_line() {
+        let config = Config::default();
+        let lines = config.x_points + config.y_points;
+        let mut graphics = Graphics::new(SvgCanvas::new(800, 600), config).unwrap();
+        graphics.render(0.7).unwrap();
+
+        let svg = graphics.canvas().to_svg();
+        assert_eq!(svg.matches("<polyline ").count(), lines);
+        assert!(!svg.contains("<line "));
+    }
+
+    #[test]
+    fn draws_one_polyline_per_mesh_line() {
+        let canvas = DisplayListCanvas::new(800, 600);
+        let mut graphics = Graphics::new(canvas, Config::default()).unwrap();
+        graphics.render(0.7).unwrap();
+
+        let polylines = graphics
+            .canvas()
+            .commands()
+            .iter()
+            .filter(|command| matches!(command.operation, Operation::DrawPolyline { .. }))
+            .count();
+
+        assert_eq!(polylines, 102);
+    }
+}
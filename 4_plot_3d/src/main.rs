@@ -1,417 +1,106 @@
-use windows::{
-    core::*, Win32::Foundation::*, Win32::Graphics::Direct2D::Common::*,
-    Win32::Graphics::Direct2D::*, Win32::Graphics::Gdi::*, Win32::System::Com::*,
-    Win32::System::LibraryLoader::*, Win32::System::Performance::*, Win32::UI::HiDpi::*,
-    Win32::UI::WindowsAndMessaging::*,
-};
-
-struct DrawingParams {
-    function: fn(f64, f64) -> f64,
-    x_points: usize,
-    y_points: usize,
-    spread: f64,
-    x_shift: f64,
-    y_shift: f64,
+#[cfg(all(not(windows), feature = "software-window"))]
+mod soft_window;
+#[cfg(unix)]
+mod tty;
+#[cfg(windows)]
+mod window;
+
+use common::clock::{Clock, ManualClock};
+use common::config::ConfigFile;
+use common::record::{RecordOptions, Recorder};
+use common::software::SoftwareCanvas;
+use common::svg::{SvgCanvas, SvgOptions};
+
+use plot_3d::config::Config;
+use plot_3d::graphics::{rotation_angle, Graphics, ROTATION_PERIOD};
+
+fn record(options: &RecordOptions, config: Config) -> common::Result<()> {
+    let mut recorder = Recorder::new(options)?;
+    let mut clock = ManualClock::new();
+    let canvas = SoftwareCanvas::new(config.width as u32, config.height as u32)?;
+    let mut graphics = Graphics::new(canvas, config)?;
+
+    for frame in 0..options.frames {
+        clock.set(options.frame_time(frame));
+        let time = clock.get_time(ROTATION_PERIOD);
+        graphics.render(rotation_angle(time))?;
+        recorder.write_frame(graphics.canvas())?;
+    }
+
+    recorder.finish()
 }
 
-const PARAMS: DrawingParams = DrawingParams {
-    function: |x: f64, y: f64| -> f64 { (10. * (x * x + y * y).sqrt()).cos() / 4. },
-    x_points: 51,
-    y_points: 51,
-    spread: 200.,
-    x_shift: 400.,
-    y_shift: 350.,
-};
-
-type PlotArray = [[(f64, f64, f64); PARAMS.y_points]; PARAMS.x_points];
+fn export_svg(options: &SvgOptions, config: Config) -> common::Result<()> {
+    let mut clock = ManualClock::new();
+    let canvas = SvgCanvas::new(config.width as u32, config.height as u32);
+    let mut graphics = Graphics::new(canvas, config)?;
 
-struct Plot {
-    // Box to prevent stack overflow when x_points, y_points >~ 100
-    array: Box<PlotArray>,
+    clock.set(options.time);
+    let time = clock.get_time(ROTATION_PERIOD);
+    graphics.render(rotation_angle(time))?;
+    graphics.canvas().save(&options.path)
 }
 
-impl Plot {
-    pub fn get_initial_plot<F>(function: F) -> Plot
-    where
-        F: Fn(f64, f64) -> f64,
-    {
-        let mut array = Box::new([[(0., 0., 0.); PARAMS.y_points]; PARAMS.x_points]);
-
-        for (i, row) in array.iter_mut().enumerate() {
-            // map [0; X_POINTS) -> [-1, 1]
-            let x =
-                ((i as i64 - (PARAMS.x_points / 2) as i64) as f64) / ((PARAMS.x_points / 2) as f64);
-
-            for (j, value) in row.iter_mut().enumerate() {
-                // map [0; X_POINTS) -> [-1, 1]
-                let y = ((j as i64 - (PARAMS.y_points / 2) as i64) as f64)
-                    / ((PARAMS.y_points / 2) as f64);
-                let z = function(x, y);
-                *value = (x, y, z);
-            }
-        }
-
-        Plot { array }
-    }
-
-    fn rotate_around_z(x: f64, y: f64, alpha: f64) -> (f64, f64) /* x, y */ {
-        let x_prim = x * alpha.cos() - y * alpha.sin();
-        let y_prim = x * alpha.sin() + y * alpha.cos();
-        (x_prim, y_prim)
-    }
-
-    fn project_onto_plane(y: f64, z: f64, alpha: f64) -> f64 /* y */ {
-        y * alpha.cos() - z * alpha.sin()
-    }
-
-    pub fn get_pixel_value(&self, i: usize, j: usize, alpha: f64) -> (f32, f32) {
-        let (x, y, z) = self.array[i][j];
-        let (x, y) = Self::rotate_around_z(x, y, alpha/8.);
-        let y = Self::project_onto_plane(y, z, alpha);
-
-        let x_pixel = x * PARAMS.spread + PARAMS.x_shift;
-        let y_pixel = y * PARAMS.spread + PARAMS.y_shift;
-
-        (x_pixel as f32, y_pixel as f32)
-    }
-}
-
-struct Graphics {
-    render_target: ID2D1HwndRenderTarget,
-    plot: Plot,
-    brush: ID2D1SolidColorBrush,
-}
-
-impl Graphics {
-    fn render(&self, alpha: f64) -> Result<()> {
-        self.begin_draw();
-        self.clear_screen(0., 0., 0.);
-
-        for i in 0..PARAMS.x_points {
-            let mut previous_point = self.plot.get_pixel_value(i, 0, alpha);
+#[cfg(windows)]
+fn main() -> windows::core::Result<()> {
+    use common::config::ConfigWatcher;
+    use windows::Win32::System::Com::*;
 
-            for j in 1..PARAMS.y_points {
-                let next_point = self.plot.get_pixel_value(i, j, alpha);
-                self.draw_line(previous_point, next_point);
-                previous_point = next_point;
-            }
-        }
+    let config_file = ConfigFile::from_args(std::env::args().skip(1), "plot_3d")?;
+    let config: Config = config_file.load_or_default();
 
-        for j in 0..PARAMS.y_points {
-            let mut previous_point = self.plot.get_pixel_value(0, j, alpha);
-
-            for i in 1..PARAMS.x_points {
-                let next_point = self.plot.get_pixel_value(i, j, alpha);
-                self.draw_line(previous_point, next_point);
-                previous_point = next_point;
-            }
-        }
-
-        self.end_draw()
-    }
-
-    fn new(window: &Window) -> Result<Self> {
-        let options = D2D1_FACTORY_OPTIONS::default();
-
-        let factory: ID2D1Factory =
-            unsafe { D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, Some(&options))? };
-
-        let width = window.client_area_width as u32;
-        let height = window.client_area_height as u32;
-
-        let properties = D2D1_RENDER_TARGET_PROPERTIES::default();
-        let hwnd_properties = D2D1_HWND_RENDER_TARGET_PROPERTIES {
-            hwnd: window.handle,
-            pixelSize: D2D_SIZE_U { width, height },
-            ..Default::default()
-        };
-
-        let render_target =
-            unsafe { factory.CreateHwndRenderTarget(&properties, &hwnd_properties)? };
-
-        let brush = unsafe {
-            render_target.CreateSolidColorBrush(
-                &D2D1_COLOR_F {
-                    r: 1.,
-                    g: 1.,
-                    b: 1.,
-                    a: 1.0,
-                },
-                None,
-            )?
-        };
-
-        Ok(Graphics {
-            render_target,
-            brush,
-            plot: Plot::get_initial_plot(PARAMS.function),
-        })
+    if let Some(options) = SvgOptions::from_args(std::env::args().skip(1))? {
+        return Ok(export_svg(&options, config)?);
     }
 
-    fn clear_screen(&self, r: f32, g: f32, b: f32) {
-        unsafe {
-            self.render_target
-                .Clear(Some(&D2D1_COLOR_F { r, g, b, a: 1.0 }));
-        }
+    if let Some(options) = RecordOptions::from_args(std::env::args().skip(1))? {
+        return Ok(record(&options, config)?);
     }
 
-    fn draw_line(&self, p1: (f32, f32), p2: (f32, f32)) {
-        let point0 = D2D_POINT_2F { x: p1.0, y: p1.1 };
-        let point1 = D2D_POINT_2F { x: p2.0, y: p2.1 };
-
-        unsafe {
-            self.render_target
-                .DrawLine(point0, point1, &self.brush, 1., None);
-        }
-    }
-
-    fn begin_draw(&self) {
-        unsafe {
-            self.render_target.BeginDraw();
-        }
-    }
-
-    fn end_draw(&self) -> Result<()> {
-        unsafe { self.render_target.EndDraw(None, None) }
-    }
-}
-
-fn main() -> Result<()> {
     unsafe {
         CoInitializeEx(None, COINIT_MULTITHREADED)?;
     }
 
-    let mut window = Window::new()?;
-    window.run()
-}
-
-struct Timer {
-    start_time: i64,
-    update_time: i64,
-    frequency: i64,
+    let clock = common::clock::QpcClock::new()?;
+    window::run(Box::new(clock), config, ConfigWatcher::new(config_file))
 }
 
-impl Timer {
-    fn query_performance_frequency() -> Result<i64> {
-        let mut frequency = 0;
-
-        unsafe {
-            QueryPerformanceFrequency(&mut frequency).ok()?;
-        }
-
-        Ok(frequency)
-    }
-
-    fn query_performance_counter() -> Result<i64> {
-        let mut counter = 0;
+#[cfg(all(not(windows), feature = "software-window"))]
+fn open_window(config: Config, config_file: ConfigFile) -> common::Result<()> {
+    use common::clock::InstantClock;
+    use common::config::ConfigWatcher;
 
-        unsafe {
-            QueryPerformanceCounter(&mut counter).ok()?;
-        }
-
-        Ok(counter)
-    }
-
-    fn new() -> Result<Self> {
-        let frequency = Self::query_performance_frequency()?;
-        let counter = Self::query_performance_counter()?;
-
-        Ok(Timer {
-            start_time: counter,
-            update_time: counter,
-            frequency,
-        })
-    }
-
-    fn get_time(&self, period_in_seconds: u32) -> f64 {
-        let delta = self.update_time.wrapping_sub(self.start_time);
-        let period = self.frequency * period_in_seconds as i64;
-        let time = delta % period;
-
-        time as f64 / self.frequency as f64
-    }
-
-    fn update(&mut self) -> Result<()> {
-        self.update_time = Self::query_performance_counter()?;
-        Ok(())
-    }
+    soft_window::run(
+        Box::new(InstantClock::new()),
+        config,
+        ConfigWatcher::new(config_file),
+    )
 }
 
-struct Window {
-    handle: HWND,
-    visible: bool,
-    timer: Timer,
-    client_area_width: i32,
-    client_area_height: i32,
-    graphics: Option<Graphics>,
-    alpha: f64,
+#[cfg(all(not(windows), not(feature = "software-window")))]
+fn open_window(_config: Config, _config_file: ConfigFile) -> common::Result<()> {
+    eprintln!(
+        "plot_3d needs Windows or the software-window feature to open a window; \
+         use {} or {} to render offscreen",
+        common::record::USAGE,
+        common::svg::USAGE
+    );
+    std::process::exit(2);
 }
 
-impl Window {
-    fn new() -> Result<Self> {
-        Ok(Window {
-            handle: HWND(0),
-            visible: false,
-            timer: Timer::new()?,
-            client_area_width: 0,
-            client_area_height: 0,
-            graphics: None,
-            alpha: 0.,
-        })
-    }
-
-    fn message_handler(&mut self, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
-        unsafe {
-            match message {
-                WM_PAINT => {
-                    let mut ps = PAINTSTRUCT::default();
-                    BeginPaint(self.handle, &mut ps);
-
-                    if let Some(graphics) = &self.graphics {
-                        graphics.render(self.alpha).unwrap();
-                    }
-
-                    EndPaint(self.handle, &ps);
-                    LRESULT(0)
-                }
+#[cfg(not(windows))]
+fn main() -> common::Result<()> {
+    let config_file = ConfigFile::from_args(std::env::args().skip(1), "plot_3d")?;
+    let config: Config = config_file.load_or_default();
 
-                WM_SIZE | WM_DISPLAYCHANGE => {
-                    if let Some(graphics) = &self.graphics {
-                        graphics.render(self.alpha).unwrap();
-                    }
-                    LRESULT(0)
-                }
-                WM_ACTIVATE => {
-                    self.visible = true; // TODO: unpack !HIWORD(wparam);
-                    LRESULT(0)
-                }
-                WM_DESTROY => {
-                    PostQuitMessage(0);
-                    LRESULT(0)
-                }
-                _ => DefWindowProcA(self.handle, message, wparam, lparam),
-            }
-        }
+    if let Some(options) = SvgOptions::from_args(std::env::args().skip(1))? {
+        return export_svg(&options, config);
     }
 
-    fn run(&mut self) -> Result<()> {
-        unsafe {
-            let instance = GetModuleHandleA(None)?;
-            debug_assert!(instance.0 != 0);
-            let window_class = s!("AnimationWave");
-
-            let wc = WNDCLASSA {
-                hCursor: LoadCursorW(None, IDC_HAND)?,
-                hInstance: instance,
-                lpszClassName: window_class,
-                style: CS_HREDRAW | CS_VREDRAW,
-                lpfnWndProc: Some(Self::wndproc),
-                ..Default::default()
-            };
-
-            let atom = RegisterClassA(&wc);
-            debug_assert!(atom != 0);
-
-            let handle = CreateWindowExA(
-                WINDOW_EX_STYLE::default(),
-                window_class,
-                s!("Animation Wave"),
-                WS_OVERLAPPEDWINDOW | WS_VISIBLE,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                None,
-                None,
-                instance,
-                Some(self as *mut _ as _),
-            );
-
-            assert!(handle.0 != 0);
-            assert!(handle == self.handle);
-
-            let mut client_rect = RECT {
-                left: 0,
-                top: 0,
-                right: 800,
-                bottom: 600,
-            };
-
-            AdjustWindowRectEx(
-                &mut client_rect,
-                WS_OVERLAPPEDWINDOW,
-                false,
-                WINDOW_EX_STYLE::default(),
-            );
-
-            let dpi = GetDpiForWindow(handle) as i32;
-
-            self.client_area_width =
-                ((((client_rect.right - client_rect.left) * dpi) as f32) / 96.0).ceil() as i32;
-            self.client_area_height =
-                ((((client_rect.bottom - client_rect.top) * dpi) as f32) / 96.0).ceil() as i32;
-
-            SetWindowPos(
-                handle,
-                None,
-                CW_USEDEFAULT,
-                CW_USEDEFAULT,
-                self.client_area_width,
-                self.client_area_height,
-                SWP_NOMOVE,
-            );
-
-            self.graphics = Some(Graphics::new(self)?);
-
-            let mut message = MSG::default();
-
-            loop {
-                if let Some(graphics) = &self.graphics {
-                    self.alpha = self.timer.get_time(48) * std::f64::consts::PI / 3.;
-                    graphics.render(self.alpha)?;
-                    self.timer.update()?;
-                }
-
-                match PeekMessageA(&mut message, None, 0, 0, PM_REMOVE) {
-                    BOOL(0) => continue,
-                    BOOL(_) => (),
-                }
-
-                match message.message {
-                    WM_QUIT => return Ok(()),
-                    _ => DispatchMessageA(&message),
-                };
-            }
-        }
-    }
-
-    extern "system" fn wndproc(
-        window: HWND,
-        message: u32,
-        wparam: WPARAM,
-        lparam: LPARAM,
-    ) -> LRESULT {
-        unsafe {
-            match message {
-                WM_NCCREATE => {
-                    let this = {
-                        let cs = lparam.0 as *const CREATESTRUCTA;
-                        (*cs).lpCreateParams as *mut Self
-                    };
-
-                    (*this).handle = window;
-                    SetWindowLongPtrA(window, GWLP_USERDATA, this as _);
-                }
-                _ => {
-                    let this = GetWindowLongPtrA(window, GWLP_USERDATA) as *mut Self;
-
-                    if !this.is_null() {
-                        return (*this).message_handler(message, wparam, lparam);
-                    }
-                }
-            }
-
-            DefWindowProcA(window, message, wparam, lparam)
-        }
+    match RecordOptions::from_args(std::env::args().skip(1))? {
+        Some(options) => record(&options, config),
+        #[cfg(unix)]
+        None if common::tty::requested(std::env::args().skip(1)) => tty::run(&config),
+        None => open_window(config, config_file),
     }
 }
@@ -0,0 +1,91 @@
+use common::canvas::Canvas;
+use common::clock::Clock;
+use common::config::ConfigWatcher;
+use common::dpi::Dpi;
+use common::input::InputEvent;
+use common::playback::PlaybackClock;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::Result;
+use softapp::{App, Window, WindowBuilder};
+
+use plot_3d::config::Config;
+use plot_3d::graphics::{rotation_angle, Graphics, ROTATION_PERIOD};
+
+const TITLE: &str = "Animation Wave";
+
+struct AnimationWave {
+    graphics: Graphics<SoftwareCanvas>,
+    clock: PlaybackClock,
+    watcher: ConfigWatcher,
+}
+
+impl AnimationWave {
+    fn new(
+        window: &Window,
+        clock: Box<dyn Clock>,
+        config: Config,
+        watcher: ConfigWatcher,
+    ) -> Result<Self> {
+        let (width, height) = window.client_size();
+        let mut canvas = SoftwareCanvas::new(width, height)?;
+        canvas.set_dpi(window.dpi());
+
+        Ok(AnimationWave {
+            graphics: Graphics::new(canvas, config)?,
+            clock: PlaybackClock::new(clock, 60),
+            watcher,
+        })
+    }
+
+    /* Picks up edits of the configuration file between frames. */
+    fn reload_config(&mut self, window: &Window) -> Result<()> {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return Ok(());
+        };
+
+        let old = self.graphics.config();
+
+        if (config.width, config.height) != (old.width, old.height) {
+            window.set_client_size(config.width, config.height);
+        }
+
+        self.graphics.set_config(config)
+    }
+}
+
+impl App for AnimationWave {
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.graphics.canvas()
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        self.reload_config(window)?;
+
+        let alpha = rotation_angle(self.clock.get_time(ROTATION_PERIOD));
+        self.graphics.render(alpha)?;
+        self.clock.update()
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)
+    }
+
+    fn on_dpi_changed(&mut self, _window: &Window, dpi: Dpi) -> Result<()> {
+        self.graphics.set_dpi(dpi);
+        Ok(())
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.clock.playback.on_input(&event) {
+            window.set_title(&self.clock.playback.title(TITLE));
+        }
+    }
+}
+
+pub fn run(clock: Box<dyn Clock>, config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new(TITLE)
+        .client_size(config.width, config.height)
+        .target_fps(60)
+        .run(|window| AnimationWave::new(window, clock, config, watcher))
+}
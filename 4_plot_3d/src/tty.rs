@@ -0,0 +1,33 @@
+use common::braille::BrailleCanvas;
+use common::Result;
+
+use plot_3d::config::Config;
+use plot_3d::graphics::{rotation_angle, surface, Plot, ROTATION_PERIOD};
+
+const FPS: u32 = 30;
+
+/* Window pixels to dots: scaled to fit the terminal and centered, keeping
+the aspect ratio since braille dots are about square. */
+fn fit(canvas: &BrailleCanvas, config: &Config) -> impl Fn((f32, f32)) -> (f32, f32) {
+    let (width, height) = (canvas.width() as f32, canvas.height() as f32);
+    let scale = (width / config.width as f32).min(height / config.height as f32);
+    let x_offset = (width - config.width as f32 * scale) / 2.;
+    let y_offset = (height - config.height as f32 * scale) / 2.;
+
+    move |(x, y)| (x * scale + x_offset, y * scale + y_offset)
+}
+
+pub fn run(config: &Config) -> Result<()> {
+    let plot = Plot::get_initial_plot(surface, config);
+
+    common::tty::animate(FPS, |canvas, frame| {
+        let time = (frame as f64 / FPS as f64) % ROTATION_PERIOD as f64;
+        let to_dots = fit(canvas, config);
+
+        for line in plot.lines(rotation_angle(time)) {
+            for segment in line.windows(2) {
+                canvas.line(to_dots(segment[0]), to_dots(segment[1]));
+            }
+        }
+    })
+}
@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+use common::canvas::Canvas;
+use common::clock::Clock;
+use common::config::ConfigWatcher;
+use common::d2d::D2DCanvas;
+use common::dpi::Dpi;
+use common::hud::Hud;
+use common::input::{InputEvent, Key};
+use common::playback::PlaybackClock;
+use common::screenshot;
+use common::stats::FrameStats;
+use winapp::{App, Pacing, Window, WindowBuilder};
+use windows::{
+    core::*, Win32::Foundation::*, Win32::Graphics::Gdi::HDC, Win32::UI::WindowsAndMessaging::*,
+};
+
+use plot_3d::config::Config;
+use plot_3d::graphics::{rotation_angle, Graphics, ROTATION_PERIOD};
+
+const TITLE: &str = "Animation Wave";
+
+struct AnimationWave {
+    graphics: Graphics<D2DCanvas>,
+    clock: PlaybackClock,
+    watcher: ConfigWatcher,
+    hud: Hud,
+    alpha: f64,
+}
+
+impl AnimationWave {
+    fn new(
+        window: &Window,
+        clock: Box<dyn Clock>,
+        config: Config,
+        watcher: ConfigWatcher,
+    ) -> Result<Self> {
+        let (width, height) = window.client_size();
+        let mut canvas = D2DCanvas::with_vsync(window.handle(), width, height, window.vsync())?;
+        canvas.set_dpi(window.dpi());
+
+        Ok(AnimationWave {
+            graphics: Graphics::new(canvas, config)?,
+            clock: PlaybackClock::new(clock, 60),
+            watcher,
+            hud: Hud::default(),
+            alpha: 0.,
+        })
+    }
+
+    /* Draws the current frame again, offscreen, without the HUD. */
+    fn save_screenshot(&self, window: &Window) -> Result<PathBuf> {
+        let (width, height) = self.graphics.canvas().pixel_size();
+        let canvas = D2DCanvas::for_bitmap(width, height, window.dpi())?;
+
+        let mut graphics = self.graphics.snapshot(canvas)?;
+        graphics.render(self.alpha)?;
+
+        let path = screenshot::path("plot_3d");
+        graphics.canvas_mut().save_png(&path)?;
+        Ok(path)
+    }
+
+    /* Picks up edits of the configuration file between frames. */
+    fn reload_config(&mut self, window: &Window) -> Result<()> {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return Ok(());
+        };
+
+        let old = self.graphics.config();
+
+        if (config.width, config.height) != (old.width, old.height) {
+            /* The WM_SIZE this sends finds the app busy, so resize here. */
+            window.set_client_size(config.width, config.height)?;
+            let (width, height) = window.client_size();
+            self.graphics.resize(width, height)?;
+        }
+
+        self.graphics.set_config(config)?;
+        Ok(())
+    }
+}
+
+impl App for AnimationWave {
+    fn on_message(
+        &mut self,
+        window: &Window,
+        message: u32,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            /* Drawn again on the next WM_PAINT. */
+            WM_DISPLAYCHANGE => {
+                window.invalidate();
+                Some(LRESULT(0))
+            }
+            _ => None,
+        }
+    }
+
+    fn on_paint(&mut self, _window: &Window, _hdc: HDC) -> Result<()> {
+        self.graphics.render(self.alpha)?;
+        Ok(())
+    }
+
+    fn on_frame(&mut self, window: &Window, stats: &FrameStats) -> Result<()> {
+        self.reload_config(window)?;
+
+        let canvas = self.graphics.canvas_mut();
+        canvas.set_overlay(self.hud.text(stats, canvas.pixel_size(), window.dpi()));
+        self.alpha = rotation_angle(self.clock.get_time(ROTATION_PERIOD));
+        self.graphics.render(self.alpha)?;
+        self.clock.update()?;
+        Ok(())
+    }
+
+    fn on_dpi_changed(&mut self, _window: &Window, dpi: Dpi) -> Result<()> {
+        self.graphics.set_dpi(dpi);
+        Ok(())
+    }
+
+    fn is_occluded(&self) -> bool {
+        self.graphics.canvas().is_occluded()
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)?;
+        self.graphics.render(self.alpha)?;
+        Ok(())
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.clock.playback.on_input(&event) {
+            window.set_title(&self.clock.playback.title(TITLE));
+            return;
+        }
+
+        match event {
+            InputEvent::KeyDown {
+                key: Key::F(3),
+                repeat: false,
+                ..
+            } => self.hud.toggle(),
+            InputEvent::KeyDown {
+                key: Key::F(12),
+                repeat: false,
+                ..
+            } => match self.save_screenshot(window) {
+                Ok(path) => println!("saved {}", path.display()),
+                Err(error) => eprintln!("screenshot failed: {error}"),
+            },
+            _ => {}
+        }
+    }
+}
+
+pub fn run(clock: Box<dyn Clock>, config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new("AnimationWave")
+        .title(TITLE)
+        .cursor(IDC_HAND)
+        .client_size(config.width, config.height)
+        .pacing(Pacing::Continuous {
+            target_fps: 60,
+            vsync: true,
+        })
+        .remember_placement()
+        .run(|window| AnimationWave::new(window, clock, config, watcher))
+}
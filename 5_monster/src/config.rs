@@ -0,0 +1,127 @@
+use common::canvas::{Color, GradientStop};
+use serde::Deserialize;
+
+/// Loaded from `monster.toml`; see `monster.example.toml`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub width: i32,
+    pub height: i32,
+    pub background: Color,
+    pub outline_color: Color,
+    pub nose_color: Color,
+    pub eye_radius: f32,
+    pub ball_radius: f32,
+    pub outline_gradient: Vec<GradientStop>,
+    pub eye_gradient: Vec<GradientStop>,
+}
+
+fn stop(position: f32, r: f32, g: f32, b: f32) -> GradientStop {
+    GradientStop {
+        position,
+        color: Color::rgb(r, g, b),
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 1400,
+            height: 600,
+            background: Color::rgb(0.7, 0.7, 1.),
+            outline_color: Color::rgb(0., 0., 0.),
+            nose_color: Color::rgb(0.25, 0.25, 0.25),
+            eye_radius: 90.,
+            ball_radius: 30.,
+            outline_gradient: vec![
+                stop(0., 0.8, 1., 0.8),
+                stop(0.5, 0., 0.9, 0.),
+                stop(0.8, 0.1, 0.6, 0.1),
+                stop(1.0, 0.3, 0.4, 0.3),
+            ],
+            eye_gradient: vec![stop(0.7, 1., 1., 1.), stop(1.0, 0.7, 0.7, 0.7)],
+        }
+    }
+}
+
+fn validate_stops(key: &str, stops: &[GradientStop], problems: &mut Vec<String>) {
+    if stops.is_empty() {
+        problems.push(format!("{key} needs at least one stop"));
+    }
+
+    if let Some(stop) = stops
+        .iter()
+        .find(|stop| !(0. ..=1.).contains(&stop.position))
+    {
+        problems.push(format!(
+            "{key} positions must be in 0..=1, got {}",
+            stop.position
+        ));
+    }
+
+    if stops
+        .windows(2)
+        .any(|pair| pair[0].position > pair[1].position)
+    {
+        problems.push(format!("{key} positions must be in ascending order"));
+    }
+}
+
+impl common::config::Config for Config {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.width <= 0 || self.height <= 0 {
+            problems.push(format!(
+                "width and height must be positive, got {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        if !(self.ball_radius > 0. && self.ball_radius.is_finite()) {
+            problems.push(format!(
+                "ball_radius must be positive, got {}",
+                self.ball_radius
+            ));
+        }
+
+        /* The ball has to fit inside the eye it rolls in. */
+        if !(self.eye_radius > self.ball_radius && self.eye_radius.is_finite()) {
+            problems.push(format!(
+                "eye_radius must be larger than ball_radius ({}), got {}",
+                self.ball_radius, self.eye_radius
+            ));
+        }
+
+        validate_stops("outline_gradient", &self.outline_gradient, &mut problems);
+        validate_stops("eye_gradient", &self.eye_gradient, &mut problems);
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config;
+
+    #[test]
+    fn example_file_matches_defaults() {
+        let example = include_str!("../monster.example.toml");
+        assert_eq!(config::parse::<Config>(example), Ok(Config::default()));
+    }
+
+    #[test]
+    fn rejects_degenerate_values() {
+        let error = config::parse::<Config>(
+            "eye_radius = 20\n\
+             eye_gradient = [{ position = 1.0, color = \"#ffffff\" }, \
+                             { position = 0.5, color = \"#000000\" }]",
+        )
+        .unwrap_err();
+
+        assert!(error.contains("eye_radius"), "{error}");
+        assert!(error.contains("eye_gradient"), "{error}");
+        assert!(config::parse::<Config>("outline_gradient = []").is_err());
+    }
+}
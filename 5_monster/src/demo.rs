@@ -0,0 +1,140 @@
+use common::clock::{Clock, ManualClock};
+use common::demo::Demo;
+use common::dpi::Dpi;
+use common::input::{InputEvent, MouseButton};
+use common::software::SoftwareCanvas;
+use common::Result;
+
+use crate::config::Config;
+use crate::graphics::{swing_angle, Graphics, SWING_PERIOD};
+
+pub struct MonsterDemo {
+    graphics: Graphics<SoftwareCanvas>,
+    clock: ManualClock,
+    dpi: Dpi,
+    lbutton_up: bool,
+}
+
+impl MonsterDemo {
+    pub fn new(config: Config) -> Result<Self> {
+        let canvas = SoftwareCanvas::new(config.width as u32, config.height as u32)?;
+
+        Ok(MonsterDemo {
+            graphics: Graphics::new(canvas, config)?,
+            clock: ManualClock::new(),
+            dpi: Dpi::DEFAULT,
+            lbutton_up: false,
+        })
+    }
+
+    /// Scales the drawing, and the mouse positions coming in, like the
+    /// window does on a monitor of `dpi`.
+    pub fn set_dpi(&mut self, dpi: Dpi) {
+        self.dpi = dpi;
+        self.graphics.set_dpi(dpi);
+    }
+
+    pub fn graphics(&self) -> &Graphics<SoftwareCanvas> {
+        &self.graphics
+    }
+}
+
+impl Demo for MonsterDemo {
+    fn name(&self) -> &str {
+        "Monster"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.clock.advance(dt);
+    }
+
+    fn render(&mut self) -> Result<()> {
+        let alpha = swing_angle(self.clock.get_time(SWING_PERIOD));
+        self.graphics.render(alpha, self.lbutton_up)
+    }
+
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.graphics.canvas()
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)
+    }
+
+    fn on_input(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::MouseMove { x, y } => self.graphics.on_mouse_move(x, y, self.dpi),
+            InputEvent::MouseDown {
+                button: MouseButton::Left,
+                ..
+            } => self.lbutton_up = true,
+            InputEvent::MouseUp {
+                button: MouseButton::Left,
+                ..
+            }
+            | InputEvent::FocusLost => self.lbutton_up = false,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::canvas::Point;
+    use common::replay::{self, Frames, Replay};
+    use common::spy::Entry;
+    use std::path::Path;
+
+    fn pupils(demo: &MonsterDemo) -> [Point; 2] {
+        let (left, right) = demo.graphics().balls();
+        [left.point, right.point]
+    }
+
+    fn trace() -> Vec<Entry> {
+        replay::load(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/traces/look_between_the_eyes.jsonl"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn replayed_cursor_path() {
+        let trace = trace();
+        let mut demo = MonsterDemo::new(Config::default()).unwrap();
+        let mut replay = Replay::new(&mut demo, Frames::Continuous);
+
+        /* Up to the first paint after the cursor stops right between the
+        eyes: each pupil is at the edge of its orbit, facing the other. */
+        replay.run(&trace[..9]).unwrap();
+        assert_eq!(replay.time(), 0.72);
+        assert!(!replay.demo().lbutton_up);
+        assert_eq!(
+            pupils(replay.demo()),
+            [Point { x: -68., y: -84. }, Point { x: 64., y: -84. }]
+        );
+
+        /* The window narrows and the monster moves under the cursor, now
+        within reach of the right pupil. */
+        replay.run(&trace[9..]).unwrap();
+        assert_eq!(
+            pupils(replay.demo()),
+            [Point { x: -68., y: -84. }, Point { x: 98., y: -84. }]
+        );
+    }
+
+    /* The same trace on a 150% monitor: as many pixels are fewer DIPs, so
+    the cursor stops below the eyes and the pupils look down at it. */
+    #[test]
+    fn replayed_at_144_dpi() {
+        let mut demo = MonsterDemo::new(Config::default()).unwrap();
+        demo.set_dpi(Dpi(144));
+
+        let trace = trace();
+        let mut replay = Replay::new(&mut demo, Frames::Continuous);
+        replay.run(&trace[..9]).unwrap();
+
+        let pupils = pupils(replay.demo()).map(|point| (point.x.round(), point.y.round()));
+        assert_eq!(pupils, [(-69., -71.), (65., -71.)]);
+    }
+}
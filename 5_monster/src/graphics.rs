@@ -0,0 +1,601 @@
+use common::canvas::*;
+use common::dpi::Dpi;
+use common::{Error, Result};
+
+use crate::config::Config;
+
+/* The eyes swing back and forth once per this many seconds. */
+pub const SWING_PERIOD: u32 = 2;
+
+pub fn swing_angle(time: f64) -> f64 {
+    (time * std::f64::consts::PI).sin() * 10.
+}
+
+/* Centers of the eyes, in the coordinates of the original drawing moved so
+that the monster is centered on (716, 294). */
+const EYE_Y: f32 = 210. - 294.;
+const EYE_L_X: f32 = 588. - 716.;
+const EYE_R_X: f32 = 840. - 716.;
+
+/* Path geometries belong to the factory and survive a lost device. */
+struct Geometries<C: Canvas> {
+    outline_path: C::Geometry,
+    nose_path: C::Geometry,
+    nosmile_path: C::Geometry,
+    smile_path: C::Geometry,
+}
+
+/* Brushes belong to the render target and are rebuilt together with it. */
+struct DeviceResources<C: Canvas> {
+    brush: C::Brush,
+    nosebrush: C::Brush,
+    outline_gradient: C::Brush,
+    left_eye_gradient: C::Brush,
+    right_eye_gradient: C::Brush,
+}
+
+pub struct Graphics<C: Canvas> {
+    canvas: C,
+    config: Config,
+    geometries: Geometries<C>,
+    resources: DeviceResources<C>,
+    mouse_pos: (f32, f32),
+}
+
+impl<C: Canvas> Geometries<C> {
+    fn new(canvas: &mut C) -> Result<Self> {
+        Ok(Geometries {
+            outline_path: canvas.create_geometry(&Graphics::<C>::draw_monster_outline())?,
+            nose_path: canvas.create_geometry(&Graphics::<C>::draw_monster_nose())?,
+            nosmile_path: canvas.create_geometry(&Graphics::<C>::draw_monster_nosmile())?,
+            smile_path: canvas.create_geometry(&Graphics::<C>::draw_monster_smile())?,
+        })
+    }
+}
+
+impl<C: Canvas> DeviceResources<C> {
+    fn new(canvas: &mut C, config: &Config) -> Result<Self> {
+        Ok(DeviceResources {
+            brush: canvas.create_solid_brush(config.outline_color)?,
+            nosebrush: canvas.create_solid_brush(config.nose_color)?,
+            outline_gradient: Graphics::create_outline_gradient_brush(canvas, config)?,
+            left_eye_gradient: Graphics::create_left_eye_gradient_brush(canvas, config)?,
+            right_eye_gradient: Graphics::create_right_eye_gradient_brush(canvas, config)?,
+        })
+    }
+}
+
+impl<C: Canvas> Graphics<C> {
+    fn draw_monster_nosmile() -> Path {
+        let mut sink = PathBuilder::new();
+
+        sink.begin_figure(Point {
+            x: 616.00 - 716.00,
+            y: 427.50 - 294.00,
+        });
+        sink.add_arc(ArcSegment {
+            point: Point {
+                x: 816.00 - 716.00,
+                y: 427.50 - 294.00,
+            },
+            rotation_angle: 0.,
+            size: Size {
+                width: 10.,
+                height: 2.,
+            },
+            sweep_direction: SweepDirection::Clockwise,
+            arc_size: ArcSize::Small,
+        });
+        sink.end_figure(FigureEnd::Open);
+
+        sink.close()
+    }
+
+    fn draw_monster_smile() -> Path {
+        let mut sink = PathBuilder::new();
+
+        sink.begin_figure(Point {
+            x: 616.00 - 716.00,
+            y: 427.50 - 294.00,
+        });
+        sink.add_arc(ArcSegment {
+            point: Point {
+                x: 816.00 - 716.00,
+                y: 427.50 - 294.00,
+            },
+            rotation_angle: 0.,
+            size: Size {
+                width: 10.,
+                height: 7.,
+            },
+            sweep_direction: SweepDirection::CounterClockwise,
+            arc_size: ArcSize::Small,
+        });
+        sink.end_figure(FigureEnd::Open);
+
+        sink.close()
+    }
+
+    fn draw_monster_nose() -> Path {
+        let mut sink = PathBuilder::new();
+
+        sink.begin_figure(Point {
+            x: 649.95 - 716.00,
+            y: 333.04 - 294.00,
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 649.95 - 716.00,
+                y: 333.04 - 294.00,
+            },
+            point2: Point {
+                x: 679.40 - 716.00,
+                y: 382.37 - 294.00,
+            },
+            point3: Point {
+                x: 713.28 - 716.00,
+                y: 383.71 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 752.93 - 716.00,
+                y: 381.21 - 294.00,
+            },
+            point2: Point {
+                x: 774.51 - 716.00,
+                y: 337.71 - 294.00,
+            },
+            point3: Point {
+                x: 778.78 - 716.00,
+                y: 329.96 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 778.78 - 716.00,
+                y: 329.96 - 294.00,
+            },
+            point2: Point {
+                x: 789.32 - 716.00,
+                y: 295.96 - 294.00,
+            },
+            point3: Point {
+                x: 715.79 - 716.00,
+                y: 291.21 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 635.73 - 716.00,
+                y: 293.71 - 294.00,
+            },
+            point2: Point {
+                x: 649.95 - 716.00,
+                y: 333.04 - 294.00,
+            },
+            point3: Point {
+                x: 649.95 - 716.00,
+                y: 333.04 - 294.00,
+            },
+        });
+        sink.end_figure(FigureEnd::Closed);
+
+        sink.close()
+    }
+
+    fn get_eye(&self, x: f32, y: f32) -> Ellipse {
+        Ellipse {
+            point: Point { x, y },
+            radius_x: self.config.eye_radius,
+            radius_y: self.config.eye_radius,
+        }
+    }
+
+    fn get_ball(&self, eye: (f32, f32), translation: (f32, f32)) -> Ellipse {
+        let (curbias_x, curbias_y) = self.mouse_pos;
+        let (translation_x, translation_y) = translation;
+        let (cursor_x, cursor_y) = (curbias_x - translation_x, curbias_y - translation_y);
+        let (eye_x, eye_y) = eye;
+        let (currel_x, currel_y) = (cursor_x - eye_x, cursor_y - eye_y);
+
+        let ball_orbit = self.config.eye_radius - self.config.ball_radius;
+
+        let cursor_formula = currel_x * currel_x + currel_y * currel_y;
+
+        let (ball_x, ball_y) = {
+            if cursor_formula <= ball_orbit * ball_orbit {
+                (cursor_x, cursor_y)
+            } else {
+                let factor = ball_orbit / cursor_formula.sqrt();
+                (currel_x * factor + eye_x, currel_y * factor + eye_y)
+            }
+        };
+
+        Ellipse {
+            point: Point {
+                x: ball_x,
+                y: ball_y,
+            },
+            radius_x: self.config.ball_radius,
+            radius_y: self.config.ball_radius,
+        }
+    }
+
+    fn draw_monster_outline() -> Path {
+        let mut sink = PathBuilder::new();
+
+        sink.begin_figure(Point {
+            x: 837.50 - 716.00,
+            y: 83.06 - 294.00,
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 837.50 - 716.00,
+                y: 83.06 - 294.00,
+            },
+            point2: Point {
+                x: 888.50 - 716.00,
+                y: -9. - 294.0001,
+            },
+            point3: Point {
+                x: 965.50 - 716.00,
+                y: 54.54 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 965.50 - 716.00,
+                y: 54.54 - 294.00,
+            },
+            point2: Point {
+                x: 1035.00 - 716.00,
+                y: 115.09 - 294.00,
+            },
+            point3: Point {
+                x: 911.50 - 716.00,
+                y: 205.15 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 898.50 - 716.00,
+                y: 231.17 - 294.00,
+            },
+            point2: Point {
+                x: 1028.50 - 716.00,
+                y: 415.81 - 294.00,
+            },
+            point3: Point {
+                x: 963.50 - 716.00,
+                y: 513.38 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 929.00 - 716.00,
+                y: 563.92 - 294.00,
+            },
+            point2: Point {
+                x: 859.50 - 716.00,
+                y: 585.94 - 294.00,
+            },
+            point3: Point {
+                x: 726.50 - 716.00,
+                y: 586.44 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 610.50 - 716.00,
+                y: 584.43 - 294.00,
+            },
+            point2: Point {
+                x: 493.50 - 716.00,
+                y: 587.44 - 294.00,
+            },
+            point3: Point {
+                x: 454.50 - 716.00,
+                y: 491.37 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 422.31 - 716.00,
+                y: 419.50 - 294.00,
+            },
+            point2: Point {
+                x: 492.00 - 716.00,
+                y: 295.22 - 294.00,
+            },
+            point3: Point {
+                x: 516.00 - 716.00,
+                y: 205.15 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 379.50 - 716.00,
+                y: 99.07 - 294.00,
+            },
+            point2: Point {
+                x: 474.52 - 716.00,
+                y: 44.37 - 294.00,
+            },
+            point3: Point {
+                x: 473.50 - 716.00,
+                y: 45.03 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 545.33 - 716.00,
+                y: 2.17 - 294.00,
+            },
+            point2: Point {
+                x: 585.22 - 716.00,
+                y: 77.69 - 294.00,
+            },
+            point3: Point {
+                x: 587.50 - 716.00,
+                y: 82.56 - 294.00,
+            },
+        });
+        sink.add_bezier(BezierSegment {
+            point1: Point {
+                x: 587.50 - 716.00,
+                y: 82.56 - 294.00,
+            },
+            point2: Point {
+                x: 702.17 - 716.00,
+                y: -21. - 294.0035,
+            },
+            point3: Point {
+                x: 837.50 - 716.00,
+                y: 83.06 - 294.00,
+            },
+        });
+        sink.end_figure(FigureEnd::Closed);
+
+        sink.close()
+    }
+
+    fn create_outline_gradient_brush(canvas: &mut C, config: &Config) -> Result<C::Brush> {
+        let ellipse_center = Point { x: 0.00, y: 30.00 };
+
+        canvas.create_radial_gradient_brush(&RadialGradient {
+            center: ellipse_center,
+            radius_x: 340.,
+            radius_y: 380.,
+            gradient_origin_offset: Point { x: 0., y: 0. },
+            stops: config.outline_gradient.clone(),
+        })
+    }
+
+    fn create_left_eye_gradient_brush(canvas: &mut C, config: &Config) -> Result<C::Brush> {
+        Self::create_eye_gradient_brush(canvas, config, EYE_L_X)
+    }
+
+    fn create_right_eye_gradient_brush(canvas: &mut C, config: &Config) -> Result<C::Brush> {
+        Self::create_eye_gradient_brush(canvas, config, EYE_R_X)
+    }
+
+    fn create_eye_gradient_brush(canvas: &mut C, config: &Config, x: f32) -> Result<C::Brush> {
+        let ellipse_center = Point { x, y: EYE_Y };
+
+        canvas.create_radial_gradient_brush(&RadialGradient {
+            center: ellipse_center,
+            radius_x: config.eye_radius,
+            radius_y: config.eye_radius,
+            gradient_origin_offset: Point { x: 0., y: 0. },
+            stops: config.eye_gradient.clone(),
+        })
+    }
+
+    pub fn render(&mut self, alpha: f64, lbutton_up: bool) -> Result<()> {
+        self.draw(alpha, lbutton_up);
+
+        match self.canvas.end_draw() {
+            /* The frame is lost; the next one draws with the new target. */
+            Err(Error::DeviceLost) => {
+                self.canvas.recreate_target()?;
+                self.resources = DeviceResources::new(&mut self.canvas, &self.config)?;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn draw(&mut self, alpha: f64, lbutton_up: bool) {
+        self.canvas.begin_draw();
+        self.canvas.clear(self.config.background);
+
+        let translation = self.translation();
+
+        let translation_matrix = Matrix::translation(translation.0, translation.1);
+        let rotation_matrix = Matrix::rotation(alpha as f32, 0., 0.) * translation_matrix;
+
+        let left_eye = self.get_eye(EYE_L_X, EYE_Y);
+        let right_eye = self.get_eye(EYE_R_X, EYE_Y);
+
+        let (left_ball, right_ball) = self.balls();
+
+        let canvas = &mut self.canvas;
+        let geometries = &self.geometries;
+        let resources = &self.resources;
+
+        canvas.set_transform(&translation_matrix);
+        canvas.fill_geometry(&geometries.outline_path, &resources.outline_gradient);
+        canvas.draw_geometry(&geometries.outline_path, &resources.brush, 5.);
+
+        canvas.fill_ellipse(&left_eye, &resources.left_eye_gradient);
+        canvas.fill_ellipse(&right_eye, &resources.right_eye_gradient);
+
+        canvas.draw_ellipse(&left_eye, &resources.brush, 2.);
+        canvas.draw_ellipse(&right_eye, &resources.brush, 2.);
+
+        canvas.fill_ellipse(&left_ball, &resources.brush);
+        canvas.fill_ellipse(&right_ball, &resources.brush);
+
+        canvas.set_transform(&rotation_matrix);
+        canvas.fill_geometry(&geometries.nose_path, &resources.nosebrush);
+        canvas.draw_geometry(&geometries.nose_path, &resources.brush, 2.);
+
+        let mouth = match lbutton_up {
+            false => &geometries.nosmile_path,
+            true => &geometries.smile_path,
+        };
+
+        canvas.draw_geometry(mouth, &resources.brush, 7.);
+    }
+
+    /* The monster is drawn around the center of the canvas. */
+    fn translation(&self) -> (f32, f32) {
+        let size = self.canvas.size();
+        (size.width / 2., size.height / 2.)
+    }
+
+    /// The left and right pupil, looking at the mouse, relative to the
+    /// center of the canvas.
+    pub fn balls(&self) -> (Ellipse, Ellipse) {
+        let translation = self.translation();
+
+        (
+            self.get_ball((EYE_L_X, EYE_Y), translation),
+            self.get_ball((EYE_R_X, EYE_Y), translation),
+        )
+    }
+
+    pub fn canvas(&self) -> &C {
+        &self.canvas
+    }
+
+    pub fn canvas_mut(&mut self) -> &mut C {
+        &mut self.canvas
+    }
+
+    /* The same scene drawn into another canvas, e.g. for a screenshot. */
+    pub fn snapshot<D: Canvas>(&self, canvas: D) -> Result<Graphics<D>> {
+        let mut graphics = Graphics::new(canvas, self.config.clone())?;
+        graphics.mouse_pos = self.mouse_pos;
+        Ok(graphics)
+    }
+
+    pub fn new(mut canvas: C, config: Config) -> Result<Self> {
+        let geometries = Geometries::new(&mut canvas)?;
+        let resources = DeviceResources::new(&mut canvas, &config)?;
+
+        Ok(Graphics {
+            canvas,
+            config,
+            geometries,
+            resources,
+            mouse_pos: (0., 0.),
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /* The brushes carry the colors and gradient stops, so a reloaded
+    configuration rebuilds them. */
+    pub fn set_config(&mut self, config: Config) -> Result<()> {
+        self.resources = DeviceResources::new(&mut self.canvas, &config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    /* Only the window reacts to resizing and the mouse. */
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+
+    pub fn set_dpi(&mut self, dpi: Dpi) {
+        self.canvas.set_dpi(dpi);
+    }
+
+    /// Follows the cursor at a client position in physical pixels; the
+    /// scene is laid out in DIPs at `dpi`.
+    pub fn on_mouse_move(&mut self, x: i32, y: i32, dpi: Dpi) {
+        self.mouse_pos = (dpi.to_dips(x as f32), dpi.to_dips(y as f32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::clock::{Clock, ManualClock};
+    use common::display_list::{DisplayListCanvas, Operation};
+    use common::golden;
+    use common::software::SoftwareCanvas;
+    use common::svg::SvgCanvas;
+    use std::path::Path;
+
+    #[test]
+    fn swing_peaks_at_quarter_periods() {
+        let mut clock = ManualClock::new();
+
+        clock.set(0.5);
+        assert!((swing_angle(clock.get_time(SWING_PERIOD)) - 10.).abs() < 1e-9);
+
+        clock.set(1.5);
+        assert!((swing_angle(clock.get_time(SWING_PERIOD)) + 10.).abs() < 1e-9);
+
+        clock.set(SWING_PERIOD as f64 + 0.5);
+        assert!((swing_angle(clock.get_time(SWING_PERIOD)) - 10.).abs() < 1e-9);
+    }
+
+    fn assert_golden(mouse_pos: (i32, i32), alpha: f64, lbutton_up: bool, name: &str) {
+        let canvas = SoftwareCanvas::new(1400, 600).unwrap();
+        let mut graphics = Graphics::new(canvas, Config::default()).unwrap();
+        graphics.on_mouse_move(mouse_pos.0, mouse_pos.1, Dpi::DEFAULT);
+        graphics.render(alpha, lbutton_up).unwrap();
+
+        let reference = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(name);
+        golden::assert_matches(graphics.canvas(), &reference, 2);
+    }
+
+    #[test]
+    fn golden_eyes_follow_mouse() {
+        assert_golden((1100, 120), 4., false, "monster_mouse_top_right.png");
+    }
+
+    #[test]
+    fn golden_smile() {
+        assert_golden((300, 500), -7., true, "monster_smile.png");
+    }
+
+    #[test]
+    fn svg_keeps_curves_and_gradients() {
+        let mut graphics = Graphics::new(SvgCanvas::new(1400, 600), Config::default()).unwrap();
+        graphics.render(4., false).unwrap();
+
+        let svg = graphics.canvas().to_svg();
+        assert_eq!(svg.matches("<radialGradient ").count(), 3);
+        assert!(svg.contains(r#"fill="url(#gradient1)""#));
+        assert!(svg.contains(" C"), "Bezier outline");
+        assert!(svg.contains(" A"), "mouth arc");
+    }
+
+    fn mouth(lbutton_up: bool) -> common::canvas::Path {
+        let canvas = DisplayListCanvas::new(1400, 600);
+        let mut graphics = Graphics::new(canvas, Config::default()).unwrap();
+        graphics.render(0., lbutton_up).unwrap();
+
+        match &graphics.canvas().commands().last().unwrap().operation {
+            Operation::DrawGeometry { geometry, .. } => geometry.clone(),
+            operation => panic!("the mouth is drawn last, got {operation:?}"),
+        }
+    }
+
+    #[test]
+    fn smiles_while_the_button_is_down() {
+        type G = Graphics<DisplayListCanvas>;
+
+        assert_eq!(mouth(true), G::draw_monster_smile());
+        assert_eq!(mouth(false), G::draw_monster_nosmile());
+    }
+}
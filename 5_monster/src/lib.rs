@@ -0,0 +1,6 @@
+//! Drawing and configuration of the monster demo, shared by its executable
+//! and the gallery.
+
+pub mod config;
+pub mod demo;
+pub mod graphics;
@@ -0,0 +1,110 @@
+use common::canvas::Canvas;
+use common::clock::Clock;
+use common::config::ConfigWatcher;
+use common::dpi::Dpi;
+use common::input::{InputEvent, MouseButton};
+use common::playback::PlaybackClock;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::Result;
+use softapp::{App, Window, WindowBuilder};
+
+use monster::config::Config;
+use monster::graphics::{swing_angle, Graphics, SWING_PERIOD};
+
+const TITLE: &str = "Monster likes when you click the mouse button";
+
+struct Monster {
+    graphics: Graphics<SoftwareCanvas>,
+    clock: PlaybackClock,
+    watcher: ConfigWatcher,
+    lbutton_up: bool,
+}
+
+impl Monster {
+    fn new(
+        window: &Window,
+        clock: Box<dyn Clock>,
+        config: Config,
+        watcher: ConfigWatcher,
+    ) -> Result<Self> {
+        let (width, height) = window.client_size();
+        let mut canvas = SoftwareCanvas::new(width, height)?;
+        canvas.set_dpi(window.dpi());
+
+        Ok(Monster {
+            graphics: Graphics::new(canvas, config)?,
+            clock: PlaybackClock::new(clock, 60),
+            watcher,
+            lbutton_up: false,
+        })
+    }
+
+    /* Picks up edits of the configuration file between frames. */
+    fn reload_config(&mut self, window: &Window) -> Result<()> {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return Ok(());
+        };
+
+        let old = self.graphics.config();
+
+        if (config.width, config.height) != (old.width, old.height) {
+            window.set_client_size(config.width, config.height);
+        }
+
+        self.graphics.set_config(config)
+    }
+}
+
+impl App for Monster {
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.graphics.canvas()
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        self.reload_config(window)?;
+
+        let alpha = swing_angle(self.clock.get_time(SWING_PERIOD));
+        self.graphics.render(alpha, self.lbutton_up)?;
+        self.clock.update()
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)
+    }
+
+    fn on_dpi_changed(&mut self, _window: &Window, dpi: Dpi) -> Result<()> {
+        self.graphics.set_dpi(dpi);
+        Ok(())
+    }
+
+    /* X11 grabs the pointer while a button is held, so the release arrives
+    even outside the window. */
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.clock.playback.on_input(&event) {
+            window.set_title(&self.clock.playback.title(TITLE));
+            return;
+        }
+
+        match event {
+            InputEvent::MouseMove { x, y } => self.graphics.on_mouse_move(x, y, window.dpi()),
+            InputEvent::MouseDown {
+                button: MouseButton::Left,
+                ..
+            } => self.lbutton_up = true,
+            InputEvent::MouseUp {
+                button: MouseButton::Left,
+                ..
+            }
+            | InputEvent::FocusLost => self.lbutton_up = false,
+            _ => {}
+        }
+    }
+}
+
+pub fn run(clock: Box<dyn Clock>, config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new(TITLE)
+        .client_size(config.width, config.height)
+        .target_fps(60)
+        .run(|window| Monster::new(window, clock, config, watcher))
+}
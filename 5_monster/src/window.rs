@@ -0,0 +1,174 @@
+use std::path::PathBuf;
+
+use common::canvas::Canvas;
+use common::clock::Clock;
+use common::config::ConfigWatcher;
+use common::d2d::D2DCanvas;
+use common::dpi::Dpi;
+use common::hud::Hud;
+use common::input::{InputEvent, Key, MouseButton};
+use common::playback::PlaybackClock;
+use common::screenshot;
+use common::stats::FrameStats;
+use winapp::{App, Pacing, Window, WindowBuilder};
+use windows::{
+    core::*,
+    Win32::Graphics::Gdi::HDC,
+    Win32::UI::Input::KeyboardAndMouse::{ReleaseCapture, SetCapture},
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use monster::config::Config;
+use monster::graphics::{swing_angle, Graphics, SWING_PERIOD};
+
+const TITLE: &str = "Monster likes when you click the mouse button";
+
+struct Monster {
+    graphics: Graphics<D2DCanvas>,
+    clock: PlaybackClock,
+    watcher: ConfigWatcher,
+    hud: Hud,
+    alpha: f64,
+    lbutton_up: bool,
+}
+
+impl Monster {
+    fn new(
+        window: &Window,
+        clock: Box<dyn Clock>,
+        config: Config,
+        watcher: ConfigWatcher,
+    ) -> Result<Self> {
+        let (width, height) = window.client_size();
+        let mut canvas = D2DCanvas::with_vsync(window.handle(), width, height, window.vsync())?;
+        canvas.set_dpi(window.dpi());
+
+        Ok(Monster {
+            graphics: Graphics::new(canvas, config)?,
+            clock: PlaybackClock::new(clock, 60),
+            watcher,
+            hud: Hud::default(),
+            alpha: 0.,
+            lbutton_up: false,
+        })
+    }
+
+    /* Draws the current frame again, offscreen, without the HUD. */
+    fn save_screenshot(&self, window: &Window) -> Result<PathBuf> {
+        let (width, height) = self.graphics.canvas().pixel_size();
+        let canvas = D2DCanvas::for_bitmap(width, height, window.dpi())?;
+
+        let mut graphics = self.graphics.snapshot(canvas)?;
+        graphics.render(self.alpha, self.lbutton_up)?;
+
+        let path = screenshot::path("monster");
+        graphics.canvas_mut().save_png(&path)?;
+        Ok(path)
+    }
+
+    /* Picks up edits of the configuration file between frames. */
+    fn reload_config(&mut self, window: &Window) -> Result<()> {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return Ok(());
+        };
+
+        let old = self.graphics.config();
+
+        if (config.width, config.height) != (old.width, old.height) {
+            /* The WM_SIZE this sends finds the app busy, so resize here. */
+            window.set_client_size(config.width, config.height)?;
+            let (width, height) = window.client_size();
+            self.graphics.resize(width, height)?;
+        }
+
+        self.graphics.set_config(config)?;
+        Ok(())
+    }
+}
+
+impl App for Monster {
+    fn on_paint(&mut self, _window: &Window, _hdc: HDC) -> Result<()> {
+        self.graphics.render(self.alpha, self.lbutton_up)?;
+        Ok(())
+    }
+
+    fn on_frame(&mut self, window: &Window, stats: &FrameStats) -> Result<()> {
+        self.reload_config(window)?;
+
+        let canvas = self.graphics.canvas_mut();
+        canvas.set_overlay(self.hud.text(stats, canvas.pixel_size(), window.dpi()));
+        self.alpha = swing_angle(self.clock.get_time(SWING_PERIOD));
+
+        self.graphics.render(self.alpha, self.lbutton_up)?;
+        self.clock.update()?;
+        Ok(())
+    }
+
+    fn on_dpi_changed(&mut self, _window: &Window, dpi: Dpi) -> Result<()> {
+        self.graphics.set_dpi(dpi);
+        Ok(())
+    }
+
+    fn is_occluded(&self) -> bool {
+        self.graphics.canvas().is_occluded()
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)?;
+        Ok(())
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.clock.playback.on_input(&event) {
+            window.set_title(&self.clock.playback.title(TITLE));
+            return;
+        }
+
+        match event {
+            InputEvent::MouseMove { x, y } => self.graphics.on_mouse_move(x, y, window.dpi()),
+            /* Captured, so that the release is seen outside the window too. */
+            InputEvent::MouseDown {
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.lbutton_up = true;
+                unsafe { SetCapture(window.handle()) };
+            }
+            InputEvent::MouseUp {
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.lbutton_up = false;
+                unsafe { ReleaseCapture() };
+            }
+            InputEvent::FocusLost => self.lbutton_up = false,
+            InputEvent::KeyDown {
+                key: Key::F(3),
+                repeat: false,
+                ..
+            } => self.hud.toggle(),
+            InputEvent::KeyDown {
+                key: Key::F(12),
+                repeat: false,
+                ..
+            } => match self.save_screenshot(window) {
+                Ok(path) => println!("saved {}", path.display()),
+                Err(error) => eprintln!("screenshot failed: {error}"),
+            },
+            _ => {}
+        }
+    }
+}
+
+pub fn run(clock: Box<dyn Clock>, config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new("Monster")
+        .title(TITLE)
+        .cursor(IDC_HAND)
+        .client_size(config.width, config.height)
+        .pacing(Pacing::Continuous {
+            target_fps: 60,
+            vsync: true,
+        })
+        .remember_placement()
+        .run(|window| Monster::new(window, clock, config, watcher))
+}
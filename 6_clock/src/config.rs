@@ -0,0 +1,96 @@
+use common::canvas::Color;
+use serde::Deserialize;
+
+/// Loaded from `clock.toml`; see `clock.example.toml`. Lengths are in
+/// pixels of the `Digits.png` and `Watch.png` sprites.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub width: i32,
+    pub height: i32,
+    pub background: Color,
+    pub tilt: f32,
+    pub digit_width: f32,
+    pub digit_opacity: f32,
+    pub separator_width: f32,
+    pub watch_vertical_margin: f32,
+    pub watch_horizontal_margin: f32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: 1400,
+            height: 600,
+            background: Color::rgb(0.7, 0.7, 1.),
+            tilt: -7.2,
+            digit_width: 108.,
+            digit_opacity: 0.7,
+            separator_width: 100.,
+            watch_vertical_margin: 104.,
+            watch_horizontal_margin: 119.,
+        }
+    }
+}
+
+impl common::config::Config for Config {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.width <= 0 || self.height <= 0 {
+            problems.push(format!(
+                "width and height must be positive, got {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        for (key, value) in [
+            ("digit_width", self.digit_width),
+            ("separator_width", self.separator_width),
+        ] {
+            if !(value > 0. && value.is_finite()) {
+                problems.push(format!("{key} must be positive, got {value}"));
+            }
+        }
+
+        if !(0. ..=1.).contains(&self.digit_opacity) {
+            problems.push(format!(
+                "digit_opacity must be in 0..=1, got {}",
+                self.digit_opacity
+            ));
+        }
+
+        for (key, value) in [
+            ("tilt", self.tilt),
+            ("watch_vertical_margin", self.watch_vertical_margin),
+            ("watch_horizontal_margin", self.watch_horizontal_margin),
+        ] {
+            if !value.is_finite() {
+                problems.push(format!("{key} must be a number, got {value}"));
+            }
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config;
+
+    #[test]
+    fn example_file_matches_defaults() {
+        let example = include_str!("../clock.example.toml");
+        assert_eq!(config::parse::<Config>(example), Ok(Config::default()));
+    }
+
+    #[test]
+    fn rejects_degenerate_values() {
+        let error = config::parse::<Config>("digit_width = 0\ndigit_opacity = 1.5").unwrap_err();
+
+        assert!(error.contains("digit_width"), "{error}");
+        assert!(error.contains("digit_opacity"), "{error}");
+        assert!(config::parse::<Config>("watch_margin = 3").is_err());
+    }
+}
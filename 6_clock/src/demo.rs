@@ -0,0 +1,56 @@
+use chrono::NaiveTime;
+use common::clock::{Clock, ManualClock};
+use common::demo::Demo;
+use common::software::SoftwareCanvas;
+use common::Result;
+
+use crate::config::Config;
+use crate::graphics::{accelerated_time, get_random_time, Graphics, SECS_IN_DAY, STEPS_PER_SECOND};
+
+pub struct ClockDemo {
+    graphics: Graphics<SoftwareCanvas>,
+    clock: ManualClock,
+    time: NaiveTime,
+}
+
+impl ClockDemo {
+    /// Loads the digit and watch sprites from the working directory, like
+    /// the clock window.
+    pub fn new(config: Config) -> Result<Self> {
+        let canvas = SoftwareCanvas::new(config.width as u32, config.height as u32)?;
+
+        Ok(ClockDemo {
+            graphics: Graphics::new(canvas, config)?,
+            clock: ManualClock::new(),
+            time: get_random_time(),
+        })
+    }
+}
+
+impl Demo for ClockDemo {
+    fn name(&self) -> &str {
+        "Clock"
+    }
+
+    fn update(&mut self, dt: f64) {
+        self.clock.advance(dt);
+    }
+
+    fn render(&mut self) -> Result<()> {
+        let timer_time = self.clock.get_time(SECS_IN_DAY / 4);
+        let (time, draw_separator) = accelerated_time(&self.time, timer_time);
+        self.graphics.render(&time, draw_separator)
+    }
+
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.graphics.canvas()
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)
+    }
+
+    fn steps_per_second(&self) -> u32 {
+        STEPS_PER_SECOND
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, NaiveTime, Timelike};
+use common::canvas::{Canvas, Matrix, Rect};
+use common::dpi::Dpi;
+use common::{Error, Result};
+use rand::Rng;
+
+use crate::config::Config;
+
+pub const SECS_IN_DAY: u32 = 60 * 60 * 24;
+/// A single step of playback moves the accelerated clock on by one second.
+pub const STEPS_PER_SECOND: u32 = 4;
+
+/* The clock runs four times faster than real time, starting at `start`.
+Returns the time to show and whether the separator is lit. */
+pub fn accelerated_time(start: &NaiveTime, timer_time: f64) -> (NaiveTime, bool) {
+    let additional_seconds = (timer_time * 4.) as i64;
+    let additional_duration = Duration::seconds(additional_seconds);
+    let (total_time, _) = start.overflowing_add_signed(additional_duration);
+
+    (total_time, additional_seconds % 2 == 0)
+}
+
+/* Where a window starts counting; recordings use a fixed time instead. */
+pub fn get_random_time() -> NaiveTime {
+    let secs = rand::thread_rng().gen_range(0..SECS_IN_DAY);
+    NaiveTime::from_num_seconds_from_midnight_opt(secs, 0).unwrap()
+}
+
+/* Bitmaps belong to the render target and are reloaded together with it. */
+struct DeviceResources<C: Canvas> {
+    digits_bitmap: C::Bitmap,
+    watch_bitmap: C::Bitmap,
+}
+
+impl<C: Canvas> DeviceResources<C> {
+    fn new(canvas: &mut C, assets: &Path) -> Result<Self> {
+        Ok(DeviceResources {
+            digits_bitmap: canvas.load_bitmap(&assets.join("Digits.png"))?,
+            watch_bitmap: canvas.load_bitmap(&assets.join("Watch.png"))?,
+        })
+    }
+}
+
+pub struct Graphics<C: Canvas> {
+    canvas: C,
+    config: Config,
+    assets: PathBuf,
+    resources: DeviceResources<C>,
+}
+
+impl<C: Canvas> Graphics<C> {
+    fn get_translation_for_bitmap_centering(&self, bmp: &C::Bitmap) -> Matrix {
+        let translation = {
+            let scrsize = self.canvas.size();
+            let bmpsize = self.canvas.bitmap_size(bmp);
+            (
+                (scrsize.width - bmpsize.width) / 2.,
+                (scrsize.height - bmpsize.height) / 2.,
+            )
+        };
+
+        Matrix::translation(translation.0, translation.1)
+    }
+
+    fn get_translation_for_bitmap_rotation(&self, bmp: &C::Bitmap, angle: f32) -> Matrix {
+        let bmpsize = self.canvas.bitmap_size(bmp);
+        Matrix::rotation(angle, bmpsize.width / 2., bmpsize.height / 2.)
+    }
+
+    fn draw_watch(&mut self) {
+        self.canvas
+            .draw_bitmap(&self.resources.watch_bitmap, None, 1., None);
+    }
+
+    fn draw_separator(&mut self) {
+        let digits_width = self.canvas.bitmap_size(&self.resources.digits_bitmap).width;
+        let digits_height = self
+            .canvas
+            .bitmap_size(&self.resources.digits_bitmap)
+            .height;
+
+        let separator_width = self.config.separator_width;
+
+        let source_rect = {
+            let separator_begin = digits_width - separator_width;
+            let separator_end = digits_width;
+
+            Rect {
+                top: 0.,
+                bottom: digits_height,
+                left: separator_begin,
+                right: separator_end,
+            }
+        };
+
+        let target_rect = {
+            let horizontal_margin =
+                self.config.watch_horizontal_margin + self.config.digit_width * 2.;
+            Rect {
+                top: self.config.watch_vertical_margin,
+                bottom: self.config.watch_vertical_margin + digits_height,
+                left: horizontal_margin,
+                right: horizontal_margin + separator_width,
+            }
+        };
+
+        self.canvas.draw_bitmap(
+            &self.resources.digits_bitmap,
+            Some(&target_rect),
+            self.config.digit_opacity,
+            Some(&source_rect),
+        );
+    }
+
+    fn draw_digit(&mut self, num: i32, pos: i32, separator: bool) {
+        let digits_height = self
+            .canvas
+            .bitmap_size(&self.resources.digits_bitmap)
+            .height;
+
+        let source_rect = {
+            let digit_begin = self.config.digit_width * num as f32;
+            let digit_end = digit_begin + self.config.digit_width;
+
+            Rect {
+                top: 0.,
+                bottom: digits_height,
+                left: digit_begin,
+                right: digit_end,
+            }
+        };
+
+        let target_rect = {
+            let horizontal_margin =
+                self.config.watch_horizontal_margin + self.config.digit_width * pos as f32 + {
+                    if separator {
+                        self.config.separator_width
+                    } else {
+                        0.
+                    }
+                };
+
+            Rect {
+                top: self.config.watch_vertical_margin,
+                bottom: self.config.watch_vertical_margin + digits_height,
+                left: horizontal_margin,
+                right: horizontal_margin + self.config.digit_width,
+            }
+        };
+
+        self.canvas.draw_bitmap(
+            &self.resources.digits_bitmap,
+            Some(&target_rect),
+            self.config.digit_opacity,
+            Some(&source_rect),
+        );
+    }
+
+    fn render_time(&mut self, time: &NaiveTime) {
+        let hour = time.hour() as i32;
+        let minute = time.minute() as i32;
+
+        self.draw_digit(hour / 10, 0, false);
+        self.draw_digit(hour % 10, 1, false);
+        self.draw_digit(minute / 10, 2, true);
+        self.draw_digit(minute % 10, 3, true);
+    }
+
+    pub fn render(&mut self, time: &NaiveTime, draw_separator: bool) -> Result<()> {
+        self.draw(time, draw_separator);
+
+        match self.canvas.end_draw() {
+            /* The frame is lost; the next one draws with the new target. */
+            Err(Error::DeviceLost) => {
+                self.canvas.recreate_target()?;
+                self.resources = DeviceResources::new(&mut self.canvas, &self.assets)?;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn draw(&mut self, time: &NaiveTime, draw_separator: bool) {
+        self.canvas.begin_draw();
+        self.canvas.clear(self.config.background);
+
+        let rotation_matrix = self
+            .get_translation_for_bitmap_rotation(&self.resources.watch_bitmap, self.config.tilt);
+        let watch_centering =
+            self.get_translation_for_bitmap_centering(&self.resources.watch_bitmap);
+
+        let final_matrix = rotation_matrix * watch_centering;
+        self.canvas.set_transform(&final_matrix);
+
+        self.draw_watch();
+        self.render_time(time);
+
+        if draw_separator {
+            self.draw_separator();
+        }
+    }
+
+    pub fn canvas(&self) -> &C {
+        &self.canvas
+    }
+
+    pub fn canvas_mut(&mut self) -> &mut C {
+        &mut self.canvas
+    }
+
+    /* The same scene drawn into another canvas, e.g. for a screenshot. */
+    pub fn snapshot<D: Canvas>(&self, canvas: D) -> Result<Graphics<D>> {
+        Graphics::with_assets(canvas, self.config.clone(), &self.assets)
+    }
+
+    /* The sprites are looked up in the working directory, next to the exe. */
+    pub fn new(canvas: C, config: Config) -> Result<Self> {
+        Self::with_assets(canvas, config, Path::new(""))
+    }
+
+    pub fn with_assets(mut canvas: C, config: Config, directory: &Path) -> Result<Self> {
+        let resources = DeviceResources::new(&mut canvas, directory)?;
+
+        Ok(Graphics {
+            canvas,
+            config,
+            assets: directory.to_path_buf(),
+            resources,
+        })
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /* The layout is read from the configuration on every frame. */
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /* Only the window resizes the scene. */
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)
+    }
+
+    pub fn set_dpi(&mut self, dpi: Dpi) {
+        self.canvas.set_dpi(dpi);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::clock::{Clock, ManualClock};
+    use common::golden;
+    use common::software::SoftwareCanvas;
+    use common::svg::SvgCanvas;
+
+    #[test]
+    fn accelerated_time_runs_four_times_faster() {
+        let start = NaiveTime::from_hms_opt(23, 59, 58).unwrap();
+        let mut clock = ManualClock::new();
+
+        let (time, separator) = accelerated_time(&start, clock.get_time(SECS_IN_DAY / 4));
+        assert_eq!(time, start);
+        assert!(separator);
+
+        clock.advance(0.25);
+        let (time, separator) = accelerated_time(&start, clock.get_time(SECS_IN_DAY / 4));
+        assert_eq!(time, NaiveTime::from_hms_opt(23, 59, 59).unwrap());
+        assert!(!separator);
+
+        clock.advance(0.5);
+        let (time, _) = accelerated_time(&start, clock.get_time(SECS_IN_DAY / 4));
+        assert_eq!(time, NaiveTime::from_hms_opt(0, 0, 1).unwrap());
+    }
+
+    #[test]
+    fn golden_clock() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+        let canvas = SoftwareCanvas::new(1400, 600).unwrap();
+        let mut graphics =
+            Graphics::with_assets(canvas, Config::default(), &directory.join("fixtures")).unwrap();
+
+        let time = NaiveTime::from_hms_opt(10, 37, 0).unwrap();
+        graphics.render(&time, true).unwrap();
+
+        golden::assert_matches(
+            graphics.canvas(),
+            &directory.join("golden/clock_10_37.png"),
+            2,
+        );
+    }
+
+    #[test]
+    fn svg_clips_digit_sprites() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let canvas = SvgCanvas::new(1400, 600);
+        let mut graphics = Graphics::with_assets(canvas, Config::default(), &directory).unwrap();
+
+        let time = NaiveTime::from_hms_opt(10, 37, 0).unwrap();
+        graphics.render(&time, true).unwrap();
+
+        let svg = graphics.canvas().to_svg();
+        let images = svg.matches("<image ").count();
+        assert!(images > 4, "{images} images");
+        assert_eq!(
+            svg.matches("clip-path=").count(),
+            images - 1,
+            "all but the watch"
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::NaiveTime;
+use common::canvas::Canvas;
+use common::clock::Clock;
+use common::config::ConfigWatcher;
+use common::dpi::Dpi;
+use common::input::InputEvent;
+use common::playback::PlaybackClock;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::Result;
+use softapp::{App, Window, WindowBuilder};
+
+use clock::config::Config;
+use clock::graphics::{accelerated_time, get_random_time, Graphics, SECS_IN_DAY, STEPS_PER_SECOND};
+
+const TITLE: &str = "Clock";
+
+struct Watch {
+    graphics: Graphics<SoftwareCanvas>,
+    clock: PlaybackClock,
+    watcher: ConfigWatcher,
+    time: NaiveTime,
+}
+
+impl Watch {
+    fn new(
+        window: &Window,
+        clock: Box<dyn Clock>,
+        config: Config,
+        watcher: ConfigWatcher,
+    ) -> Result<Self> {
+        let (width, height) = window.client_size();
+        let mut canvas = SoftwareCanvas::new(width, height)?;
+        canvas.set_dpi(window.dpi());
+
+        Ok(Watch {
+            graphics: Graphics::new(canvas, config)?,
+            clock: PlaybackClock::new(clock, STEPS_PER_SECOND),
+            watcher,
+            time: get_random_time(),
+        })
+    }
+
+    /* Picks up edits of the configuration file between frames. */
+    fn reload_config(&mut self, window: &Window) {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return;
+        };
+
+        let old = self.graphics.config();
+
+        if (config.width, config.height) != (old.width, old.height) {
+            window.set_client_size(config.width, config.height);
+        }
+
+        self.graphics.set_config(config);
+    }
+}
+
+impl App for Watch {
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.graphics.canvas()
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        self.reload_config(window);
+
+        let timer_time = self.clock.get_time(SECS_IN_DAY / 4);
+        let (time, draw_separator) = accelerated_time(&self.time, timer_time);
+        self.graphics.render(&time, draw_separator)?;
+        self.clock.update()
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)
+    }
+
+    fn on_dpi_changed(&mut self, _window: &Window, dpi: Dpi) -> Result<()> {
+        self.graphics.set_dpi(dpi);
+        Ok(())
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.clock.playback.on_input(&event) {
+            window.set_title(&self.clock.playback.title(TITLE));
+        }
+    }
+}
+
+pub fn run(clock: Box<dyn Clock>, config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new(TITLE)
+        .client_size(config.width, config.height)
+        .target_fps(60)
+        .run(|window| Watch::new(window, clock, config, watcher))
+}
@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use chrono::NaiveTime;
+use common::canvas::Canvas;
+use common::clock::Clock;
+use common::config::ConfigWatcher;
+use common::d2d::D2DCanvas;
+use common::dpi::Dpi;
+use common::hud::Hud;
+use common::input::{InputEvent, Key};
+use common::playback::PlaybackClock;
+use common::screenshot;
+use common::stats::FrameStats;
+use winapp::{App, Pacing, Window, WindowBuilder};
+use windows::{
+    core::*, Win32::Foundation::*, Win32::Graphics::Gdi::*, Win32::UI::WindowsAndMessaging::*,
+};
+
+use clock::config::Config;
+use clock::graphics::{accelerated_time, get_random_time, Graphics, SECS_IN_DAY, STEPS_PER_SECOND};
+
+const TITLE: &str = "Clock";
+
+struct Watch {
+    graphics: Graphics<D2DCanvas>,
+    clock: PlaybackClock,
+    watcher: ConfigWatcher,
+    hud: Hud,
+    time: NaiveTime,
+}
+
+impl Watch {
+    fn new(
+        window: &Window,
+        clock: Box<dyn Clock>,
+        config: Config,
+        watcher: ConfigWatcher,
+    ) -> Result<Self> {
+        let (width, height) = window.client_size();
+        let mut canvas = D2DCanvas::with_vsync(window.handle(), width, height, window.vsync())?;
+        canvas.set_dpi(window.dpi());
+
+        Ok(Watch {
+            graphics: Graphics::new(canvas, config)?,
+            clock: PlaybackClock::new(clock, STEPS_PER_SECOND),
+            watcher,
+            hud: Hud::default(),
+            time: get_random_time(),
+        })
+    }
+
+    fn current_time(&self) -> (NaiveTime, bool) {
+        let timer_time = self.clock.get_time(SECS_IN_DAY / 4);
+        accelerated_time(&self.time, timer_time)
+    }
+
+    fn call_render(&mut self) -> Result<()> {
+        let (total_time, draw_separator) = self.current_time();
+
+        self.graphics.render(&total_time, draw_separator)?;
+        self.clock.update()?;
+
+        Ok(())
+    }
+
+    /* Draws the current frame again, offscreen, without the HUD. */
+    fn save_screenshot(&self, window: &Window) -> Result<PathBuf> {
+        let (width, height) = self.graphics.canvas().pixel_size();
+        let canvas = D2DCanvas::for_bitmap(width, height, window.dpi())?;
+
+        let mut graphics = self.graphics.snapshot(canvas)?;
+        let (time, draw_separator) = self.current_time();
+        graphics.render(&time, draw_separator)?;
+
+        let path = screenshot::path("clock");
+        graphics.canvas_mut().save_png(&path)?;
+        Ok(path)
+    }
+
+    /* Picks up edits of the configuration file between frames. */
+    fn reload_config(&mut self, window: &Window) -> Result<()> {
+        let Some(config) = self.watcher.poll::<Config>() else {
+            return Ok(());
+        };
+
+        let old = self.graphics.config();
+
+        if (config.width, config.height) != (old.width, old.height) {
+            /* The WM_SIZE this sends finds the app busy, so resize here. */
+            window.set_client_size(config.width, config.height)?;
+            let (width, height) = window.client_size();
+            self.graphics.resize(width, height)?;
+        }
+
+        self.graphics.set_config(config);
+        Ok(())
+    }
+}
+
+impl App for Watch {
+    fn on_message(
+        &mut self,
+        window: &Window,
+        message: u32,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            WM_PAINT => {
+                let mut ps = PAINTSTRUCT::default();
+
+                unsafe {
+                    BeginPaint(window.handle(), &mut ps);
+                    self.call_render().ok();
+                    EndPaint(window.handle(), &ps);
+                }
+
+                Some(LRESULT(0))
+            }
+            _ => None,
+        }
+    }
+
+    fn on_frame(&mut self, window: &Window, stats: &FrameStats) -> Result<()> {
+        self.reload_config(window)?;
+
+        let canvas = self.graphics.canvas_mut();
+        canvas.set_overlay(self.hud.text(stats, canvas.pixel_size(), window.dpi()));
+        self.call_render()
+    }
+
+    fn on_dpi_changed(&mut self, _window: &Window, dpi: Dpi) -> Result<()> {
+        self.graphics.set_dpi(dpi);
+        Ok(())
+    }
+
+    fn is_occluded(&self) -> bool {
+        self.graphics.canvas().is_occluded()
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.graphics.resize(width, height)?;
+        Ok(())
+    }
+
+    fn on_input(&mut self, window: &Window, event: InputEvent) {
+        if self.clock.playback.on_input(&event) {
+            window.set_title(&self.clock.playback.title(TITLE));
+            return;
+        }
+
+        match event {
+            InputEvent::KeyDown {
+                key: Key::F(3),
+                repeat: false,
+                ..
+            } => self.hud.toggle(),
+            InputEvent::KeyDown {
+                key: Key::F(12),
+                repeat: false,
+                ..
+            } => match self.save_screenshot(window) {
+                Ok(path) => println!("saved {}", path.display()),
+                Err(error) => eprintln!("screenshot failed: {error}"),
+            },
+            _ => {}
+        }
+    }
+}
+
+pub fn run(clock: Box<dyn Clock>, config: Config, watcher: ConfigWatcher) -> Result<()> {
+    WindowBuilder::new("Clock")
+        .title(TITLE)
+        .cursor(IDC_HAND)
+        .client_size(config.width, config.height)
+        .pacing(Pacing::Continuous {
+            target_fps: 60,
+            vsync: true,
+        })
+        .remember_placement()
+        .run(|window| Watch::new(window, clock, config, watcher))
+}
@@ -0,0 +1,169 @@
+/* Bit of each dot within a braille cell, indexed by [y][x]; see the Unicode
+Braille Patterns block, which starts at U+2800. */
+const DOT_BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+const BLANK: u32 = 0x2800;
+
+/// Monochrome framebuffer printed as Unicode braille: every character cell
+/// holds 2x4 dots, so a terminal gets twice its columns and four times its
+/// rows in resolution, with roughly square dots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrailleCanvas {
+    columns: usize,
+    rows: usize,
+    cells: Vec<u8>,
+}
+
+impl BrailleCanvas {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        BrailleCanvas {
+            columns,
+            rows,
+            cells: vec![0; columns * rows],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Width in dots.
+    pub fn width(&self) -> usize {
+        self.columns * 2
+    }
+
+    /// Height in dots.
+    pub fn height(&self) -> usize {
+        self.rows * 4
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+    }
+
+    /* The cell holding the dot and its bit, `None` outside the canvas. */
+    fn locate(&self, x: i32, y: i32) -> Option<(usize, u8)> {
+        if x < 0 || y < 0 || x as usize >= self.width() || y as usize >= self.height() {
+            return None;
+        }
+
+        let (x, y) = (x as usize, y as usize);
+        Some((y / 4 * self.columns + x / 2, DOT_BITS[y % 4][x % 2]))
+    }
+
+    /// Sets the dot at `(x, y)`; dots outside the canvas are ignored.
+    pub fn set(&mut self, x: i32, y: i32) {
+        if let Some((cell, bit)) = self.locate(x, y) {
+            self.cells[cell] |= bit;
+        }
+    }
+
+    pub fn is_set(&self, x: i32, y: i32) -> bool {
+        self.locate(x, y)
+            .is_some_and(|(cell, bit)| self.cells[cell] & bit != 0)
+    }
+
+    /// Dots along the segment, one per step of the longer axis.
+    pub fn line(&mut self, (x0, y0): (f32, f32), (x1, y1): (f32, f32)) {
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.) as u32;
+
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let x = x0 + (x1 - x0) * t;
+            let y = y0 + (y1 - y0) * t;
+            self.set(x.round() as i32, y.round() as i32);
+        }
+    }
+
+    /// Outline of an axis-aligned ellipse.
+    pub fn ellipse(&mut self, (center_x, center_y): (f32, f32), radius_x: f32, radius_y: f32) {
+        /* Enough samples that neighbouring ones are at most a dot apart. */
+        let steps = (std::f32::consts::TAU * radius_x.max(radius_y))
+            .ceil()
+            .max(4.) as u32;
+
+        for step in 0..steps {
+            let angle = std::f32::consts::TAU * step as f32 / steps as f32;
+            let x = center_x + radius_x * angle.cos();
+            let y = center_y + radius_y * angle.sin();
+            self.set(x.round() as i32, y.round() as i32);
+        }
+    }
+
+    /// The text of each row, top to bottom.
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.cells.chunks(self.columns.max(1)).map(|row| {
+            row.iter()
+                .map(|&bits| char::from_u32(BLANK + bits as u32).unwrap())
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(canvas: &BrailleCanvas) -> Vec<String> {
+        canvas.lines().collect()
+    }
+
+    #[test]
+    fn dots_map_to_braille_patterns() {
+        let mut canvas = BrailleCanvas::new(2, 1);
+        assert_eq!(text(&canvas), ["\u{2800}\u{2800}"]);
+
+        canvas.set(0, 0);
+        canvas.set(1, 3);
+        canvas.set(2, 2);
+
+        assert_eq!(text(&canvas), ["\u{2881}\u{2804}"]);
+        assert!(canvas.is_set(1, 3));
+        assert!(!canvas.is_set(1, 2));
+
+        canvas.clear();
+        assert_eq!(text(&canvas), ["\u{2800}\u{2800}"]);
+    }
+
+    #[test]
+    fn ignores_dots_outside() {
+        let mut canvas = BrailleCanvas::new(1, 1);
+
+        canvas.set(-1, 0);
+        canvas.set(2, 0);
+        canvas.set(0, 4);
+        canvas.line((-10., -10.), (-1., -1.));
+
+        assert_eq!(text(&canvas), ["\u{2800}"]);
+        assert!(!canvas.is_set(-1, 0));
+    }
+
+    #[test]
+    fn lines_are_connected() {
+        let mut canvas = BrailleCanvas::new(4, 2);
+        canvas.line((0., 0.), (7., 7.));
+
+        for i in 0..8 {
+            assert!(canvas.is_set(i, i), "dot {i}");
+        }
+
+        canvas.clear();
+        canvas.line((0., 5.), (7., 5.));
+        assert_eq!(text(&canvas), ["\u{2800}".repeat(4), "\u{2812}".repeat(4)]);
+    }
+
+    #[test]
+    fn ellipse_outline() {
+        let mut canvas = BrailleCanvas::new(8, 3);
+        canvas.ellipse((8., 6.), 6., 4.);
+
+        for (x, y) in [(14, 6), (2, 6), (8, 10), (8, 2)] {
+            assert!(canvas.is_set(x, y), "extreme ({x}, {y})");
+        }
+
+        assert!(!canvas.is_set(8, 6), "the inside stays empty");
+    }
+}
@@ -0,0 +1,415 @@
+use std::f32::consts::PI;
+use std::ops::Mul;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dpi::Dpi;
+use crate::Result;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Color { r, g, b, a: 1. }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Rect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl Rect {
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Ellipse {
+    pub point: Point,
+    pub radius_x: f32,
+    pub radius_y: f32,
+}
+
+/// Affine transform in the row-vector convention used by Direct2D:
+/// `x' = x * m11 + y * m21 + m31`, `y' = x * m12 + y * m22 + m32`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Matrix {
+    pub m11: f32,
+    pub m12: f32,
+    pub m21: f32,
+    pub m22: f32,
+    pub m31: f32,
+    pub m32: f32,
+}
+
+impl Matrix {
+    pub const fn identity() -> Self {
+        Matrix {
+            m11: 1.,
+            m12: 0.,
+            m21: 0.,
+            m22: 1.,
+            m31: 0.,
+            m32: 0.,
+        }
+    }
+
+    pub const fn translation(x: f32, y: f32) -> Self {
+        Matrix {
+            m31: x,
+            m32: y,
+            ..Self::identity()
+        }
+    }
+
+    /// Clockwise rotation by `angle` degrees around (`x`, `y`), same as
+    /// `D2D1MakeRotateMatrix`.
+    pub fn rotation(angle: f32, x: f32, y: f32) -> Self {
+        let (sin, cos) = (angle * PI / 180.).sin_cos();
+
+        Matrix {
+            m11: cos,
+            m12: sin,
+            m21: -sin,
+            m22: cos,
+            m31: x - x * cos + y * sin,
+            m32: y - x * sin - y * cos,
+        }
+    }
+
+    pub fn transform_point(&self, point: Point) -> Point {
+        Point {
+            x: point.x * self.m11 + point.y * self.m21 + self.m31,
+            y: point.x * self.m12 + point.y * self.m22 + self.m32,
+        }
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// `a * b` applies `a` first, then `b`.
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, rhs: Matrix) -> Matrix {
+        Matrix {
+            m11: self.m11 * rhs.m11 + self.m12 * rhs.m21,
+            m12: self.m11 * rhs.m12 + self.m12 * rhs.m22,
+            m21: self.m21 * rhs.m11 + self.m22 * rhs.m21,
+            m22: self.m21 * rhs.m12 + self.m22 * rhs.m22,
+            m31: self.m31 * rhs.m11 + self.m32 * rhs.m21 + rhs.m31,
+            m32: self.m31 * rhs.m12 + self.m32 * rhs.m22 + rhs.m32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GradientStop {
+    pub position: f32,
+    pub color: Color,
+}
+
+/// Radial gradient brush description. Colors are interpolated in sRGB space
+/// and clamped past the last stop, like `D2D1_GAMMA_2_2` with
+/// `D2D1_EXTEND_MODE_CLAMP`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RadialGradient {
+    pub center: Point,
+    pub radius_x: f32,
+    pub radius_y: f32,
+    pub gradient_origin_offset: Point,
+    pub stops: Vec<GradientStop>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepDirection {
+    CounterClockwise,
+    Clockwise,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArcSize {
+    Small,
+    Large,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct BezierSegment {
+    pub point1: Point,
+    pub point2: Point,
+    pub point3: Point,
+}
+
+/// Elliptical arc from the current point to `point`, same semantics as
+/// `D2D1_ARC_SEGMENT` (radii are scaled up if they cannot span the endpoints).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ArcSegment {
+    pub point: Point,
+    pub size: Size,
+    pub rotation_angle: f32,
+    pub sweep_direction: SweepDirection,
+    pub arc_size: ArcSize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Segment {
+    Line(Point),
+    Bezier(BezierSegment),
+    Arc(ArcSegment),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FigureEnd {
+    Open,
+    Closed,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Figure {
+    pub start: Point,
+    pub segments: Vec<Segment>,
+    pub end: FigureEnd,
+}
+
+/// Device-independent path description. Backends turn it into their own
+/// geometry object with `Canvas::create_geometry`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Path {
+    pub figures: Vec<Figure>,
+}
+
+/// Mirrors `ID2D1GeometrySink`: `begin_figure`, `add_*` segments, `end_figure`.
+#[derive(Default)]
+pub struct PathBuilder {
+    figures: Vec<Figure>,
+    current: Option<Figure>,
+}
+
+impl PathBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin_figure(&mut self, start: Point) {
+        debug_assert!(self.current.is_none(), "previous figure was not ended");
+
+        self.current = Some(Figure {
+            start,
+            segments: Vec::new(),
+            end: FigureEnd::Open,
+        });
+    }
+
+    fn push(&mut self, segment: Segment) {
+        self.current
+            .as_mut()
+            .expect("segment added outside of a figure")
+            .segments
+            .push(segment);
+    }
+
+    pub fn add_line(&mut self, point: Point) {
+        self.push(Segment::Line(point));
+    }
+
+    pub fn add_bezier(&mut self, bezier: BezierSegment) {
+        self.push(Segment::Bezier(bezier));
+    }
+
+    pub fn add_arc(&mut self, arc: ArcSegment) {
+        self.push(Segment::Arc(arc));
+    }
+
+    pub fn end_figure(&mut self, end: FigureEnd) {
+        let mut figure = self.current.take().expect("no figure to end");
+        figure.end = end;
+        self.figures.push(figure);
+    }
+
+    pub fn close(self) -> Path {
+        debug_assert!(self.current.is_none(), "last figure was not ended");
+        Path {
+            figures: self.figures,
+        }
+    }
+}
+
+/// Approximates an elliptical arc starting at `from` with cubic Beziers, for
+/// backends that have no native arc primitive. Follows the endpoint to center
+/// conversion from the SVG specification (appendix B.2.4).
+pub fn arc_to_beziers(from: Point, arc: &ArcSegment) -> Vec<BezierSegment> {
+    let to = arc.point;
+    let (mut rx, mut ry) = (arc.size.width.abs(), arc.size.height.abs());
+
+    if from == to {
+        return Vec::new();
+    }
+
+    if rx == 0. || ry == 0. {
+        return vec![BezierSegment {
+            point1: from,
+            point2: to,
+            point3: to,
+        }];
+    }
+
+    let (sin_phi, cos_phi) = (arc.rotation_angle * PI / 180.).sin_cos();
+    let sweep = arc.sweep_direction == SweepDirection::Clockwise;
+    let large_arc = arc.arc_size == ArcSize::Large;
+
+    let (dx2, dy2) = ((from.x - to.x) / 2., (from.y - to.y) / 2.);
+    let x1p = cos_phi * dx2 + sin_phi * dy2;
+    let y1p = -sin_phi * dx2 + cos_phi * dy2;
+
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let (rx2, ry2) = (rx * rx, ry * ry);
+    let numerator = rx2 * ry2 - rx2 * y1p * y1p - ry2 * x1p * x1p;
+    let denominator = rx2 * y1p * y1p + ry2 * x1p * x1p;
+    let sign = if large_arc == sweep { -1. } else { 1. };
+    let coefficient = sign * (numerator / denominator).max(0.).sqrt();
+
+    let cxp = coefficient * rx * y1p / ry;
+    let cyp = -coefficient * ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (from.x + to.x) / 2.;
+    let cy = sin_phi * cxp + cos_phi * cyp + (from.y + to.y) / 2.;
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+
+    let (ux, uy) = ((x1p - cxp) / rx, (y1p - cyp) / ry);
+    let (vx, vy) = ((-x1p - cxp) / rx, (-y1p - cyp) / ry);
+
+    let theta = angle(1., 0., ux, uy);
+    let mut delta = angle(ux, uy, vx, vy);
+
+    if !sweep && delta > 0. {
+        delta -= 2. * PI;
+    } else if sweep && delta < 0. {
+        delta += 2. * PI;
+    }
+
+    let map = |ux: f32, uy: f32| Point {
+        x: cx + rx * ux * cos_phi - ry * uy * sin_phi,
+        y: cy + rx * ux * sin_phi + ry * uy * cos_phi,
+    };
+
+    let count = (delta.abs() / (PI / 2.)).ceil().max(1.) as usize;
+    let step = delta / count as f32;
+    let k = 4. / 3. * (step / 4.).tan();
+
+    (0..count)
+        .map(|i| {
+            let t1 = theta + step * i as f32;
+            let t2 = t1 + step;
+            let (sin1, cos1) = t1.sin_cos();
+            let (sin2, cos2) = t2.sin_cos();
+
+            BezierSegment {
+                point1: map(cos1 - k * sin1, sin1 + k * cos1),
+                point2: map(cos2 + k * sin2, sin2 - k * cos2),
+                point3: if i + 1 == count { to } else { map(cos2, sin2) },
+            }
+        })
+        .collect()
+}
+
+/// Drawing surface used by the demos' `Graphics`. Coordinates are in
+/// device-independent pixels; brushes, geometries and bitmaps are created by
+/// the canvas and are only valid for the canvas that created them.
+///
+/// Geometries are device independent. Brushes and bitmaps are not: when
+/// `end_draw` fails with [`Error::DeviceLost`], the owner calls
+/// `recreate_target` and creates them again.
+///
+/// [`Error::DeviceLost`]: crate::Error::DeviceLost
+pub trait Canvas {
+    type Brush;
+    type Geometry;
+    type Bitmap;
+
+    fn create_solid_brush(&mut self, color: Color) -> Result<Self::Brush>;
+    fn create_radial_gradient_brush(&mut self, gradient: &RadialGradient) -> Result<Self::Brush>;
+    fn create_geometry(&mut self, path: &Path) -> Result<Self::Geometry>;
+    fn load_bitmap(&mut self, path: &std::path::Path) -> Result<Self::Bitmap>;
+    fn bitmap_size(&self, bitmap: &Self::Bitmap) -> Size;
+
+    /// Size in device independent pixels.
+    fn size(&self) -> Size;
+    /// Resizes the surface to `width` x `height` physical pixels.
+    fn resize(&mut self, width: u32, height: u32) -> Result<()>;
+    /// Sets how many physical pixels a device independent pixel covers.
+    fn set_dpi(&mut self, dpi: Dpi);
+
+    fn begin_draw(&mut self);
+    fn end_draw(&mut self) -> Result<()>;
+    fn recreate_target(&mut self) -> Result<()>;
+
+    fn set_transform(&mut self, transform: &Matrix);
+    fn clear(&mut self, color: Color);
+    fn draw_line(&mut self, p0: Point, p1: Point, brush: &Self::Brush, stroke_width: f32);
+
+    /// Connected segments through `points`. Raster backends draw them one
+    /// line at a time, vector output keeps a single polyline.
+    fn draw_polyline(&mut self, points: &[Point], brush: &Self::Brush, stroke_width: f32) {
+        for segment in points.windows(2) {
+            self.draw_line(segment[0], segment[1], brush, stroke_width);
+        }
+    }
+    fn fill_rectangle(&mut self, rect: &Rect, brush: &Self::Brush);
+    fn fill_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush);
+    fn draw_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush, stroke_width: f32);
+    fn fill_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush);
+    fn draw_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush, stroke_width: f32);
+
+    /// Draws `source` (whole bitmap if `None`) of `bitmap` scaled into
+    /// `destination` (bitmap size at the origin if `None`), with linear
+    /// interpolation.
+    fn draw_bitmap(
+        &mut self,
+        bitmap: &Self::Bitmap,
+        destination: Option<&Rect>,
+        opacity: f32,
+        source: Option<&Rect>,
+    );
+}
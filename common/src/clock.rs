@@ -0,0 +1,197 @@
+use std::time::Instant;
+
+use crate::Result;
+
+/// Source of animation time. The time is sampled by `update`, so everything
+/// drawn for one frame sees the same instant.
+pub trait Clock {
+    /// Samples the underlying time source.
+    fn update(&mut self) -> Result<()>;
+
+    /// Seconds between the creation of the clock and the last `update`.
+    fn elapsed(&self) -> f64;
+
+    /// Elapsed seconds wrapped into `[0, period_in_seconds)`.
+    fn get_time(&self, period_in_seconds: u32) -> f64 {
+        self.elapsed() % period_in_seconds as f64
+    }
+}
+
+/// Clock backed by `QueryPerformanceCounter`.
+#[cfg(windows)]
+pub struct QpcClock {
+    start_time: i64,
+    update_time: i64,
+    frequency: i64,
+}
+
+#[cfg(windows)]
+impl QpcClock {
+    fn query_performance_frequency() -> Result<i64> {
+        use windows::Win32::System::Performance::QueryPerformanceFrequency;
+
+        let mut frequency = 0;
+
+        unsafe {
+            QueryPerformanceFrequency(&mut frequency).ok()?;
+        }
+
+        Ok(frequency)
+    }
+
+    fn query_performance_counter() -> Result<i64> {
+        use windows::Win32::System::Performance::QueryPerformanceCounter;
+
+        let mut counter = 0;
+
+        unsafe {
+            QueryPerformanceCounter(&mut counter).ok()?;
+        }
+
+        Ok(counter)
+    }
+
+    pub fn new() -> Result<Self> {
+        let frequency = Self::query_performance_frequency()?;
+        let counter = Self::query_performance_counter()?;
+
+        Ok(QpcClock {
+            start_time: counter,
+            update_time: counter,
+            frequency,
+        })
+    }
+}
+
+#[cfg(windows)]
+impl Clock for QpcClock {
+    fn update(&mut self) -> Result<()> {
+        self.update_time = Self::query_performance_counter()?;
+        Ok(())
+    }
+
+    fn elapsed(&self) -> f64 {
+        let delta = self.update_time.wrapping_sub(self.start_time);
+        delta as f64 / self.frequency as f64
+    }
+
+    /* Wraps in ticks rather than seconds so long runs do not lose precision. */
+    fn get_time(&self, period_in_seconds: u32) -> f64 {
+        let delta = self.update_time.wrapping_sub(self.start_time);
+        let period = self.frequency * period_in_seconds as i64;
+        let time = delta % period;
+
+        time as f64 / self.frequency as f64
+    }
+}
+
+/// Portable clock backed by `std::time::Instant`.
+pub struct InstantClock {
+    start_time: Instant,
+    update_time: Instant,
+}
+
+impl InstantClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+
+        InstantClock {
+            start_time: now,
+            update_time: now,
+        }
+    }
+}
+
+impl Default for InstantClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for InstantClock {
+    fn update(&mut self) -> Result<()> {
+        self.update_time = Instant::now();
+        Ok(())
+    }
+
+    fn elapsed(&self) -> f64 {
+        (self.update_time - self.start_time).as_secs_f64()
+    }
+}
+
+/// Clock that only moves when told to; used for recordings and tests.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    elapsed: f64,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, seconds: f64) {
+        self.elapsed = seconds;
+    }
+
+    pub fn advance(&mut self, seconds: f64) {
+        self.elapsed += seconds;
+    }
+}
+
+impl Clock for ManualClock {
+    fn update(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_only_moves_when_advanced() {
+        let mut clock = ManualClock::new();
+        assert_eq!(clock.elapsed(), 0.);
+
+        clock.update().unwrap();
+        assert_eq!(clock.elapsed(), 0.);
+
+        clock.advance(1.5);
+        clock.advance(0.25);
+        assert_eq!(clock.elapsed(), 1.75);
+
+        clock.set(10.);
+        assert_eq!(clock.elapsed(), 10.);
+    }
+
+    #[test]
+    fn get_time_wraps_into_period() {
+        let mut clock = ManualClock::new();
+
+        clock.set(47.5);
+        assert_eq!(clock.get_time(48), 47.5);
+
+        clock.set(48.);
+        assert_eq!(clock.get_time(48), 0.);
+
+        clock.set(100.);
+        assert_eq!(clock.get_time(48), 4.);
+    }
+
+    #[test]
+    fn instant_clock_is_frozen_between_updates() {
+        let mut clock = InstantClock::new();
+        assert_eq!(clock.elapsed(), 0.);
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(clock.elapsed(), 0.);
+
+        clock.update().unwrap();
+        assert!(clock.elapsed() >= 0.005);
+    }
+}
@@ -0,0 +1,331 @@
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::de::{self, DeserializeOwned, Deserializer, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use crate::canvas::Color;
+use crate::{Error, Result};
+
+pub const USAGE: &str = "--config <file.toml>";
+
+/// Demo parameters read from TOML. Keys left out keep their `Default`
+/// value, so an empty file reproduces the built-in look.
+pub trait Config: DeserializeOwned + Default {
+    /// Checks the values the TOML types alone cannot rule out. Returns one
+    /// message per problem, each naming the offending key.
+    fn validate(&self) -> Vec<String>;
+}
+
+/// Parses and validates `text`, reporting every problem at once.
+pub fn parse<T: Config>(text: &str) -> std::result::Result<T, String> {
+    let config: T = toml::from_str(text).map_err(|error| error.to_string())?;
+    let problems = config.validate();
+
+    match problems.is_empty() {
+        true => Ok(config),
+        false => Err(problems.join("\n")),
+    }
+}
+
+/// Where a demo looks for its configuration: `--config <file>` or
+/// `<name>.toml` next to the executable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigFile {
+    path: PathBuf,
+    /* Only a file named on the command line has to exist. */
+    required: bool,
+}
+
+impl ConfigFile {
+    pub fn from_args<I>(args: I, name: &str) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--config" {
+                let path = args.next().ok_or_else(|| {
+                    Error::Usage(format!("--config needs a value; usage: {USAGE}"))
+                })?;
+
+                return Ok(ConfigFile {
+                    path: PathBuf::from(path),
+                    required: true,
+                });
+            }
+        }
+
+        let file_name = format!("{name}.toml");
+        let path = match std::env::current_exe() {
+            Ok(exe) => exe.with_file_name(file_name),
+            Err(_) => PathBuf::from(file_name),
+        };
+
+        Ok(ConfigFile {
+            path,
+            required: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load<T: Config>(&self) -> Result<T> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound && !self.required => {
+                return Ok(T::default())
+            }
+            Err(error) => return Err(self.error(error.to_string())),
+        };
+
+        parse(&text).map_err(|message| self.error(message))
+    }
+
+    /// Like `load`, but reports a broken file on stderr and carries on with
+    /// the built-in values instead of refusing to start.
+    pub fn load_or_default<T: Config>(&self) -> T {
+        self.load().unwrap_or_else(|error| {
+            eprintln!("{error}\nfalling back to the built-in configuration");
+            T::default()
+        })
+    }
+
+    fn error(&self, message: String) -> Error {
+        Error::Config {
+            path: self.path.clone(),
+            message,
+        }
+    }
+}
+
+/// Notices when a configuration file is edited while the demo runs. Polled
+/// once per frame; a stat call is cheap next to drawing a frame.
+pub struct ConfigWatcher {
+    file: ConfigFile,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// Only changes made after this call are reported.
+    pub fn new(file: ConfigFile) -> Self {
+        let modified = modified(&file.path);
+        ConfigWatcher { file, modified }
+    }
+
+    /// Returns the new configuration when the file changed since the last
+    /// call. A broken edit is reported on stderr and the demo keeps its
+    /// current values; deleting an optional file restores the built-in ones.
+    pub fn poll<T: Config>(&mut self) -> Option<T> {
+        let modified = modified(&self.file.path);
+
+        if modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+
+        match self.file.load() {
+            Ok(config) => Some(config),
+            Err(error) => {
+                eprintln!("{error}\nkeeping the previous configuration");
+                None
+            }
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/* Colors are written either as "#rrggbb" / "#rrggbbaa" or as an array of
+three or four channels in 0..=1. */
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+struct ColorVisitor;
+
+impl<'de> Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a color as \"#rrggbb\", \"#rrggbbaa\" or [r, g, b(, a)]")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Color, E> {
+        parse_hex_color(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Color, A::Error> {
+        let mut channels = Vec::with_capacity(4);
+
+        while let Some(channel) = seq.next_element::<f32>()? {
+            if !(0. ..=1.).contains(&channel) {
+                return Err(de::Error::custom(format!(
+                    "color channel {channel} is outside 0..=1"
+                )));
+            }
+
+            channels.push(channel);
+        }
+
+        match channels[..] {
+            [r, g, b] => Ok(Color::rgb(r, g, b)),
+            [r, g, b, a] => Ok(Color { r, g, b, a }),
+            _ => Err(de::Error::invalid_length(channels.len(), &self)),
+        }
+    }
+}
+
+fn parse_hex_color(value: &str) -> Option<Color> {
+    let digits = value.strip_prefix('#')?;
+
+    if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+        return None;
+    }
+
+    let channel = |index: usize| -> Option<f32> {
+        let byte = u8::from_str_radix(digits.get(index * 2..index * 2 + 2)?, 16).ok()?;
+        Some(byte as f32 / 255.)
+    };
+
+    Some(Color {
+        r: channel(0)?,
+        g: channel(1)?,
+        b: channel(2)?,
+        a: match digits.len() {
+            8 => channel(3)?,
+            _ => 1.,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(default, deny_unknown_fields)]
+    struct Sample {
+        count: u32,
+        color: Color,
+    }
+
+    impl Default for Sample {
+        fn default() -> Self {
+            Sample {
+                count: 3,
+                color: Color::rgb(0., 0., 0.),
+            }
+        }
+    }
+
+    impl Config for Sample {
+        fn validate(&self) -> Vec<String> {
+            match self.count {
+                0 => vec!["count must be positive".to_string()],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_keys_keep_defaults() {
+        assert_eq!(parse::<Sample>(""), Ok(Sample::default()));
+        assert_eq!(parse::<Sample>("count = 5").unwrap().count, 5);
+    }
+
+    #[test]
+    fn colors_accept_hex_and_channels() {
+        let sample: Sample = parse(r##"color = "#ff0080""##).unwrap();
+        assert_eq!(sample.color, Color::rgb(1., 0., 128. / 255.));
+
+        let sample: Sample = parse(r##"color = "#FF000080""##).unwrap();
+        assert_eq!(sample.color.a, 128. / 255.);
+
+        let sample: Sample = parse("color = [0.25, 0.5, 1]").unwrap();
+        assert_eq!(sample.color, Color::rgb(0.25, 0.5, 1.));
+
+        assert!(parse::<Sample>(r#"color = "red""#).is_err());
+        assert!(parse::<Sample>("color = [0.5, 2, 0]").is_err());
+        assert!(parse::<Sample>("color = [0.5, 0.5]").is_err());
+    }
+
+    #[test]
+    fn errors_name_the_key() {
+        let error = parse::<Sample>("colour = [0, 0, 0]").unwrap_err();
+        assert!(error.contains("colour"), "{error}");
+
+        let error = parse::<Sample>("count = -1").unwrap_err();
+        assert!(error.contains("count"), "{error}");
+
+        let error = parse::<Sample>("count = 0").unwrap_err();
+        assert_eq!(error, "count must be positive");
+    }
+
+    #[test]
+    fn watcher_reports_edits_once() {
+        let path = std::env::temp_dir().join(format!("config_watch_{}.toml", std::process::id()));
+        let touch = |text: &str, seconds: u64| {
+            fs::write(&path, text).unwrap();
+            let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+
+        let file = ConfigFile {
+            path: path.clone(),
+            required: false,
+        };
+
+        touch("count = 1", 1_000);
+        let mut watcher = ConfigWatcher::new(file);
+        assert_eq!(watcher.poll::<Sample>(), None);
+
+        touch("count = 2", 2_000);
+        assert_eq!(watcher.poll::<Sample>().unwrap().count, 2);
+        assert_eq!(watcher.poll::<Sample>(), None);
+
+        /* A broken edit is skipped, the fix after it is picked up. */
+        touch("count = 0", 3_000);
+        assert_eq!(watcher.poll::<Sample>(), None);
+        touch("count = 4", 4_000);
+        assert_eq!(watcher.poll::<Sample>().unwrap().count, 4);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.poll::<Sample>(), Some(Sample::default()));
+    }
+
+    #[test]
+    fn explicit_file_must_exist() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let default = ConfigFile::from_args(args(&["--gif"]), "no_such_demo").unwrap();
+        assert_eq!(default.path().file_name().unwrap(), "no_such_demo.toml");
+        assert_eq!(default.load::<Sample>().unwrap(), Sample::default());
+
+        let explicit = ConfigFile::from_args(args(&["--config", "no/such.toml"]), "demo").unwrap();
+        assert!(matches!(
+            explicit.load::<Sample>(),
+            Err(Error::Config { .. })
+        ));
+
+        assert!(ConfigFile::from_args(args(&["--config"]), "demo").is_err());
+    }
+}
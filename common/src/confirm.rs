@@ -0,0 +1,355 @@
+use serde::{Deserialize, Serialize};
+
+use crate::input::{Key, Modifiers};
+use crate::settings::Settings;
+use crate::{Error, Result};
+
+/// What the user answered a [`Confirm`] with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Answer {
+    Ok,
+    Yes,
+    No,
+    /// Also the answer of Escape and of the close box, whatever the buttons
+    /// are.
+    Cancel,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Button {
+    pub label: String,
+    pub answer: Answer,
+}
+
+/// A question for a modal dialog: a title, some text, a row of buttons and
+/// optionally a "Don't ask again" checkbox.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Confirm {
+    pub title: String,
+    pub text: String,
+    pub buttons: Vec<Button>,
+    /// Index of the button that has the focus first.
+    pub default: usize,
+    /// Where [`Settings`] keep the answer given with "Don't ask again"
+    /// checked; no checkbox without one.
+    pub dont_ask_again: Option<String>,
+}
+
+impl Confirm {
+    /// Asks with OK and Cancel buttons.
+    pub fn new(title: &str, text: &str) -> Self {
+        Confirm {
+            title: title.to_owned(),
+            text: text.to_owned(),
+            buttons: Vec::new(),
+            default: 0,
+            dont_ask_again: None,
+        }
+        .buttons(&[("OK", Answer::Ok), ("Cancel", Answer::Cancel)])
+    }
+
+    /// Replaces the buttons, left to right; there must be at least one.
+    pub fn buttons(mut self, buttons: &[(&str, Answer)]) -> Self {
+        assert!(!buttons.is_empty(), "no buttons");
+        self.buttons = buttons
+            .iter()
+            .map(|&(label, answer)| Button {
+                label: label.to_owned(),
+                answer,
+            })
+            .collect();
+        self.default = 0;
+        self
+    }
+
+    pub fn default_button(mut self, index: usize) -> Self {
+        assert!(index < self.buttons.len(), "no button {index}");
+        self.default = index;
+        self
+    }
+
+    /// Adds the "Don't ask again" checkbox, remembering under `key`.
+    pub fn dont_ask_again(mut self, key: &str) -> Self {
+        self.dont_ask_again = Some(key.to_owned());
+        self
+    }
+
+    /// The answer given before with "Don't ask again" checked, if any; the
+    /// dialog is not shown then.
+    pub fn remembered(&self, settings: &Settings) -> Option<Answer> {
+        settings
+            .confirmations
+            .get(self.dont_ask_again.as_ref()?)
+            .copied()
+    }
+
+    /// There is a button, and the default one is among them. The fields are
+    /// public, so the builder methods alone cannot make sure of it.
+    pub fn validate(&self) -> Result<()> {
+        if self.buttons.is_empty() {
+            return Err(Error::Usage(format!("{:?} has no buttons", self.title)));
+        }
+
+        if self.default >= self.buttons.len() {
+            return Err(Error::Usage(format!(
+                "{:?} has no button {} to be the default",
+                self.title, self.default
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Keeps `answer` for [`remembered`](Self::remembered). Cancel is never
+    /// kept: it would leave no way to answer otherwise.
+    pub fn remember(&self, settings: &mut Settings, answer: Answer) {
+        if let (Some(key), false) = (&self.dont_ask_again, answer == Answer::Cancel) {
+            settings.confirmations.insert(key.clone(), answer);
+        }
+    }
+}
+
+/// A control of the dialog that can have the focus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Button(usize),
+    Checkbox,
+}
+
+/// What a key press did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reaction {
+    /// Not a key of the dialog; the focused control may have it.
+    Ignored,
+    /// The focus moved or the checkbox toggled.
+    Changed,
+    Answered(Answer),
+}
+
+/// Keyboard navigation of a [`Confirm`] dialog, apart from any window: Tab
+/// and Shift+Tab go through the buttons and then the checkbox, the arrow
+/// keys along the buttons. Space presses the focused control and Enter the
+/// focused button, or the default one from the checkbox. Escape cancels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Navigation {
+    focus: Control,
+    checked: bool,
+    answers: Vec<Answer>,
+    default: usize,
+    checkbox: bool,
+}
+
+impl Navigation {
+    /// For a `confirm` that passes [`Confirm::validate`].
+    pub fn new(confirm: &Confirm) -> Self {
+        Navigation {
+            focus: Control::Button(confirm.default),
+            checked: false,
+            answers: confirm.buttons.iter().map(|button| button.answer).collect(),
+            default: confirm.default,
+            checkbox: confirm.dont_ask_again.is_some(),
+        }
+    }
+
+    pub fn focus(&self) -> Control {
+        self.focus
+    }
+
+    /// Follows the focus moved by the mouse.
+    pub fn set_focus(&mut self, focus: Control) {
+        self.focus = focus;
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    pub fn set_checked(&mut self, checked: bool) {
+        self.checked = checked;
+    }
+
+    /// The button that Enter presses, highlighted as such.
+    pub fn default_button(&self) -> usize {
+        match self.focus {
+            Control::Button(index) => index,
+            Control::Checkbox => self.default,
+        }
+    }
+
+    /// The answer of a button or None for the checkbox, which toggles.
+    pub fn press(&mut self, control: Control) -> Option<Answer> {
+        self.focus = control;
+
+        match control {
+            Control::Button(index) => Some(self.answers[index]),
+            Control::Checkbox => {
+                self.checked = !self.checked;
+                None
+            }
+        }
+    }
+
+    pub fn on_key(&mut self, key: Key, modifiers: Modifiers) -> Reaction {
+        let controls = self.answers.len() + self.checkbox as usize;
+        let index = match self.focus {
+            Control::Button(index) => index,
+            Control::Checkbox => self.answers.len(),
+        };
+        let at = |index: usize| match index < self.answers.len() {
+            true => Control::Button(index),
+            false => Control::Checkbox,
+        };
+
+        let focus = match (key, self.focus) {
+            (Key::Escape, _) => return Reaction::Answered(Answer::Cancel),
+            (Key::Enter, _) => {
+                return Reaction::Answered(self.answers[self.default_button()]);
+            }
+            (Key::Space, focus) => {
+                return match self.press(focus) {
+                    Some(answer) => Reaction::Answered(answer),
+                    None => Reaction::Changed,
+                };
+            }
+            (Key::Tab, _) if modifiers.shift => at((index + controls - 1) % controls),
+            (Key::Tab, _) => at((index + 1) % controls),
+            (Key::Left | Key::Up, Control::Button(index)) => Control::Button(index.max(1) - 1),
+            (Key::Right | Key::Down, Control::Button(index)) => {
+                Control::Button((index + 1).min(self.answers.len() - 1))
+            }
+            _ => return Reaction::Ignored,
+        };
+
+        self.focus = focus;
+        Reaction::Changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        ctrl: false,
+        alt: false,
+    };
+
+    fn quit() -> Confirm {
+        Confirm::new("Want to quit?", "Are you sure?")
+            .buttons(&[
+                ("Quit", Answer::Yes),
+                ("Minimize", Answer::No),
+                ("Cancel", Answer::Cancel),
+            ])
+            .default_button(2)
+            .dont_ask_again("quit")
+    }
+
+    #[test]
+    fn tab_goes_round_the_buttons_and_the_checkbox() {
+        let mut navigation = Navigation::new(&quit());
+        let none = Modifiers::default();
+
+        assert_eq!(navigation.focus(), Control::Button(2));
+        assert_eq!(navigation.on_key(Key::Tab, none), Reaction::Changed);
+        assert_eq!(navigation.focus(), Control::Checkbox);
+        assert_eq!(navigation.on_key(Key::Tab, none), Reaction::Changed);
+        assert_eq!(navigation.focus(), Control::Button(0));
+        navigation.on_key(Key::Tab, SHIFT);
+        navigation.on_key(Key::Tab, SHIFT);
+        assert_eq!(navigation.focus(), Control::Button(2));
+
+        /* Without a checkbox there is nothing after the last button. */
+        let mut navigation = Navigation::new(&Confirm::new("", ""));
+        navigation.on_key(Key::Tab, none);
+        navigation.on_key(Key::Tab, none);
+        assert_eq!(navigation.focus(), Control::Button(0));
+    }
+
+    #[test]
+    fn arrows_stay_on_the_buttons() {
+        let mut navigation = Navigation::new(&quit());
+        let none = Modifiers::default();
+
+        assert_eq!(navigation.on_key(Key::Right, none), Reaction::Changed);
+        assert_eq!(navigation.focus(), Control::Button(2));
+        navigation.on_key(Key::Left, none);
+        navigation.on_key(Key::Up, none);
+        navigation.on_key(Key::Left, none);
+        assert_eq!(navigation.focus(), Control::Button(0));
+
+        navigation.set_focus(Control::Checkbox);
+        assert_eq!(navigation.on_key(Key::Left, none), Reaction::Ignored);
+        assert_eq!(navigation.on_key(Key::Char('Q'), none), Reaction::Ignored);
+    }
+
+    #[test]
+    fn enter_space_and_escape_answer() {
+        let mut navigation = Navigation::new(&quit());
+        let none = Modifiers::default();
+
+        assert_eq!(
+            navigation.on_key(Key::Enter, none),
+            Reaction::Answered(Answer::Cancel)
+        );
+
+        navigation.set_focus(Control::Checkbox);
+        assert_eq!(navigation.on_key(Key::Space, none), Reaction::Changed);
+        assert!(navigation.is_checked());
+        assert_eq!(navigation.default_button(), 2);
+
+        navigation.on_key(Key::Tab, none);
+        assert_eq!(
+            navigation.on_key(Key::Space, none),
+            Reaction::Answered(Answer::Yes)
+        );
+        assert_eq!(
+            navigation.on_key(Key::Escape, none),
+            Reaction::Answered(Answer::Cancel)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "no buttons")]
+    fn a_dialog_needs_a_button() {
+        Confirm::new("Want to quit?", "Are you sure?").buttons(&[]);
+    }
+
+    #[test]
+    fn fields_set_by_hand_are_validated() {
+        assert!(quit().validate().is_ok());
+
+        let mut confirm = quit();
+        confirm.default = 3;
+        assert_eq!(
+            confirm.validate().unwrap_err().to_string(),
+            "\"Want to quit?\" has no button 3 to be the default"
+        );
+
+        confirm.buttons.clear();
+        assert_eq!(
+            confirm.validate().unwrap_err().to_string(),
+            "\"Want to quit?\" has no buttons"
+        );
+    }
+
+    #[test]
+    fn only_answers_other_than_cancel_are_remembered() {
+        let mut settings = Settings::default();
+        let confirm = quit();
+
+        confirm.remember(&mut settings, Answer::Cancel);
+        assert_eq!(confirm.remembered(&settings), None);
+
+        confirm.remember(&mut settings, Answer::Yes);
+        assert_eq!(confirm.remembered(&settings), Some(Answer::Yes));
+
+        /* Without a key there is neither a checkbox nor anything to keep. */
+        let plain = Confirm::new("Want to quit?", "Are you sure?");
+        plain.remember(&mut settings, Answer::Ok);
+        assert_eq!(plain.remembered(&settings), None);
+        assert_eq!(settings.confirmations.len(), 1);
+    }
+}
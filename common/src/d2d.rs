@@ -0,0 +1,611 @@
+use windows::{
+    core::*,
+    Foundation::Numerics::Matrix3x2,
+    Win32::{
+        Foundation::*,
+        Graphics::{Direct2D::Common::*, Direct2D::*, DirectWrite::*, Imaging::D2D::*, Imaging::*},
+        System::{
+            Com::*,
+            SystemServices::{GENERIC_READ, GENERIC_WRITE},
+        },
+    },
+};
+
+use crate::canvas::*;
+use crate::dpi::Dpi;
+
+/// Direct2D backend drawing into a window or, for screenshots, into a WIC
+/// bitmap.
+pub struct D2DCanvas {
+    factory: ID2D1Factory,
+    render_target: ID2D1RenderTarget,
+    target: Target,
+    imaging_factory: Option<IWICImagingFactory2>,
+    overlay: Option<String>,
+    /* Created on the first overlay; the brushes go with the render target. */
+    text_format: Option<(IDWriteFactory, IDWriteTextFormat)>,
+    overlay_brushes: Option<(ID2D1Brush, ID2D1Brush)>,
+}
+
+enum Target {
+    Window {
+        render_target: ID2D1HwndRenderTarget,
+        vsync: bool,
+    },
+    Bitmap(IWICBitmap),
+}
+
+const OVERLAY_FONT_SIZE: f32 = 14.;
+const OVERLAY_PADDING: f32 = 6.;
+
+impl D2DCanvas {
+    pub fn new(hwnd: HWND, width: u32, height: u32) -> Result<Self> {
+        Self::with_vsync(hwnd, width, height, true)
+    }
+
+    /// Without `vsync`, `end_draw` presents immediately instead of waiting
+    /// for the vertical blank.
+    pub fn with_vsync(hwnd: HWND, width: u32, height: u32, vsync: bool) -> Result<Self> {
+        let factory = Self::create_factory()?;
+        let render_target = Self::create_render_target(&factory, hwnd, width, height, vsync)?;
+
+        Self::with_target(
+            factory,
+            Target::Window {
+                render_target,
+                vsync,
+            },
+            None,
+        )
+    }
+
+    /// Draws offscreen into a `width` x `height` pixel bitmap; see
+    /// `save_png`.
+    pub fn for_bitmap(width: u32, height: u32, dpi: Dpi) -> Result<Self> {
+        let imaging_factory: IWICImagingFactory2 =
+            unsafe { CoCreateInstance(&CLSID_WICImagingFactory2, None, CLSCTX_INPROC_SERVER)? };
+
+        let bitmap = unsafe {
+            imaging_factory.CreateBitmap(
+                width,
+                height,
+                &GUID_WICPixelFormat32bppPBGRA,
+                WICBitmapCacheOnDemand,
+            )?
+        };
+
+        let mut canvas = Self::with_target(
+            Self::create_factory()?,
+            Target::Bitmap(bitmap),
+            Some(imaging_factory),
+        )?;
+        canvas.set_dpi(dpi);
+
+        Ok(canvas)
+    }
+
+    fn create_factory() -> Result<ID2D1Factory> {
+        let options = D2D1_FACTORY_OPTIONS::default();
+        unsafe { D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, Some(&options)) }
+    }
+
+    fn with_target(
+        factory: ID2D1Factory,
+        target: Target,
+        imaging_factory: Option<IWICImagingFactory2>,
+    ) -> Result<Self> {
+        let render_target = Self::drawing_target(&factory, &target)?;
+
+        Ok(D2DCanvas {
+            factory,
+            render_target,
+            target,
+            imaging_factory,
+            overlay: None,
+            text_format: None,
+            overlay_brushes: None,
+        })
+    }
+
+    fn create_render_target(
+        factory: &ID2D1Factory,
+        hwnd: HWND,
+        width: u32,
+        height: u32,
+        vsync: bool,
+    ) -> Result<ID2D1HwndRenderTarget> {
+        let properties = D2D1_RENDER_TARGET_PROPERTIES::default();
+        let hwnd_properties = D2D1_HWND_RENDER_TARGET_PROPERTIES {
+            hwnd,
+            pixelSize: D2D_SIZE_U { width, height },
+            presentOptions: if vsync {
+                D2D1_PRESENT_OPTIONS_NONE
+            } else {
+                D2D1_PRESENT_OPTIONS_IMMEDIATELY
+            },
+        };
+
+        unsafe { factory.CreateHwndRenderTarget(&properties, &hwnd_properties) }
+    }
+
+    /* The window target is drawn through its base interface; a bitmap gets
+    a software render target of its own. */
+    fn drawing_target(factory: &ID2D1Factory, target: &Target) -> Result<ID2D1RenderTarget> {
+        match target {
+            Target::Window { render_target, .. } => render_target.cast(),
+            Target::Bitmap(bitmap) => unsafe {
+                factory
+                    .CreateWicBitmapRenderTarget(bitmap, &D2D1_RENDER_TARGET_PROPERTIES::default())
+            },
+        }
+    }
+
+    pub fn render_target(&self) -> &ID2D1RenderTarget {
+        &self.render_target
+    }
+
+    pub fn pixel_size(&self) -> (u32, u32) {
+        let size = unsafe { self.render_target.GetPixelSize() };
+        (size.width, size.height)
+    }
+
+    /// Text drawn in the top left corner on top of every frame, e.g. the
+    /// HUD, until replaced with `None`.
+    pub fn set_overlay(&mut self, text: Option<String>) {
+        self.overlay = text;
+    }
+
+    fn draw_overlay(&mut self, text: &str) -> Result<()> {
+        if self.text_format.is_none() {
+            let factory: IDWriteFactory =
+                unsafe { DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)? };
+            let format = unsafe {
+                factory.CreateTextFormat(
+                    w!("Consolas"),
+                    None,
+                    DWRITE_FONT_WEIGHT_NORMAL,
+                    DWRITE_FONT_STYLE_NORMAL,
+                    DWRITE_FONT_STRETCH_NORMAL,
+                    OVERLAY_FONT_SIZE,
+                    w!("en-us"),
+                )?
+            };
+
+            self.text_format = Some((factory, format));
+        }
+
+        if self.overlay_brushes.is_none() {
+            let background = self.create_solid_brush(Color {
+                r: 0.,
+                g: 0.,
+                b: 0.,
+                a: 0.6,
+            })?;
+            let foreground = self.create_solid_brush(Color::rgb(1., 1., 0.6))?;
+
+            self.overlay_brushes = Some((background, foreground));
+        }
+
+        let (factory, format) = self.text_format.as_ref().unwrap();
+        let (background, foreground) = self.overlay_brushes.as_ref().unwrap();
+
+        let text: Vec<u16> = text.encode_utf16().collect();
+        let layout = unsafe { factory.CreateTextLayout(&text, format, f32::MAX, f32::MAX)? };
+        let metrics = unsafe { layout.GetMetrics()? };
+
+        let panel = D2D_RECT_F {
+            left: 0.,
+            top: 0.,
+            right: metrics.width + 2. * OVERLAY_PADDING,
+            bottom: metrics.height + 2. * OVERLAY_PADDING,
+        };
+        let origin = D2D_POINT_2F {
+            x: OVERLAY_PADDING,
+            y: OVERLAY_PADDING,
+        };
+
+        unsafe {
+            self.render_target.SetTransform(&Matrix3x2::identity());
+            self.render_target.FillRectangle(&panel, background);
+            self.render_target.DrawTextLayout(
+                origin,
+                &layout,
+                foreground,
+                D2D1_DRAW_TEXT_OPTIONS_NONE,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Whether the window is covered, so that presenting has no effect.
+    pub fn is_occluded(&self) -> bool {
+        match &self.target {
+            Target::Window { render_target, .. } => {
+                let state = unsafe { render_target.CheckWindowState() };
+                state.0 & D2D1_WINDOW_STATE_OCCLUDED.0 != 0
+            }
+            Target::Bitmap(_) => false,
+        }
+    }
+
+    /// Encodes what was drawn into a bitmap canvas as a PNG file.
+    pub fn save_png(&mut self, path: &std::path::Path) -> Result<()> {
+        let Target::Bitmap(bitmap) = &self.target else {
+            return Err(Error::new(
+                E_NOTIMPL,
+                "only bitmap canvases can be saved".into(),
+            ));
+        };
+
+        let bitmap = bitmap.clone();
+        let imaging_factory = self.imaging_factory()?;
+        let (width, height) = unsafe {
+            let (mut width, mut height) = (0, 0);
+            bitmap.GetSize(&mut width, &mut height)?;
+            (width, height)
+        };
+
+        unsafe {
+            let stream = imaging_factory.CreateStream()?;
+            stream.InitializeFromFilename(&HSTRING::from(path.as_os_str()), GENERIC_WRITE)?;
+
+            let encoder =
+                imaging_factory.CreateEncoder(&GUID_ContainerFormatPng, std::ptr::null())?;
+            encoder.Initialize(&stream, WICBitmapEncoderNoCache)?;
+
+            let mut frame = None;
+            encoder.CreateNewFrame(&mut frame, std::ptr::null_mut())?;
+            let frame = frame.unwrap();
+
+            frame.Initialize(None)?;
+            frame.SetSize(width, height)?;
+
+            /* The encoder may pick a different format and convert to it. */
+            let mut format = GUID_WICPixelFormat32bppBGRA;
+            frame.SetPixelFormat(&mut format)?;
+            frame.WriteSource(&bitmap, std::ptr::null())?;
+
+            frame.Commit()?;
+            encoder.Commit()?;
+        }
+
+        Ok(())
+    }
+
+    fn imaging_factory(&mut self) -> Result<&IWICImagingFactory2> {
+        if self.imaging_factory.is_none() {
+            self.imaging_factory = Some(unsafe {
+                CoCreateInstance(&CLSID_WICImagingFactory2, None, CLSCTX_INPROC_SERVER)?
+            });
+        }
+
+        Ok(self.imaging_factory.as_ref().unwrap())
+    }
+}
+
+fn color(color: Color) -> D2D1_COLOR_F {
+    let Color { r, g, b, a } = color;
+    D2D1_COLOR_F { r, g, b, a }
+}
+
+fn point(point: Point) -> D2D_POINT_2F {
+    D2D_POINT_2F {
+        x: point.x,
+        y: point.y,
+    }
+}
+
+fn rect(rect: &Rect) -> D2D_RECT_F {
+    D2D_RECT_F {
+        left: rect.left,
+        top: rect.top,
+        right: rect.right,
+        bottom: rect.bottom,
+    }
+}
+
+fn ellipse(ellipse: &Ellipse) -> D2D1_ELLIPSE {
+    D2D1_ELLIPSE {
+        point: point(ellipse.point),
+        radiusX: ellipse.radius_x,
+        radiusY: ellipse.radius_y,
+    }
+}
+
+fn matrix(matrix: &Matrix) -> Matrix3x2 {
+    Matrix3x2 {
+        M11: matrix.m11,
+        M12: matrix.m12,
+        M21: matrix.m21,
+        M22: matrix.m22,
+        M31: matrix.m31,
+        M32: matrix.m32,
+    }
+}
+
+fn bezier(bezier: &BezierSegment) -> D2D1_BEZIER_SEGMENT {
+    D2D1_BEZIER_SEGMENT {
+        point1: point(bezier.point1),
+        point2: point(bezier.point2),
+        point3: point(bezier.point3),
+    }
+}
+
+fn arc(arc: &ArcSegment) -> D2D1_ARC_SEGMENT {
+    D2D1_ARC_SEGMENT {
+        point: point(arc.point),
+        size: D2D_SIZE_F {
+            width: arc.size.width,
+            height: arc.size.height,
+        },
+        rotationAngle: arc.rotation_angle,
+        sweepDirection: match arc.sweep_direction {
+            SweepDirection::Clockwise => D2D1_SWEEP_DIRECTION_CLOCKWISE,
+            SweepDirection::CounterClockwise => D2D1_SWEEP_DIRECTION_COUNTER_CLOCKWISE,
+        },
+        arcSize: match arc.arc_size {
+            ArcSize::Small => D2D1_ARC_SIZE_SMALL,
+            ArcSize::Large => D2D1_ARC_SIZE_LARGE,
+        },
+    }
+}
+
+impl Canvas for D2DCanvas {
+    type Brush = ID2D1Brush;
+    type Geometry = ID2D1PathGeometry;
+    type Bitmap = ID2D1Bitmap;
+
+    fn create_solid_brush(&mut self, color: Color) -> crate::Result<Self::Brush> {
+        let brush = unsafe {
+            self.render_target
+                .CreateSolidColorBrush(&self::color(color), None)?
+        };
+
+        Ok(brush.cast()?)
+    }
+
+    fn create_radial_gradient_brush(
+        &mut self,
+        gradient: &RadialGradient,
+    ) -> crate::Result<Self::Brush> {
+        let gradient_stops: Vec<_> = gradient
+            .stops
+            .iter()
+            .map(|stop| D2D1_GRADIENT_STOP {
+                position: stop.position,
+                color: color(stop.color),
+            })
+            .collect();
+
+        let gradient_collection = unsafe {
+            self.render_target.CreateGradientStopCollection(
+                &gradient_stops,
+                D2D1_GAMMA_2_2,
+                D2D1_EXTEND_MODE_CLAMP,
+            )?
+        };
+
+        let brush = unsafe {
+            self.render_target.CreateRadialGradientBrush(
+                &D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES {
+                    center: point(gradient.center),
+                    radiusX: gradient.radius_x,
+                    radiusY: gradient.radius_y,
+                    gradientOriginOffset: point(gradient.gradient_origin_offset),
+                },
+                None,
+                &gradient_collection,
+            )?
+        };
+
+        Ok(brush.cast()?)
+    }
+
+    fn create_geometry(&mut self, path: &Path) -> crate::Result<Self::Geometry> {
+        let geometry = unsafe { self.factory.CreatePathGeometry()? };
+        let sink = unsafe { geometry.Open()? };
+
+        for figure in &path.figures {
+            unsafe {
+                sink.BeginFigure(point(figure.start), D2D1_FIGURE_BEGIN_FILLED);
+
+                for segment in &figure.segments {
+                    match segment {
+                        Segment::Line(to) => sink.AddLine(point(*to)),
+                        Segment::Bezier(segment) => sink.AddBezier(&bezier(segment)),
+                        Segment::Arc(segment) => sink.AddArc(&arc(segment)),
+                    }
+                }
+
+                sink.EndFigure(match figure.end {
+                    FigureEnd::Open => D2D1_FIGURE_END_OPEN,
+                    FigureEnd::Closed => D2D1_FIGURE_END_CLOSED,
+                });
+            }
+        }
+
+        unsafe { sink.Close()? };
+
+        Ok(geometry)
+    }
+
+    fn load_bitmap(&mut self, path: &std::path::Path) -> crate::Result<Self::Bitmap> {
+        let imaging_factory = self.imaging_factory()?;
+        let uri = HSTRING::from(path.as_os_str());
+
+        let decoder = unsafe {
+            imaging_factory.CreateDecoderFromFilename(
+                &uri,
+                std::ptr::null(),
+                GENERIC_READ,
+                WICDecodeMetadataCacheOnLoad,
+            )?
+        };
+
+        let frame_decoder = unsafe { decoder.GetFrame(0)? };
+        let format_converter = unsafe { imaging_factory.CreateFormatConverter()? };
+
+        unsafe {
+            format_converter.Initialize(
+                &frame_decoder,
+                &GUID_WICPixelFormat32bppPBGRA,
+                WICBitmapDitherTypeNone,
+                None,
+                0.,
+                WICBitmapPaletteTypeMedianCut,
+            )?
+        };
+
+        let bitmap = unsafe {
+            self.render_target
+                .CreateBitmapFromWicBitmap(&format_converter, None)?
+        };
+
+        Ok(bitmap)
+    }
+
+    fn bitmap_size(&self, bitmap: &Self::Bitmap) -> Size {
+        let size = unsafe { bitmap.GetSize() };
+
+        Size {
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    fn size(&self) -> Size {
+        let size = unsafe { self.render_target.GetSize() };
+
+        Size {
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> crate::Result<()> {
+        match &self.target {
+            Target::Window { render_target, .. } => unsafe {
+                render_target.Resize(&D2D_SIZE_U { width, height })?
+            },
+            Target::Bitmap(_) => return Err(crate::Error::InvalidSize { width, height }),
+        }
+
+        Ok(())
+    }
+
+    fn set_dpi(&mut self, dpi: Dpi) {
+        let dpi = dpi.0 as f32;
+        unsafe { self.render_target.SetDpi(dpi, dpi) }
+    }
+
+    fn begin_draw(&mut self) {
+        unsafe { self.render_target.BeginDraw() }
+    }
+
+    fn end_draw(&mut self) -> crate::Result<()> {
+        /* Drawing must end even when the overlay could not be drawn. */
+        let overlay = match self.overlay.take() {
+            Some(text) => {
+                let result = self.draw_overlay(&text);
+                self.overlay = Some(text);
+                result
+            }
+            None => Ok(()),
+        };
+
+        match unsafe { self.render_target.EndDraw(None, None) } {
+            Err(error) if error.code() == D2DERR_RECREATE_TARGET => Err(crate::Error::DeviceLost),
+            result => Ok(result.and(overlay)?),
+        }
+    }
+
+    fn recreate_target(&mut self) -> crate::Result<()> {
+        let dpi = {
+            let (mut dpi_x, mut dpi_y) = (0., 0.);
+            unsafe { self.render_target.GetDpi(&mut dpi_x, &mut dpi_y) };
+            Dpi(dpi_x.round() as u32)
+        };
+
+        if let Target::Window {
+            render_target,
+            vsync,
+        } = &mut self.target
+        {
+            let (hwnd, size) = unsafe { (render_target.GetHwnd(), render_target.GetPixelSize()) };
+            *render_target =
+                Self::create_render_target(&self.factory, hwnd, size.width, size.height, *vsync)?;
+        }
+
+        self.render_target = Self::drawing_target(&self.factory, &self.target)?;
+
+        self.set_dpi(dpi);
+        self.overlay_brushes = None;
+        Ok(())
+    }
+
+    fn set_transform(&mut self, transform: &Matrix) {
+        unsafe { self.render_target.SetTransform(&matrix(transform)) }
+    }
+
+    fn clear(&mut self, color: Color) {
+        unsafe { self.render_target.Clear(Some(&self::color(color))) }
+    }
+
+    fn draw_line(&mut self, p0: Point, p1: Point, brush: &Self::Brush, stroke_width: f32) {
+        unsafe {
+            self.render_target
+                .DrawLine(point(p0), point(p1), brush, stroke_width, None)
+        }
+    }
+
+    fn fill_rectangle(&mut self, rect: &Rect, brush: &Self::Brush) {
+        unsafe { self.render_target.FillRectangle(&self::rect(rect), brush) }
+    }
+
+    fn fill_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush) {
+        unsafe { self.render_target.FillGeometry(geometry, brush, None) }
+    }
+
+    fn draw_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush, stroke_width: f32) {
+        unsafe {
+            self.render_target
+                .DrawGeometry(geometry, brush, stroke_width, None)
+        }
+    }
+
+    fn fill_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush) {
+        unsafe {
+            self.render_target
+                .FillEllipse(&self::ellipse(ellipse), brush)
+        }
+    }
+
+    fn draw_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush, stroke_width: f32) {
+        unsafe {
+            self.render_target
+                .DrawEllipse(&self::ellipse(ellipse), brush, stroke_width, None)
+        }
+    }
+
+    fn draw_bitmap(
+        &mut self,
+        bitmap: &Self::Bitmap,
+        destination: Option<&Rect>,
+        opacity: f32,
+        source: Option<&Rect>,
+    ) {
+        let destination = destination.map(rect);
+        let source = source.map(rect);
+
+        unsafe {
+            self.render_target.DrawBitmap(
+                bitmap,
+                destination.as_ref().map(|rect| rect as *const _),
+                opacity,
+                D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+                source.as_ref().map(|rect| rect as *const _),
+            );
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::input::InputEvent;
+use crate::software::SoftwareCanvas;
+use crate::Result;
+
+/// A demo run in-process by a host that owns the window, like the gallery.
+/// The demo keeps its own state and canvas, so it picks up where it left
+/// off whenever the host shows it again.
+pub trait Demo {
+    fn name(&self) -> &str;
+
+    /// Advances the animation by `dt` seconds.
+    fn update(&mut self, dt: f64);
+
+    /// Draws the current state into [`Demo::canvas`].
+    fn render(&mut self) -> Result<()>;
+
+    /// The last frame drawn by [`Demo::render`].
+    fn canvas(&self) -> &SoftwareCanvas;
+
+    /// New canvas size, in pixels.
+    fn resize(&mut self, width: u32, height: u32) -> Result<()>;
+
+    /// How many steps one second of animation takes with the `.` key of
+    /// [`Playback`](crate::playback::Playback).
+    fn steps_per_second(&self) -> u32 {
+        60
+    }
+
+    /// Keyboard, mouse and focus input while the demo is shown. Mouse
+    /// coordinates are canvas pixels.
+    fn on_input(&mut self, _event: InputEvent) {}
+}
@@ -0,0 +1,330 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::canvas::*;
+use crate::dpi::Dpi;
+use crate::{Error, Result};
+
+/// Brush as it was created: the display list keeps the description, not a
+/// device object.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Brush {
+    Solid { color: Color },
+    RadialGradient(RadialGradient),
+}
+
+/// Bitmap referenced by the file it was loaded from.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Bitmap {
+    pub path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// One drawing call with its arguments.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    Clear {
+        color: Color,
+    },
+    DrawLine {
+        from: Point,
+        to: Point,
+        brush: Brush,
+        stroke_width: f32,
+    },
+    DrawPolyline {
+        points: Vec<Point>,
+        brush: Brush,
+        stroke_width: f32,
+    },
+    FillRectangle {
+        rect: Rect,
+        brush: Brush,
+    },
+    FillGeometry {
+        geometry: Path,
+        brush: Brush,
+    },
+    DrawGeometry {
+        geometry: Path,
+        brush: Brush,
+        stroke_width: f32,
+    },
+    FillEllipse {
+        ellipse: Ellipse,
+        brush: Brush,
+    },
+    DrawEllipse {
+        ellipse: Ellipse,
+        brush: Brush,
+        stroke_width: f32,
+    },
+    DrawBitmap {
+        bitmap: Bitmap,
+        destination: Option<Rect>,
+        opacity: f32,
+        source: Option<Rect>,
+    },
+}
+
+/// An operation and the transform in effect when it was issued.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Command {
+    #[serde(flatten)]
+    pub operation: Operation,
+    pub transform: Matrix,
+}
+
+/// Recording backend: keeps the drawing calls of the last frame as a list of
+/// commands instead of rendering them, so tests can check what was drawn
+/// without comparing pixels.
+pub struct DisplayListCanvas {
+    width: u32,
+    height: u32,
+    dpi: Dpi,
+    transform: Matrix,
+    commands: Vec<Command>,
+}
+
+impl DisplayListCanvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        DisplayListCanvas {
+            width,
+            height,
+            dpi: Dpi::DEFAULT,
+            transform: Matrix::identity(),
+            commands: Vec::new(),
+        }
+    }
+
+    /// Commands issued since the last `begin_draw`, in order.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.commands).unwrap()
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> Result<()> {
+        Ok(fs::write(path, self.to_json())?)
+    }
+
+    fn push(&mut self, operation: Operation) {
+        self.commands.push(Command {
+            operation,
+            transform: self.transform,
+        });
+    }
+}
+
+impl Canvas for DisplayListCanvas {
+    type Brush = Brush;
+    type Geometry = Path;
+    type Bitmap = Bitmap;
+
+    fn create_solid_brush(&mut self, color: Color) -> Result<Self::Brush> {
+        Ok(Brush::Solid { color })
+    }
+
+    fn create_radial_gradient_brush(&mut self, gradient: &RadialGradient) -> Result<Self::Brush> {
+        Ok(Brush::RadialGradient(gradient.clone()))
+    }
+
+    fn create_geometry(&mut self, path: &Path) -> Result<Self::Geometry> {
+        Ok(path.clone())
+    }
+
+    /* Decoded only for its size, and to fail where the other backends do. */
+    fn load_bitmap(&mut self, path: &std::path::Path) -> Result<Self::Bitmap> {
+        let pixmap = tiny_skia::Pixmap::load_png(path)
+            .map_err(|error| Error::Image(format!("{}: {error}", path.display())))?;
+
+        Ok(Bitmap {
+            path: path.to_path_buf(),
+            width: pixmap.width(),
+            height: pixmap.height(),
+        })
+    }
+
+    fn bitmap_size(&self, bitmap: &Self::Bitmap) -> Size {
+        Size {
+            width: bitmap.width as f32,
+            height: bitmap.height as f32,
+        }
+    }
+
+    fn size(&self) -> Size {
+        Size {
+            width: self.dpi.to_dips(self.width as f32),
+            height: self.dpi.to_dips(self.height as f32),
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    fn set_dpi(&mut self, dpi: Dpi) {
+        self.dpi = dpi;
+    }
+
+    fn begin_draw(&mut self) {
+        self.commands.clear();
+        self.transform = Matrix::identity();
+    }
+
+    fn end_draw(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /* Nothing to lose. */
+    fn recreate_target(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_transform(&mut self, transform: &Matrix) {
+        self.transform = *transform;
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.push(Operation::Clear { color });
+    }
+
+    fn draw_line(&mut self, p0: Point, p1: Point, brush: &Self::Brush, stroke_width: f32) {
+        self.push(Operation::DrawLine {
+            from: p0,
+            to: p1,
+            brush: brush.clone(),
+            stroke_width,
+        });
+    }
+
+    fn draw_polyline(&mut self, points: &[Point], brush: &Self::Brush, stroke_width: f32) {
+        self.push(Operation::DrawPolyline {
+            points: points.to_vec(),
+            brush: brush.clone(),
+            stroke_width,
+        });
+    }
+
+    fn fill_rectangle(&mut self, rect: &Rect, brush: &Self::Brush) {
+        self.push(Operation::FillRectangle {
+            rect: *rect,
+            brush: brush.clone(),
+        });
+    }
+
+    fn fill_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush) {
+        self.push(Operation::FillGeometry {
+            geometry: geometry.clone(),
+            brush: brush.clone(),
+        });
+    }
+
+    fn draw_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush, stroke_width: f32) {
+        self.push(Operation::DrawGeometry {
+            geometry: geometry.clone(),
+            brush: brush.clone(),
+            stroke_width,
+        });
+    }
+
+    fn fill_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush) {
+        self.push(Operation::FillEllipse {
+            ellipse: *ellipse,
+            brush: brush.clone(),
+        });
+    }
+
+    fn draw_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush, stroke_width: f32) {
+        self.push(Operation::DrawEllipse {
+            ellipse: *ellipse,
+            brush: brush.clone(),
+            stroke_width,
+        });
+    }
+
+    fn draw_bitmap(
+        &mut self,
+        bitmap: &Self::Bitmap,
+        destination: Option<&Rect>,
+        opacity: f32,
+        source: Option<&Rect>,
+    ) {
+        self.push(Operation::DrawBitmap {
+            bitmap: bitmap.clone(),
+            destination: destination.copied(),
+            opacity,
+            source: source.copied(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_commands_of_the_last_frame() {
+        let mut canvas = DisplayListCanvas::new(100, 100);
+        let brush = canvas.create_solid_brush(Color::rgb(1., 0., 0.)).unwrap();
+
+        canvas.begin_draw();
+        canvas.clear(Color::default());
+        canvas.end_draw().unwrap();
+
+        canvas.begin_draw();
+        canvas.set_transform(&Matrix::translation(5., 6.));
+        canvas.draw_line(Point { x: 0., y: 0. }, Point { x: 1., y: 2. }, &brush, 3.);
+        canvas.end_draw().unwrap();
+
+        assert_eq!(
+            canvas.commands(),
+            [Command {
+                operation: Operation::DrawLine {
+                    from: Point { x: 0., y: 0. },
+                    to: Point { x: 1., y: 2. },
+                    brush: brush.clone(),
+                    stroke_width: 3.,
+                },
+                transform: Matrix::translation(5., 6.),
+            }]
+        );
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let mut canvas = DisplayListCanvas::new(100, 100);
+        let brush = canvas.create_solid_brush(Color::rgb(1., 0., 0.)).unwrap();
+
+        canvas.begin_draw();
+        canvas.fill_ellipse(
+            &Ellipse {
+                point: Point { x: 10., y: 20. },
+                radius_x: 3.,
+                radius_y: 4.,
+            },
+            &brush,
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&canvas.to_json()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "op": "fill_ellipse",
+                "ellipse": { "point": { "x": 10.0, "y": 20.0 }, "radius_x": 3.0, "radius_y": 4.0 },
+                "brush": { "kind": "solid", "color": { "r": 1.0, "g": 0.0, "b": 0.0, "a": 1.0 } },
+                "transform": { "m11": 1.0, "m12": 0.0, "m21": 0.0, "m22": 1.0, "m31": 0.0, "m32": 0.0 }
+            }])
+        );
+    }
+}
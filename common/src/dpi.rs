@@ -0,0 +1,75 @@
+/// Dots per inch of a monitor; 96 DPI is 100% scale, where one device
+/// independent pixel (DIP) is one physical pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dpi(pub u32);
+
+impl Dpi {
+    pub const DEFAULT: Dpi = Dpi(96);
+
+    /// Physical pixels per device independent pixel.
+    pub fn scale(self) -> f32 {
+        self.0 as f32 / Self::DEFAULT.0 as f32
+    }
+
+    pub fn to_dips(self, pixels: f32) -> f32 {
+        pixels / self.scale()
+    }
+
+    pub fn to_pixels(self, dips: f32) -> f32 {
+        dips * self.scale()
+    }
+
+    /// Whole pixels covering `dips`, for window and surface sizes.
+    pub fn to_pixels_ceil(self, dips: i32) -> i32 {
+        (dips as i64 * self.0 as i64 + Self::DEFAULT.0 as i64 - 1)
+            .div_euclid(Self::DEFAULT.0 as i64) as i32
+    }
+
+    /// The new DPI from the `wparam` of `WM_DPICHANGED`. Both words carry the
+    /// same value; the low word is the X axis.
+    pub fn from_dpi_changed(wparam: usize) -> Dpi {
+        Dpi((wparam & 0xFFFF) as u32)
+    }
+}
+
+impl Default for Dpi {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_dpi_is_identity() {
+        assert_eq!(Dpi::default().scale(), 1.);
+        assert_eq!(Dpi::DEFAULT.to_dips(123.), 123.);
+        assert_eq!(Dpi::DEFAULT.to_pixels_ceil(800), 800);
+    }
+
+    #[test]
+    fn converts_between_pixels_and_dips() {
+        let dpi = Dpi(144);
+
+        assert_eq!(dpi.scale(), 1.5);
+        assert_eq!(dpi.to_pixels(100.), 150.);
+        assert_eq!(dpi.to_dips(150.), 100.);
+        assert_eq!(dpi.to_dips(-30.), -20.);
+    }
+
+    #[test]
+    fn sizes_round_up_to_whole_pixels() {
+        assert_eq!(Dpi(120).to_pixels_ceil(1400), 1750);
+        assert_eq!(Dpi(120).to_pixels_ceil(1), 2);
+        assert_eq!(Dpi(168).to_pixels_ceil(601), 1052);
+        assert_eq!(Dpi(144).to_pixels_ceil(0), 0);
+    }
+
+    #[test]
+    fn decodes_dpi_changed_wparam() {
+        assert_eq!(Dpi::from_dpi_changed(0x0090_0090), Dpi(144));
+        assert_eq!(Dpi::from_dpi_changed(0x0078_0078), Dpi(120));
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Image(String),
+    Usage(String),
+    /// A configuration file that could not be read, parsed or validated.
+    Config {
+        path: std::path::PathBuf,
+        message: String,
+    },
+    /// The rendering device went away (driver update, remote desktop
+    /// reconnect, ...). Brushes and bitmaps of the canvas are gone with it;
+    /// see `Canvas::recreate_target`.
+    DeviceLost,
+    InvalidSize {
+        width: u32,
+        height: u32,
+    },
+    /// Failure of the portable windowing backend or its display server.
+    Window(String),
+    #[cfg(windows)]
+    Windows(windows::core::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "I/O error: {error}"),
+            Error::Image(message) => write!(f, "image error: {message}"),
+            Error::Usage(message) => write!(f, "{message}"),
+            Error::Config { path, message } => {
+                write!(f, "invalid configuration {}:\n{message}", path.display())
+            }
+            Error::DeviceLost => write!(f, "rendering device lost"),
+            Error::InvalidSize { width, height } => {
+                write!(f, "invalid surface size {width}x{height}")
+            }
+            Error::Window(message) => write!(f, "window system error: {message}"),
+            #[cfg(windows)]
+            Error::Windows(error) => write!(f, "Windows error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(windows)]
+impl From<windows::core::Error> for Error {
+    fn from(error: windows::core::Error) -> Self {
+        Error::Windows(error)
+    }
+}
+
+/* Lets the Win32 side keep returning `windows::core::Result` while calling
+into the platform-neutral code with `?`. */
+#[cfg(windows)]
+impl From<Error> for windows::core::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Windows(error) => error,
+            Error::DeviceLost => windows::Win32::Foundation::D2DERR_RECREATE_TARGET.into(),
+            other => windows::core::Error::new(
+                windows::Win32::Foundation::E_FAIL,
+                windows::core::HSTRING::from(other.to_string()),
+            ),
+        }
+    }
+}
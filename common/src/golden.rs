@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use tiny_skia::{Pixmap, PremultipliedColorU8};
+
+use crate::software::SoftwareCanvas;
+
+/// Set to regenerate the reference images instead of comparing against them.
+pub const UPDATE_VARIABLE: &str = "UPDATE_GOLDEN";
+
+/// Result of comparing a frame against its reference image.
+#[derive(Debug, PartialEq)]
+pub struct Comparison {
+    /// Pixels with at least one channel further than the tolerance.
+    pub mismatched_pixels: usize,
+    /// Largest per-channel difference seen anywhere in the frame.
+    pub max_difference: u8,
+}
+
+/// Compares two pixmaps channel by channel. Pixels whose channels all lie
+/// within `tolerance` count as equal. The returned diff image shows the
+/// expected frame faded out, with mismatched pixels in red.
+pub fn compare(actual: &Pixmap, expected: &Pixmap, tolerance: u8) -> (Comparison, Pixmap) {
+    assert_eq!(
+        (actual.width(), actual.height()),
+        (expected.width(), expected.height()),
+        "frame size differs from the reference"
+    );
+
+    let mut diff = Pixmap::new(actual.width(), actual.height()).unwrap();
+    let mut comparison = Comparison {
+        mismatched_pixels: 0,
+        max_difference: 0,
+    };
+
+    let pixels = actual.pixels().iter().zip(expected.pixels());
+
+    for (out, (a, e)) in diff.pixels_mut().iter_mut().zip(pixels) {
+        let difference = [
+            a.red().abs_diff(e.red()),
+            a.green().abs_diff(e.green()),
+            a.blue().abs_diff(e.blue()),
+            a.alpha().abs_diff(e.alpha()),
+        ]
+        .into_iter()
+        .max()
+        .unwrap();
+
+        comparison.max_difference = comparison.max_difference.max(difference);
+
+        *out = if difference > tolerance {
+            comparison.mismatched_pixels += 1;
+            PremultipliedColorU8::from_rgba(255, 0, 0, 255).unwrap()
+        } else {
+            let e = e.demultiply();
+            let luma = (e.red() as u32 + e.green() as u32 + e.blue() as u32) / 3;
+            let faded = (192 + luma / 4) as u8;
+            PremultipliedColorU8::from_rgba(faded, faded, faded, 255).unwrap()
+        };
+    }
+
+    (comparison, diff)
+}
+
+fn sibling(reference: &Path, suffix: &str) -> PathBuf {
+    let stem = reference.file_stem().unwrap().to_string_lossy();
+    reference.with_file_name(format!("{stem}.{suffix}.png"))
+}
+
+/// Panics unless `canvas` matches the PNG at `reference` within `tolerance`.
+/// On failure the rendered frame and the diff image are saved next to the
+/// reference as `<name>.actual.png` and `<name>.diff.png`.
+pub fn assert_matches(canvas: &SoftwareCanvas, reference: &Path, tolerance: u8) {
+    let actual = canvas.pixmap();
+
+    if std::env::var_os(UPDATE_VARIABLE).is_some() {
+        std::fs::create_dir_all(reference.parent().unwrap()).unwrap();
+        actual.save_png(reference).unwrap();
+        return;
+    }
+
+    let expected = Pixmap::load_png(reference).unwrap_or_else(|error| {
+        panic!(
+            "cannot load reference {}: {error}; run with {UPDATE_VARIABLE}=1 to create it",
+            reference.display()
+        )
+    });
+
+    let (comparison, diff) = compare(actual, &expected, tolerance);
+
+    if comparison.mismatched_pixels > 0 {
+        let actual_path = sibling(reference, "actual");
+        let diff_path = sibling(reference, "diff");
+        actual.save_png(&actual_path).unwrap();
+        diff.save_png(&diff_path).unwrap();
+
+        panic!(
+            "{} pixels differ from {} by more than {tolerance} (max {}); see {} and {}",
+            comparison.mismatched_pixels,
+            reference.display(),
+            comparison.max_difference,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(r: u8, g: u8, b: u8) -> Pixmap {
+        let mut pixmap = Pixmap::new(4, 3).unwrap();
+        pixmap.fill(tiny_skia::Color::from_rgba8(r, g, b, 255));
+        pixmap
+    }
+
+    #[test]
+    fn differences_within_tolerance_match() {
+        let (comparison, _) = compare(&filled(100, 100, 100), &filled(102, 99, 100), 2);
+
+        assert_eq!(
+            comparison,
+            Comparison {
+                mismatched_pixels: 0,
+                max_difference: 2
+            }
+        );
+    }
+
+    #[test]
+    fn mismatched_pixels_are_red_in_diff() {
+        let expected = filled(100, 100, 100);
+        let mut actual = expected.clone();
+        actual.pixels_mut()[5] = PremultipliedColorU8::from_rgba(0, 100, 100, 255).unwrap();
+
+        let (comparison, diff) = compare(&actual, &expected, 2);
+
+        assert_eq!(comparison.mismatched_pixels, 1);
+        assert_eq!(comparison.max_difference, 100);
+        assert_eq!(
+            diff.pixels()[5],
+            PremultipliedColorU8::from_rgba(255, 0, 0, 255).unwrap()
+        );
+        assert_ne!(diff.pixels()[0], diff.pixels()[5]);
+    }
+}
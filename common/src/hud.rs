@@ -0,0 +1,107 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::dpi::Dpi;
+use crate::stats::FrameStats;
+
+/// Frame statistics overlay of the Direct2D demos, toggled with F3.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Hud {
+    visible: bool,
+}
+
+impl Hud {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// The overlay lines, or `None` while the HUD is hidden. `pixel_size` is
+    /// the size of the render target.
+    pub fn text(&self, stats: &FrameStats, pixel_size: (u32, u32), dpi: Dpi) -> Option<String> {
+        self.visible.then(|| text(stats, pixel_size, dpi))
+    }
+}
+
+fn milliseconds(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:.1}", duration.as_secs_f64() * 1000.),
+        None => "-".to_string(),
+    }
+}
+
+fn text(stats: &FrameStats, (width, height): (u32, u32), dpi: Dpi) -> String {
+    let mut text = String::new();
+
+    match stats.fps() {
+        Some(fps) => writeln!(text, "FPS    {fps:.1}"),
+        None => writeln!(text, "FPS    -"),
+    }
+    .unwrap();
+
+    writeln!(
+        text,
+        "frame  {} ms  p50 {}  p95 {}  p99 {}",
+        milliseconds(stats.last()),
+        milliseconds(stats.percentile(50.)),
+        milliseconds(stats.percentile(95.)),
+        milliseconds(stats.percentile(99.)),
+    )
+    .unwrap();
+
+    writeln!(text, "target {width}x{height} px").unwrap();
+    write!(text, "DPI    {} ({:.0}%)", dpi.0, dpi.scale() * 100.).unwrap();
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_until_toggled() {
+        let mut hud = Hud::default();
+        let stats = FrameStats::new();
+
+        assert_eq!(hud.text(&stats, (800, 600), Dpi::DEFAULT), None);
+
+        hud.toggle();
+        assert!(hud.is_visible());
+        assert!(hud.text(&stats, (800, 600), Dpi::DEFAULT).is_some());
+
+        hud.toggle();
+        assert_eq!(hud.text(&stats, (800, 600), Dpi::DEFAULT), None);
+    }
+
+    #[test]
+    fn formats_stats_size_and_dpi() {
+        let mut stats = FrameStats::new();
+
+        for milliseconds in [16, 16, 16, 20] {
+            stats.record(Duration::from_millis(milliseconds));
+        }
+
+        assert_eq!(
+            text(&stats, (1200, 900), Dpi(144)),
+            "FPS    58.8\n\
+             frame  20.0 ms  p50 16.0  p95 20.0  p99 20.0\n\
+             target 1200x900 px\n\
+             DPI    144 (150%)"
+        );
+    }
+
+    #[test]
+    fn placeholders_before_the_first_frame() {
+        assert_eq!(
+            text(&FrameStats::new(), (800, 600), Dpi::DEFAULT),
+            "FPS    -\n\
+             frame  - ms  p50 -  p95 -  p99 -\n\
+             target 800x600 px\n\
+             DPI    96 (100%)"
+        );
+    }
+}
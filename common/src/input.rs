@@ -0,0 +1,359 @@
+use crate::win32::*;
+
+/// Keyboard, mouse and focus input of a demo window. Mouse positions are in
+/// client coordinates, in physical pixels, and may be negative or beyond the
+/// client area while the mouse is captured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    MouseMove {
+        x: i32,
+        y: i32,
+    },
+    MouseDown {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    },
+    MouseUp {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    },
+    /// In notches of the wheel, positive away from the user or to the right.
+    MouseWheel {
+        delta: f32,
+        horizontal: bool,
+    },
+    /// The cursor left the client area.
+    MouseLeave,
+    KeyDown {
+        key: Key,
+        modifiers: Modifiers,
+        /// Generated by auto-repeat while the key is held.
+        repeat: bool,
+    },
+    KeyUp {
+        key: Key,
+        modifiers: Modifiers,
+    },
+    FocusGained,
+    FocusLost,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Escape,
+    Enter,
+    Space,
+    Tab,
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    /// F1 to F24.
+    F(u8),
+    /// Letter keys as upper case, digit keys of the main row, and `+`, `-`
+    /// and `.` of the main row or the keypad.
+    Char(char),
+    /// Platform key code of anything else.
+    Other(u32),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+fn low_word(value: usize) -> u16 {
+    (value & 0xFFFF) as u16
+}
+
+fn high_word(value: usize) -> u16 {
+    ((value >> 16) & 0xFFFF) as u16
+}
+
+/* GET_X_LPARAM / GET_Y_LPARAM: the words are signed, left of or above a
+multi-monitor origin they are negative. */
+fn position(lparam: isize) -> (i32, i32) {
+    let lparam = lparam as usize;
+    (
+        low_word(lparam) as i16 as i32,
+        high_word(lparam) as i16 as i32,
+    )
+}
+
+fn key(virtual_key: u32) -> Key {
+    match virtual_key {
+        VK_BACK => Key::Backspace,
+        VK_TAB => Key::Tab,
+        VK_RETURN => Key::Enter,
+        VK_ESCAPE => Key::Escape,
+        VK_SPACE => Key::Space,
+        VK_LEFT => Key::Left,
+        VK_UP => Key::Up,
+        VK_RIGHT => Key::Right,
+        VK_DOWN => Key::Down,
+        VK_F1..=VK_F24 => Key::F((virtual_key - VK_F1 + 1) as u8),
+        0x30..=0x39 | 0x41..=0x5A => Key::Char(char::from(virtual_key as u8)),
+        /* The `=` key, `+` with Shift on US layouts. */
+        VK_OEM_PLUS | VK_ADD => Key::Char('+'),
+        VK_OEM_MINUS | VK_SUBTRACT => Key::Char('-'),
+        VK_OEM_PERIOD | VK_DECIMAL => Key::Char('.'),
+        other => Key::Other(other),
+    }
+}
+
+/// The virtual key that [`decode_win32`] reports as `key`; `None` for
+/// characters without a key of their own.
+pub fn virtual_key(key: Key) -> Option<u32> {
+    let virtual_key = match key {
+        Key::Backspace => VK_BACK,
+        Key::Tab => VK_TAB,
+        Key::Enter => VK_RETURN,
+        Key::Escape => VK_ESCAPE,
+        Key::Space => VK_SPACE,
+        Key::Left => VK_LEFT,
+        Key::Up => VK_UP,
+        Key::Right => VK_RIGHT,
+        Key::Down => VK_DOWN,
+        Key::F(n @ 1..=24) => VK_F1 + n as u32 - 1,
+        Key::F(_) => return None,
+        Key::Char(c @ ('0'..='9' | 'A'..='Z')) => c as u32,
+        Key::Char('+') => VK_OEM_PLUS,
+        Key::Char('-') => VK_OEM_MINUS,
+        Key::Char('.') => VK_OEM_PERIOD,
+        Key::Char(_) => return None,
+        Key::Other(virtual_key) => virtual_key,
+    };
+
+    Some(virtual_key)
+}
+
+/// Decodes a Win32 window message into an input event; `None` for anything
+/// that is not input. The state of the modifier keys is not part of key
+/// messages, so the caller passes it in.
+pub fn decode_win32(
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    modifiers: Modifiers,
+) -> Option<InputEvent> {
+    let (x, y) = position(lparam);
+
+    let button = match message {
+        WM_LBUTTONDOWN | WM_LBUTTONUP => Some(MouseButton::Left),
+        WM_RBUTTONDOWN | WM_RBUTTONUP => Some(MouseButton::Right),
+        WM_MBUTTONDOWN | WM_MBUTTONUP => Some(MouseButton::Middle),
+        WM_XBUTTONDOWN | WM_XBUTTONUP => Some(match high_word(wparam) {
+            XBUTTON1 => MouseButton::Back,
+            _ => MouseButton::Forward,
+        }),
+        _ => None,
+    };
+
+    let event = match message {
+        WM_MOUSEMOVE => InputEvent::MouseMove { x, y },
+        WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN | WM_XBUTTONDOWN => {
+            InputEvent::MouseDown {
+                button: button?,
+                x,
+                y,
+            }
+        }
+        WM_LBUTTONUP | WM_RBUTTONUP | WM_MBUTTONUP | WM_XBUTTONUP => InputEvent::MouseUp {
+            button: button?,
+            x,
+            y,
+        },
+        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => InputEvent::MouseWheel {
+            delta: high_word(wparam) as i16 as f32 / WHEEL_DELTA,
+            horizontal: message == WM_MOUSEHWHEEL,
+        },
+        WM_MOUSELEAVE => InputEvent::MouseLeave,
+        WM_KEYDOWN | WM_SYSKEYDOWN => InputEvent::KeyDown {
+            key: key(wparam as u32),
+            modifiers,
+            /* Bit 30 holds the previous key state. */
+            repeat: (lparam >> 30) & 1 != 0,
+        },
+        WM_KEYUP | WM_SYSKEYUP => InputEvent::KeyUp {
+            key: key(wparam as u32),
+            modifiers,
+        },
+        WM_SETFOCUS => InputEvent::FocusGained,
+        WM_KILLFOCUS => InputEvent::FocusLost,
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_lparam(low: i16, high: i16) -> isize {
+        ((high as u16 as u32) << 16 | low as u16 as u32) as i32 as isize
+    }
+
+    fn decode(message: u32, wparam: usize, lparam: isize) -> Option<InputEvent> {
+        decode_win32(message, wparam, lparam, Modifiers::default())
+    }
+
+    #[test]
+    fn virtual_keys_decode_to_their_key() {
+        let keys = [
+            Key::Escape,
+            Key::Tab,
+            Key::Down,
+            Key::F(1),
+            Key::F(24),
+            Key::Char('Q'),
+            Key::Char('7'),
+            Key::Char('+'),
+            Key::Other(0x2E),
+        ];
+
+        for key in keys {
+            assert_eq!(super::key(virtual_key(key).unwrap()), key);
+        }
+
+        assert_eq!(virtual_key(Key::Char('q')), None);
+        assert_eq!(virtual_key(Key::F(25)), None);
+    }
+
+    #[test]
+    fn mouse_positions_keep_their_sign() {
+        assert_eq!(
+            decode(WM_MOUSEMOVE, 0, make_lparam(120, 45)),
+            Some(InputEvent::MouseMove { x: 120, y: 45 })
+        );
+        assert_eq!(
+            decode(WM_MOUSEMOVE, 0, make_lparam(-5, -1200)),
+            Some(InputEvent::MouseMove { x: -5, y: -1200 })
+        );
+        assert_eq!(
+            decode(WM_LBUTTONDOWN, 0x0001, make_lparam(-32768, 32767)),
+            Some(InputEvent::MouseDown {
+                button: MouseButton::Left,
+                x: -32768,
+                y: 32767
+            })
+        );
+    }
+
+    #[test]
+    fn mouse_buttons() {
+        let lparam = make_lparam(3, 4);
+        let up = |button| Some(InputEvent::MouseUp { button, x: 3, y: 4 });
+
+        assert_eq!(decode(WM_LBUTTONUP, 0, lparam), up(MouseButton::Left));
+        assert_eq!(decode(WM_RBUTTONUP, 0, lparam), up(MouseButton::Right));
+        assert_eq!(decode(WM_MBUTTONUP, 0, lparam), up(MouseButton::Middle));
+        assert_eq!(
+            decode(WM_XBUTTONUP, 0x0001_0000, lparam),
+            up(MouseButton::Back)
+        );
+        assert_eq!(
+            decode(WM_XBUTTONUP, 0x0002_0000, lparam),
+            up(MouseButton::Forward)
+        );
+    }
+
+    #[test]
+    fn wheel_in_notches() {
+        assert_eq!(
+            decode(WM_MOUSEWHEEL, 0x0078_0000, 0),
+            Some(InputEvent::MouseWheel {
+                delta: 1.,
+                horizontal: false
+            })
+        );
+        assert_eq!(
+            decode(WM_MOUSEHWHEEL, (-60i16 as u16 as usize) << 16, 0),
+            Some(InputEvent::MouseWheel {
+                delta: -0.5,
+                horizontal: true
+            })
+        );
+    }
+
+    #[test]
+    fn keys_with_modifiers_and_repeat() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+
+        assert_eq!(
+            decode_win32(WM_KEYDOWN, 0x72, 0x003D_0001, ctrl),
+            Some(InputEvent::KeyDown {
+                key: Key::F(3),
+                modifiers: ctrl,
+                repeat: false
+            })
+        );
+        assert_eq!(
+            decode(WM_KEYDOWN, 0x41, 0x401E_0001),
+            Some(InputEvent::KeyDown {
+                key: Key::Char('A'),
+                modifiers: Modifiers::default(),
+                repeat: true
+            })
+        );
+        assert_eq!(
+            decode(WM_SYSKEYUP, 0x7B, 0xC058_0001u32 as i32 as isize),
+            Some(InputEvent::KeyUp {
+                key: Key::F(12),
+                modifiers: Modifiers::default()
+            })
+        );
+        assert_eq!(
+            decode(WM_KEYUP, 0x1B, 0),
+            Some(InputEvent::KeyUp {
+                key: Key::Escape,
+                modifiers: Modifiers::default()
+            })
+        );
+        for (virtual_key, char) in [(0xBB, '+'), (0x6D, '-'), (0xBE, '.')] {
+            assert_eq!(
+                decode(WM_KEYUP, virtual_key, 0),
+                Some(InputEvent::KeyUp {
+                    key: Key::Char(char),
+                    modifiers: Modifiers::default()
+                })
+            );
+        }
+        assert_eq!(
+            decode(WM_KEYDOWN, 0xBA, 0),
+            Some(InputEvent::KeyDown {
+                key: Key::Other(0xBA),
+                modifiers: Modifiers::default(),
+                repeat: false
+            })
+        );
+    }
+
+    #[test]
+    fn focus_leave_and_other_messages() {
+        assert_eq!(decode(WM_SETFOCUS, 0, 0), Some(InputEvent::FocusGained));
+        assert_eq!(decode(WM_KILLFOCUS, 0, 0), Some(InputEvent::FocusLost));
+        assert_eq!(decode(WM_MOUSELEAVE, 0, 0), Some(InputEvent::MouseLeave));
+
+        /* WM_PAINT, WM_SIZE */
+        assert_eq!(decode(0x000F, 0, 0), None);
+        assert_eq!(decode(0x0005, 0, make_lparam(800, 600)), None);
+    }
+}
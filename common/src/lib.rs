@@ -0,0 +1,30 @@
+pub mod braille;
+pub mod canvas;
+pub mod clock;
+pub mod config;
+pub mod confirm;
+#[cfg(windows)]
+pub mod d2d;
+pub mod demo;
+pub mod display_list;
+pub mod dpi;
+mod error;
+pub mod golden;
+pub mod hud;
+pub mod input;
+pub mod menu;
+pub mod placement;
+pub mod playback;
+pub mod record;
+pub mod replay;
+pub mod screenshot;
+pub mod settings;
+pub mod software;
+pub mod spy;
+pub mod stats;
+pub mod svg;
+#[cfg(unix)]
+pub mod tty;
+mod win32;
+
+pub use error::{Error, Result};
@@ -0,0 +1,342 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::input::{self, Key, Modifiers};
+use crate::{Error, Result};
+
+/// A key combination that runs a menu command, e.g. `Ctrl+Shift+T`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Accelerator {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl Accelerator {
+    /// Modifiers and a key joined by `+`: `Ctrl+Q`, `Alt+Enter`, `F1`,
+    /// `Ctrl++`. Keys are named as in [`Display`](fmt::Display).
+    pub fn parse(text: &str) -> Result<Self> {
+        let (modifier_names, key_name) = match text.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+
+        let mut modifiers = Modifiers::default();
+
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            let modifier = match name.to_ascii_lowercase().as_str() {
+                "ctrl" => &mut modifiers.ctrl,
+                "shift" => &mut modifiers.shift,
+                "alt" => &mut modifiers.alt,
+                _ => {
+                    return Err(Error::Usage(format!(
+                        "unknown modifier {name:?} in {text:?}"
+                    )))
+                }
+            };
+            *modifier = true;
+        }
+
+        let key = key(key_name)
+            .ok_or_else(|| Error::Usage(format!("unknown key {key_name:?} in {text:?}")))?;
+
+        Ok(Accelerator { key, modifiers })
+    }
+
+    /// Win32 virtual key code of the key.
+    pub fn virtual_key(&self) -> Option<u32> {
+        input::virtual_key(self.key)
+    }
+}
+
+fn key(name: &str) -> Option<Key> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "esc" | "escape" => Key::Escape,
+        "enter" => Key::Enter,
+        "space" => Key::Space,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "+" | "-" | "." => Key::Char(name.chars().next()?),
+        lower => match lower.strip_prefix('f').map(str::parse::<u8>) {
+            Some(Ok(n @ 1..=24)) => Key::F(n),
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_alphanumeric() => {
+                        Key::Char(c.to_ascii_uppercase())
+                    }
+                    _ => return None,
+                }
+            }
+        },
+    };
+
+    Some(key)
+}
+
+/// As menus show it, next to the label: `Ctrl+Shift+T`.
+impl fmt::Display for Accelerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "Ctrl+"),
+            (self.modifiers.alt, "Alt+"),
+            (self.modifiers.shift, "Shift+"),
+        ];
+
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{name}")?;
+        }
+
+        match self.key {
+            Key::Escape => write!(f, "Esc"),
+            Key::Enter => write!(f, "Enter"),
+            Key::Space => write!(f, "Space"),
+            Key::Tab => write!(f, "Tab"),
+            Key::Backspace => write!(f, "Backspace"),
+            Key::Left => write!(f, "Left"),
+            Key::Right => write!(f, "Right"),
+            Key::Up => write!(f, "Up"),
+            Key::Down => write!(f, "Down"),
+            Key::F(n) => write!(f, "F{n}"),
+            Key::Char(c) => write!(f, "{c}"),
+            Key::Other(code) => write!(f, "0x{code:02X}"),
+        }
+    }
+}
+
+/// A menu item that does something, identified by the `WM_COMMAND` id it
+/// sends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command {
+    pub id: u16,
+    /// With `&` before the access key, as Win32 menus take it.
+    pub label: String,
+    pub accelerator: Option<Accelerator>,
+    /// Shown with a check mark from the start.
+    pub checked: bool,
+}
+
+impl Command {
+    /// Runs the command also on `accelerator`, see [`Accelerator::parse`].
+    /// Menus are written down in code, so a typo is a bug and panics.
+    pub fn shortcut(mut self, accelerator: &str) -> Self {
+        self.accelerator =
+            Some(Accelerator::parse(accelerator).unwrap_or_else(|error| panic!("{error}")));
+        self
+    }
+
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+
+    /// The label with the accelerator after a tab, which Win32 menus align
+    /// in a column of their own.
+    pub fn text(&self) -> String {
+        match &self.accelerator {
+            Some(accelerator) => format!("{}\t{accelerator}", self.label),
+            None => self.label.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Item {
+    Command(Command),
+    Separator,
+    Submenu { label: String, items: Vec<Item> },
+}
+
+impl From<Command> for Item {
+    fn from(command: Command) -> Self {
+        Item::Command(command)
+    }
+}
+
+pub fn command(id: u16, label: &str) -> Command {
+    Command {
+        id,
+        label: label.to_owned(),
+        accelerator: None,
+        checked: false,
+    }
+}
+
+pub fn submenu(label: &str, items: Vec<Item>) -> Item {
+    Item::Submenu {
+        label: label.to_owned(),
+        items,
+    }
+}
+
+/// A menu bar, described as data and built into a native menu by the
+/// window that shows it:
+///
+/// ```
+/// use common::menu::{command, submenu, Menu};
+///
+/// const EXIT: u16 = 1;
+///
+/// let menu = Menu::new(vec![submenu(
+///     "&File",
+///     vec![command(EXIT, "E&xit").shortcut("Ctrl+Q").into()],
+/// )]);
+/// assert_eq!(menu.accelerators()[0].1, EXIT);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Menu {
+    pub items: Vec<Item>,
+}
+
+impl Menu {
+    pub fn new(items: Vec<Item>) -> Self {
+        Menu { items }
+    }
+
+    /// All commands, depth first.
+    pub fn commands(&self) -> Vec<&Command> {
+        fn collect<'a>(items: &'a [Item], commands: &mut Vec<&'a Command>) {
+            for item in items {
+                match item {
+                    Item::Command(command) => commands.push(command),
+                    Item::Separator => {}
+                    Item::Submenu { items, .. } => collect(items, commands),
+                }
+            }
+        }
+
+        let mut commands = Vec::new();
+        collect(&self.items, &mut commands);
+        commands
+    }
+
+    pub fn command(&self, id: u16) -> Option<&Command> {
+        self.commands().into_iter().find(|command| command.id == id)
+    }
+
+    /// The accelerator table: each accelerator with the command it runs.
+    pub fn accelerators(&self) -> Vec<(Accelerator, u16)> {
+        self.commands()
+            .into_iter()
+            .filter_map(|command| Some((command.accelerator?, command.id)))
+            .collect()
+    }
+
+    /// Each id and each accelerator belongs to one command only, and each
+    /// accelerator has a key.
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        let mut accelerators = HashSet::new();
+
+        for command in self.commands() {
+            if !ids.insert(command.id) {
+                return Err(Error::Usage(format!(
+                    "menu command id {} is used twice",
+                    command.id
+                )));
+            }
+
+            if let Some(accelerator) = command.accelerator {
+                if accelerator.virtual_key().is_none() {
+                    return Err(Error::Usage(format!("{accelerator} has no key")));
+                }
+
+                if !accelerators.insert(accelerator) {
+                    return Err(Error::Usage(format!(
+                        "{accelerator} runs two menu commands"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu() -> Menu {
+        Menu::new(vec![
+            submenu(
+                "&File",
+                vec![
+                    command(1, "&Open").shortcut("Ctrl+O").into(),
+                    Item::Separator,
+                    command(2, "E&xit").into(),
+                ],
+            ),
+            submenu(
+                "&View",
+                vec![submenu(
+                    "&Zoom",
+                    vec![command(3, "Zoom &in").shortcut("ctrl++").into()],
+                )],
+            ),
+            submenu(
+                "&Help",
+                vec![command(4, "&About").shortcut("F1").checked(true).into()],
+            ),
+        ])
+    }
+
+    #[test]
+    fn accelerators_parse_and_print() {
+        for text in [
+            "Ctrl+Q",
+            "Ctrl+Alt+Shift+F12",
+            "Ctrl++",
+            "Shift+Esc",
+            "Alt+Enter",
+        ] {
+            assert_eq!(Accelerator::parse(text).unwrap().to_string(), text);
+        }
+
+        let accelerator = Accelerator::parse("shift+alt+t").unwrap();
+        assert_eq!(accelerator.to_string(), "Alt+Shift+T");
+        assert_eq!(accelerator.virtual_key(), Some(0x54));
+
+        for text in ["Hyper+Q", "Ctrl+", "Ctrl+Del", "F25", "Ctrl+q+"] {
+            assert!(Accelerator::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn commands_are_found_in_submenus() {
+        let menu = menu();
+
+        let ids: Vec<_> = menu.commands().iter().map(|command| command.id).collect();
+        assert_eq!(ids, [1, 2, 3, 4]);
+
+        assert_eq!(menu.command(3).unwrap().text(), "Zoom &in\tCtrl++");
+        assert_eq!(menu.command(2).unwrap().text(), "E&xit");
+        assert!(menu.command(4).unwrap().checked);
+        assert_eq!(menu.command(5), None);
+
+        let ids: Vec<_> = menu.accelerators().iter().map(|(_, id)| *id).collect();
+        assert_eq!(ids, [1, 3, 4]);
+    }
+
+    #[test]
+    fn ids_and_accelerators_are_unique() {
+        assert!(menu().validate().is_ok());
+
+        let mut twice = menu();
+        twice.items.push(command(2, "E&xit").into());
+        assert_eq!(
+            twice.validate().unwrap_err().to_string(),
+            "menu command id 2 is used twice"
+        );
+
+        let mut twice = menu();
+        twice.items.push(command(5, "&Help").shortcut("f1").into());
+        assert_eq!(
+            twice.validate().unwrap_err().to_string(),
+            "F1 runs two menu commands"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+/// A rectangle in whole pixels, like a Win32 `RECT`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Bounds {
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.left < other.right
+            && other.left < self.right
+            && self.top < other.bottom
+            && other.top < self.bottom
+    }
+
+    /* Squared length of the gap between the two; 0 when they touch. */
+    fn distance(&self, other: &Bounds) -> i64 {
+        let gap = |a0: i32, a1: i32, b0: i32, b1: i32| (a0 - b1).max(b0 - a1).max(0) as i64;
+        let dx = gap(self.left, self.right, other.left, other.right);
+        let dy = gap(self.top, self.bottom, other.top, other.bottom);
+
+        dx * dx + dy * dy
+    }
+}
+
+/// A display, as far as placing windows on it goes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Monitor {
+    /// Device name, e.g. `\\.\DISPLAY2`.
+    pub name: String,
+    /// The monitor minus the taskbar and other docked bars, in the same
+    /// coordinates as [`Placement::bounds`].
+    pub work_area: Bounds,
+}
+
+/// Where a window was and how it was shown, saved when it closes so that
+/// the next run opens it the same way.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+    /// The outer rectangle of the window when it is neither maximized nor
+    /// minimized, in workspace coordinates as `GetWindowPlacement` reports
+    /// them.
+    pub bounds: Bounds,
+    pub maximized: bool,
+    /// Device name of the monitor the window was on.
+    pub monitor: Option<String>,
+}
+
+impl Placement {
+    /// Keeps a placement that is at least partly on one of `monitors`.
+    /// Otherwise the window goes to the monitor it was saved on, when that
+    /// is still connected, or else to the nearest one, shrunk to fit its
+    /// work area if need be.
+    pub fn clamp(&self, monitors: &[Monitor]) -> Placement {
+        if monitors
+            .iter()
+            .any(|monitor| monitor.work_area.intersects(&self.bounds))
+        {
+            return self.clone();
+        }
+
+        let saved = monitors
+            .iter()
+            .find(|monitor| Some(&monitor.name) == self.monitor.as_ref());
+        let nearest = monitors
+            .iter()
+            .min_by_key(|monitor| monitor.work_area.distance(&self.bounds));
+
+        let Some(monitor) = saved.or(nearest) else {
+            return self.clone();
+        };
+
+        let area = monitor.work_area;
+        let width = self.bounds.width().min(area.width());
+        let height = self.bounds.height().min(area.height());
+        let left = self.bounds.left.clamp(area.left, area.right - width);
+        let top = self.bounds.top.clamp(area.top, area.bottom - height);
+
+        Placement {
+            bounds: Bounds {
+                left,
+                top,
+                right: left + width,
+                bottom: top + height,
+            },
+            maximized: self.maximized,
+            monitor: Some(monitor.name.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(left: i32, top: i32, width: i32, height: i32) -> Bounds {
+        Bounds {
+            left,
+            top,
+            right: left + width,
+            bottom: top + height,
+        }
+    }
+
+    /* A 1920 x 1080 primary display with a 40 pixel taskbar, and a second
+    one to its right. */
+    fn monitors() -> Vec<Monitor> {
+        vec![
+            Monitor {
+                name: r"\\.\DISPLAY1".into(),
+                work_area: bounds(0, 0, 1920, 1040),
+            },
+            Monitor {
+                name: r"\\.\DISPLAY2".into(),
+                work_area: bounds(1920, 0, 1280, 1024),
+            },
+        ]
+    }
+
+    fn placement(bounds: Bounds, monitor: &str) -> Placement {
+        Placement {
+            bounds,
+            maximized: false,
+            monitor: Some(monitor.into()),
+        }
+    }
+
+    #[test]
+    fn visible_placements_stay() {
+        let across = placement(bounds(1800, 100, 800, 600), r"\\.\DISPLAY1");
+        assert_eq!(across.clamp(&monitors()), across);
+
+        let mostly_off = placement(bounds(-790, -500, 800, 600), r"\\.\DISPLAY1");
+        assert_eq!(mostly_off.clamp(&monitors()), mostly_off);
+    }
+
+    #[test]
+    fn a_disconnected_monitor_moves_to_the_nearest() {
+        let mut saved = placement(bounds(3400, 200, 800, 600), r"\\.\DISPLAY3");
+        saved.maximized = true;
+
+        let clamped = saved.clamp(&monitors());
+
+        assert_eq!(clamped.bounds, bounds(2400, 200, 800, 600));
+        assert_eq!(clamped.monitor.as_deref(), Some(r"\\.\DISPLAY2"));
+        assert!(clamped.maximized);
+    }
+
+    #[test]
+    fn the_saved_monitor_is_preferred() {
+        /* Nearer to the second display, but saved on the first. */
+        let saved = placement(bounds(2000, 1200, 400, 300), r"\\.\DISPLAY1");
+
+        assert_eq!(saved.clamp(&monitors()).bounds, bounds(1520, 740, 400, 300));
+    }
+
+    #[test]
+    fn too_large_placements_shrink_to_the_work_area() {
+        let saved = placement(bounds(-5000, 0, 2560, 1440), r"\\.\DISPLAY1");
+
+        assert_eq!(saved.clamp(&monitors()).bounds, bounds(0, 0, 1920, 1040));
+        assert_eq!(saved.clamp(&[]), saved, "nowhere to go");
+    }
+}
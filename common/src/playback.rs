@@ -0,0 +1,224 @@
+use std::fmt;
+
+use crate::clock::Clock;
+use crate::input::{InputEvent, Key};
+use crate::Result;
+
+const MIN_SCALE: f64 = 1. / 16.;
+const MAX_SCALE: f64 = 16.;
+
+/// Keyboard controls over the passing of animation time, the same in every
+/// demo: Space pauses and resumes, `.` pauses and steps one frame ahead,
+/// `+` and `-` double and halve the speed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Playback {
+    /// Animation seconds of one step.
+    frame: f64,
+    paused: bool,
+    scale: f64,
+    steps: u32,
+}
+
+impl Playback {
+    /// `fps` is the frame rate of the animation, which `.` steps through.
+    pub fn new(fps: u32) -> Self {
+        Playback {
+            frame: 1. / fps.max(1) as f64,
+            paused: false,
+            scale: 1.,
+            steps: 0,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// `title` with the playback state appended, unless time runs normally.
+    pub fn title(&self, title: &str) -> String {
+        match !self.paused && self.scale == 1. {
+            true => title.to_owned(),
+            false => format!("{title} ({self})"),
+        }
+    }
+
+    /// Applies a playback key; returns whether `event` was one. Held keys
+    /// repeat, except for Space.
+    pub fn on_input(&mut self, event: &InputEvent) -> bool {
+        let InputEvent::KeyDown { key, repeat, .. } = *event else {
+            return false;
+        };
+
+        match key {
+            Key::Space => {
+                if !repeat {
+                    self.paused = !self.paused;
+                }
+            }
+            Key::Char('.') => {
+                self.paused = true;
+                self.steps += 1;
+            }
+            Key::Char('+') => self.scale = (self.scale * 2.).min(MAX_SCALE),
+            Key::Char('-') => self.scale = (self.scale / 2.).max(MIN_SCALE),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Animation time for `dt` seconds of real time: scaled, nothing while
+    /// paused, plus the frames stepped since the last call.
+    pub fn advance(&mut self, dt: f64) -> f64 {
+        let running = if self.paused { 0. } else { dt * self.scale };
+        let stepped = self.steps as f64 * self.frame;
+        self.steps = 0;
+
+        running + stepped
+    }
+}
+
+impl fmt::Display for Playback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.paused {
+            true => write!(f, "paused at speed x{}", self.scale),
+            false => write!(f, "speed x{}", self.scale),
+        }
+    }
+}
+
+/// A clock whose time passes under [`Playback`] control, for animations
+/// driven by [`Clock::get_time`].
+pub struct PlaybackClock {
+    clock: Box<dyn Clock>,
+    pub playback: Playback,
+    last: f64,
+    elapsed: f64,
+}
+
+impl PlaybackClock {
+    pub fn new(clock: Box<dyn Clock>, fps: u32) -> Self {
+        PlaybackClock {
+            last: clock.elapsed(),
+            clock,
+            playback: Playback::new(fps),
+            elapsed: 0.,
+        }
+    }
+}
+
+impl Clock for PlaybackClock {
+    fn update(&mut self) -> Result<()> {
+        self.clock.update()?;
+
+        let now = self.clock.elapsed();
+        self.elapsed += self.playback.advance(now - self.last);
+        self.last = now;
+        Ok(())
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::input::Modifiers;
+
+    fn press(playback: &mut Playback, key: Key) -> bool {
+        playback.on_input(&InputEvent::KeyDown {
+            key,
+            modifiers: Modifiers::default(),
+            repeat: false,
+        })
+    }
+
+    #[test]
+    fn pause_and_step() {
+        let mut playback = Playback::new(50);
+        assert_eq!(playback.advance(0.5), 0.5);
+
+        assert!(press(&mut playback, Key::Space));
+        assert_eq!(playback.advance(0.5), 0.);
+
+        press(&mut playback, Key::Char('.'));
+        press(&mut playback, Key::Char('.'));
+        assert_eq!(playback.advance(0.5), 0.04);
+        assert_eq!(playback.advance(0.5), 0.);
+
+        press(&mut playback, Key::Space);
+        assert_eq!(playback.advance(0.5), 0.5);
+
+        press(&mut playback, Key::Char('.'));
+        assert!(playback.is_paused(), "stepping pauses");
+    }
+
+    #[test]
+    fn speed_doubles_and_halves_within_limits() {
+        let mut playback = Playback::new(60);
+
+        press(&mut playback, Key::Char('-'));
+        assert_eq!(playback.advance(1.), 0.5);
+        assert_eq!(playback.title("Clock"), "Clock (speed x0.5)");
+
+        for _ in 0..10 {
+            press(&mut playback, Key::Char('+'));
+        }
+
+        assert_eq!(playback.scale(), 16.);
+        assert!(!press(&mut playback, Key::Char('A')));
+    }
+
+    #[test]
+    fn held_space_does_not_flicker() {
+        let mut playback = Playback::new(60);
+        let held = InputEvent::KeyDown {
+            key: Key::Space,
+            modifiers: Modifiers::default(),
+            repeat: true,
+        };
+
+        assert!(playback.on_input(&held), "not for the demo either");
+        assert!(!playback.is_paused());
+        assert_eq!(playback.title("Clock"), "Clock");
+    }
+
+    #[test]
+    fn clock_follows_playback() {
+        struct Shared(std::rc::Rc<std::cell::RefCell<ManualClock>>);
+
+        impl Clock for Shared {
+            fn update(&mut self) -> Result<()> {
+                Ok(())
+            }
+
+            fn elapsed(&self) -> f64 {
+                self.0.borrow().elapsed()
+            }
+        }
+
+        let real = std::rc::Rc::new(std::cell::RefCell::new(ManualClock::new()));
+        let mut clock = PlaybackClock::new(Box::new(Shared(real.clone())), 60);
+
+        real.borrow_mut().advance(1.);
+        clock.update().unwrap();
+        assert_eq!(clock.elapsed(), 1.);
+
+        press(&mut clock.playback, Key::Char('+'));
+        real.borrow_mut().advance(1.);
+        clock.update().unwrap();
+        assert_eq!(clock.elapsed(), 3.);
+
+        press(&mut clock.playback, Key::Space);
+        real.borrow_mut().advance(1.);
+        clock.update().unwrap();
+        assert_eq!(clock.elapsed(), 3.);
+    }
+}
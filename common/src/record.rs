@@ -0,0 +1,153 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use crate::software::SoftwareCanvas;
+use crate::{Error, Result};
+
+pub const USAGE: &str = "--record <dir> [--frames N] [--fps F] [--gif]";
+
+/// Offscreen recording requested on the command line with
+/// `--record <dir> --frames N --fps F [--gif]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordOptions {
+    pub directory: PathBuf,
+    pub frames: u32,
+    pub fps: u32,
+    pub gif: bool,
+}
+
+impl RecordOptions {
+    /// Returns `Ok(None)` when `--record` is not present, so the demo can
+    /// open its window as usual.
+    pub fn from_args<I>(args: I) -> Result<Option<Self>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut directory = None;
+        let mut frames = 60;
+        let mut fps = 60;
+        let mut gif = false;
+
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Error::Usage(format!("{arg} needs a value; usage: {USAGE}")))
+            };
+
+            match arg.as_str() {
+                "--record" => directory = Some(PathBuf::from(value()?)),
+                "--frames" => frames = parse_count(&arg, &value()?)?,
+                "--fps" => fps = parse_count(&arg, &value()?)?,
+                "--gif" => gif = true,
+                _ => {}
+            }
+        }
+
+        Ok(directory.map(|directory| RecordOptions {
+            directory,
+            frames,
+            fps,
+            gif,
+        }))
+    }
+
+    /// Synthetic time of `frame`, in seconds since the start of the recording.
+    pub fn frame_time(&self, frame: u32) -> f64 {
+        frame as f64 / self.fps as f64
+    }
+}
+
+fn parse_count(arg: &str, value: &str) -> Result<u32> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(Error::Usage(format!(
+            "{arg} expects a positive number, got {value:?}; usage: {USAGE}"
+        ))),
+    }
+}
+
+/// Writes `frame_NNNNN.png` files and, optionally, `animation.gif` into the
+/// recording directory.
+pub struct Recorder {
+    options: RecordOptions,
+    frame: u32,
+    gif: Option<gif::Encoder<BufWriter<File>>>,
+}
+
+impl Recorder {
+    pub fn new(options: &RecordOptions) -> Result<Self> {
+        fs::create_dir_all(&options.directory)?;
+
+        Ok(Recorder {
+            options: options.clone(),
+            frame: 0,
+            gif: None,
+        })
+    }
+
+    pub fn write_frame(&mut self, canvas: &SoftwareCanvas) -> Result<()> {
+        let pixmap = canvas.pixmap();
+        let path = self
+            .options
+            .directory
+            .join(format!("frame_{:05}.png", self.frame));
+
+        pixmap
+            .save_png(&path)
+            .map_err(|error| Error::Image(format!("{}: {error}", path.display())))?;
+
+        if self.options.gif {
+            self.write_gif_frame(canvas)?;
+        }
+
+        self.frame += 1;
+        Ok(())
+    }
+
+    fn write_gif_frame(&mut self, canvas: &SoftwareCanvas) -> Result<()> {
+        let (width, height) = (canvas.pixmap().width(), canvas.pixmap().height());
+        let gif_size = |size: u32| {
+            u16::try_from(size).map_err(|_| Error::InvalidSize { width, height })
+        };
+
+        if self.gif.is_none() {
+            let file = File::create(self.options.directory.join("animation.gif"))?;
+            let mut encoder =
+                gif::Encoder::new(BufWriter::new(file), gif_size(width)?, gif_size(height)?, &[])
+                    .map_err(gif_error)?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+            self.gif = Some(encoder);
+        }
+
+        let mut rgba = canvas.to_rgba();
+        let mut frame =
+            gif::Frame::from_rgba_speed(gif_size(width)?, gif_size(height)?, &mut rgba, 10);
+
+        /* GIF delays are in hundredths of a second. */
+        frame.delay = (100. / self.options.fps as f64).round() as u16;
+
+        self.gif
+            .as_mut()
+            .unwrap()
+            .write_frame(&frame)
+            .map_err(gif_error)
+    }
+
+    pub fn finish(self) -> Result<()> {
+        if let Some(encoder) = self.gif {
+            encoder
+                .into_inner()?
+                .into_inner()
+                .map_err(|error| Error::Io(error.into_error()))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn gif_error(error: gif::EncodingError) -> Error {
+    Error::Image(format!("GIF encoding failed: {error}"))
+}
@@ -0,0 +1,325 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::clock::{Clock, ManualClock};
+use crate::demo::Demo;
+use crate::input::{self, Modifiers, MouseButton};
+use crate::spy::Entry;
+use crate::win32::*;
+use crate::{Error, Result};
+
+/// Reads a trace as [`Spy`](crate::spy::Spy) writes it: one JSON
+/// [`Entry`] per line. Only the time, message and parameters are needed;
+/// blank lines are skipped.
+pub fn read<R: BufRead>(reader: R) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = serde_json::from_str(&line)
+            .map_err(|error| Error::Usage(format!("trace line {}: {error}", index + 1)))?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// How a replayed demo advances between messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Frames {
+    /// By `dt` seconds on every `WM_TIMER`, whatever the time stamps say,
+    /// like a window paced by a timer that draws one frame per tick.
+    PerTimer { dt: f64 },
+    /// To the time stamp of each message before handling it, like a
+    /// continuously paced window.
+    Continuous,
+}
+
+/// Feeds recorded messages into a [`Demo`] as its window would: input
+/// events, resizes, frames and paints, timed by a fake clock instead of the
+/// wall clock, so that a recorded run is reproduced exactly.
+pub struct Replay<'a, D: Demo + ?Sized> {
+    demo: &'a mut D,
+    frames: Frames,
+    clock: ManualClock,
+    /* Key messages do not carry the modifier state; the trace does. */
+    modifiers: Modifiers,
+}
+
+impl<'a, D: Demo + ?Sized> Replay<'a, D> {
+    pub fn new(demo: &'a mut D, frames: Frames) -> Self {
+        Replay {
+            demo,
+            frames,
+            clock: ManualClock::new(),
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    /// The demo as the messages fed so far left it.
+    pub fn demo(&self) -> &D {
+        self.demo
+    }
+
+    /// Time stamp of the last message fed.
+    pub fn time(&self) -> f64 {
+        self.clock.elapsed()
+    }
+
+    pub fn run(&mut self, entries: &[Entry]) -> Result<()> {
+        entries.iter().try_for_each(|entry| self.feed(entry))
+    }
+
+    pub fn feed(&mut self, entry: &Entry) -> Result<()> {
+        /* Time stamps of a trace only ever grow. */
+        let dt = (entry.time - self.clock.elapsed()).max(0.);
+        self.clock.advance(dt);
+
+        if self.frames == Frames::Continuous && dt > 0. {
+            self.demo.update(dt);
+        }
+
+        match entry.message {
+            WM_TIMER => {
+                if let Frames::PerTimer { dt } = self.frames {
+                    self.demo.update(dt);
+                }
+            }
+            WM_SIZE => {
+                let (width, height) = (entry.lparam & 0xFFFF, (entry.lparam >> 16) & 0xFFFF);
+
+                /* Nothing to draw into while the window is minimized. */
+                if width > 0 && height > 0 {
+                    self.demo.resize(width as u32, height as u32)?;
+                }
+            }
+            WM_PAINT => self.demo.render()?,
+            message => {
+                self.track_modifiers(message, entry.wparam);
+
+                let event =
+                    input::decode_win32(message, entry.wparam, entry.lparam, self.modifiers);
+
+                if let Some(event) = event {
+                    self.demo.on_input(event);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn track_modifiers(&mut self, message: u32, virtual_key: usize) {
+        let down = match message {
+            WM_KEYDOWN | WM_SYSKEYDOWN => true,
+            WM_KEYUP | WM_SYSKEYUP => false,
+            _ => return,
+        };
+
+        match virtual_key as u32 {
+            VK_SHIFT => self.modifiers.shift = down,
+            VK_CONTROL => self.modifiers.ctrl = down,
+            VK_MENU => self.modifiers.alt = down,
+            _ => {}
+        }
+    }
+}
+
+/* Packs a point like MAKELPARAM, with negative coordinates wrapped into
+their word. */
+fn point(x: i32, y: i32) -> isize {
+    (((y as u16 as u32) << 16) | x as u16 as u32) as i32 as isize
+}
+
+/* Which X button, in the high word of wparam. */
+fn xbutton(button: u16) -> usize {
+    (button as usize) << 16
+}
+
+/// `WM_MOUSEMOVE` to a client position, for writing traces by hand.
+pub fn mouse_move(time: f64, x: i32, y: i32) -> Entry {
+    Entry::new(time, WM_MOUSEMOVE, 0, point(x, y))
+}
+
+/// A button pressed or released at a client position.
+pub fn mouse_button(time: f64, button: MouseButton, down: bool, x: i32, y: i32) -> Entry {
+    let (message, wparam) = match (button, down) {
+        (MouseButton::Left, true) => (WM_LBUTTONDOWN, MK_LBUTTON),
+        (MouseButton::Left, false) => (WM_LBUTTONUP, 0),
+        (MouseButton::Right, true) => (WM_RBUTTONDOWN, MK_RBUTTON),
+        (MouseButton::Right, false) => (WM_RBUTTONUP, 0),
+        (MouseButton::Middle, true) => (WM_MBUTTONDOWN, MK_MBUTTON),
+        (MouseButton::Middle, false) => (WM_MBUTTONUP, 0),
+        (MouseButton::Back, true) => (WM_XBUTTONDOWN, xbutton(XBUTTON1) | MK_XBUTTON1),
+        (MouseButton::Back, false) => (WM_XBUTTONUP, xbutton(XBUTTON1)),
+        (MouseButton::Forward, true) => (WM_XBUTTONDOWN, xbutton(XBUTTON2) | MK_XBUTTON2),
+        (MouseButton::Forward, false) => (WM_XBUTTONUP, xbutton(XBUTTON2)),
+    };
+
+    Entry::new(time, message, wparam, point(x, y))
+}
+
+/// `WM_SIZE` of a restored window with a new client size.
+pub fn size(time: f64, width: u16, height: u16) -> Entry {
+    Entry::new(time, WM_SIZE, 0, point(width as i32, height as i32))
+}
+
+pub fn timer(time: f64, id: usize) -> Entry {
+    Entry::new(time, WM_TIMER, id, 0)
+}
+
+pub fn paint(time: f64) -> Entry {
+    Entry::new(time, WM_PAINT, 0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputEvent, Key};
+    use crate::software::SoftwareCanvas;
+
+    /* Writes down what it is told. */
+    struct Log {
+        canvas: SoftwareCanvas,
+        calls: Vec<String>,
+    }
+
+    impl Demo for Log {
+        fn name(&self) -> &str {
+            "log"
+        }
+
+        fn update(&mut self, dt: f64) {
+            self.calls.push(format!("update {dt}"));
+        }
+
+        fn render(&mut self) -> Result<()> {
+            self.calls.push("render".into());
+            Ok(())
+        }
+
+        fn canvas(&self) -> &SoftwareCanvas {
+            &self.canvas
+        }
+
+        fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+            self.calls.push(format!("resize {width}x{height}"));
+            Ok(())
+        }
+
+        fn on_input(&mut self, event: InputEvent) {
+            self.calls.push(format!("{event:?}"));
+        }
+    }
+
+    fn log() -> Log {
+        Log {
+            canvas: SoftwareCanvas::new(1, 1).unwrap(),
+            calls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn timer_ticks_are_frames() {
+        let mut demo = log();
+        let trace = [
+            size(0., 800, 600),
+            timer(0.013, 1),
+            paint(0.014),
+            timer(0.041, 1),
+            size(0.05, 0, 0),
+        ];
+
+        let mut replay = Replay::new(&mut demo, Frames::PerTimer { dt: 0.025 });
+        replay.run(&trace).unwrap();
+        assert_eq!(replay.time(), 0.05);
+
+        assert_eq!(
+            demo.calls,
+            ["resize 800x600", "update 0.025", "render", "update 0.025"]
+        );
+    }
+
+    #[test]
+    fn continuous_frames_follow_the_time_stamps() {
+        let mut demo = log();
+        let trace = [
+            mouse_move(0.5, -20, 30),
+            mouse_button(0.75, MouseButton::Left, true, -20, 30),
+            mouse_button(0.75, MouseButton::Left, false, -20, 30),
+        ];
+
+        Replay::new(&mut demo, Frames::Continuous)
+            .run(&trace)
+            .unwrap();
+
+        assert_eq!(
+            demo.calls,
+            [
+                "update 0.5",
+                "MouseMove { x: -20, y: 30 }",
+                "update 0.25",
+                "MouseDown { button: Left, x: -20, y: 30 }",
+                "MouseUp { button: Left, x: -20, y: 30 }"
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_carry_the_modifiers_of_the_trace() {
+        let mut demo = log();
+        let key = |time, message, key| Entry::new(time, message, key, 0);
+
+        Replay::new(&mut demo, Frames::PerTimer { dt: 0. })
+            .run(&[
+                key(0., WM_KEYDOWN, VK_CONTROL as usize),
+                key(0.1, WM_KEYDOWN, 0x41),
+                key(0.2, WM_KEYUP, VK_CONTROL as usize),
+                key(0.3, WM_KEYUP, 0x41),
+            ])
+            .unwrap();
+
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+
+        assert_eq!(
+            demo.calls[1],
+            format!(
+                "{:?}",
+                InputEvent::KeyDown {
+                    key: Key::Char('A'),
+                    modifiers: ctrl,
+                    repeat: false
+                }
+            )
+        );
+        assert!(demo.calls[3].contains("modifiers: Modifiers { shift: false, ctrl: false"));
+    }
+
+    #[test]
+    fn reads_what_the_spy_writes() {
+        let text = format!(
+            "{}\n\n{}\n",
+            serde_json::to_string(&mouse_move(0.25, 3, 4)).unwrap(),
+            r#"{"time":0.5,"message":15,"name":"WM_PAINT","wparam":0,"lparam":0}"#
+        );
+
+        let entries = read(text.as_bytes()).unwrap();
+        assert_eq!(entries, [mouse_move(0.25, 3, 4), paint(0.5)]);
+
+        let error = read("{\"time\":1}".as_bytes()).unwrap_err();
+        assert!(error.to_string().starts_with("trace line 1:"), "{error}");
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDateTime};
+
+/// `<demo>_YYYYMMDD_HHMMSS_mmm.png`; the milliseconds keep quick presses of
+/// the hotkey from overwriting each other.
+pub fn file_name(demo: &str, time: NaiveDateTime) -> String {
+    format!("{demo}_{}.png", time.format("%Y%m%d_%H%M%S_%3f"))
+}
+
+/// Where a screenshot taken now goes: next to the executable, or into the
+/// working directory when its location is unknown.
+pub fn path(demo: &str) -> PathBuf {
+    let file_name = file_name(demo, Local::now().naive_local());
+
+    match std::env::current_exe() {
+        Ok(exe) => exe.with_file_name(file_name),
+        Err(_) => PathBuf::from(file_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn names_sort_by_time() {
+        let time = |hour, milli| {
+            NaiveDate::from_ymd_opt(2024, 3, 9)
+                .unwrap()
+                .and_hms_milli_opt(hour, 5, 7, milli)
+                .unwrap()
+        };
+
+        assert_eq!(
+            file_name("clock", time(8, 42)),
+            "clock_20240309_080507_042.png"
+        );
+        assert!(file_name("clock", time(8, 999)) < file_name("clock", time(10, 0)));
+    }
+}
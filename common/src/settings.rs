@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::confirm::Answer;
+use crate::placement::Placement;
+use crate::{Error, Result};
+
+const DIRECTORY: &str = "win32-demos";
+const FILE_NAME: &str = "settings.toml";
+
+/// What the demos remember between runs, per user. One TOML file holds the
+/// state of all of them; unlike a [`Config`](crate::config::Config) it is
+/// written by the demos rather than by hand.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Window placements by window class name.
+    pub windows: BTreeMap<String, Placement>,
+    /// Answers given with "Don't ask again" checked, by the key of the
+    /// [`Confirm`](crate::confirm::Confirm).
+    pub confirmations: BTreeMap<String, Answer>,
+}
+
+/// `%APPDATA%\win32-demos\settings.toml` on Windows, under
+/// `$XDG_CONFIG_HOME` or `~/.config` elsewhere. `None` when the environment
+/// names no such directory.
+pub fn path() -> Option<PathBuf> {
+    let base = match cfg!(windows) {
+        true => PathBuf::from(std::env::var_os("APPDATA")?),
+        false => match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(config) => PathBuf::from(config),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        },
+    };
+
+    Some(base.join(DIRECTORY).join(FILE_NAME))
+}
+
+impl Settings {
+    /// A missing file holds the defaults.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Settings::default()),
+            Err(error) => return Err(error.into()),
+        };
+
+        toml::from_str(&text).map_err(|error| Error::Config {
+            path: path.to_owned(),
+            message: error.to_string(),
+        })
+    }
+
+    /// Creates the directory of the file if need be.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let text = toml::to_string(self).map_err(|error| Error::Config {
+            path: path.to_owned(),
+            message: error.to_string(),
+        })?;
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        Ok(fs::write(path, text)?)
+    }
+
+    /// The settings of this user. Nothing remembered is lost by failing to
+    /// read them, so a broken file is reported on stderr and ignored.
+    pub fn load() -> Self {
+        let Some(path) = path() else {
+            return Settings::default();
+        };
+
+        Settings::load_from(&path).unwrap_or_else(|error| {
+            eprintln!("{error}\nstarting with default settings");
+            Settings::default()
+        })
+    }
+
+    /// Reads the file again, applies `change` and writes it back, so that
+    /// demos running side by side do not undo each other's changes.
+    pub fn update<F: FnOnce(&mut Settings)>(change: F) -> Result<()> {
+        let Some(path) = path() else {
+            return Ok(());
+        };
+
+        let mut settings = Settings::load_from(&path)?;
+        change(&mut settings);
+        settings.save_to(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placement::Bounds;
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let path = std::env::temp_dir()
+            .join(format!("settings-test-{}", std::process::id()))
+            .join(FILE_NAME);
+
+        assert_eq!(Settings::load_from(&path).unwrap(), Settings::default());
+
+        let mut settings = Settings::default();
+        settings.windows.insert(
+            "WinAPI madness".into(),
+            Placement {
+                bounds: Bounds {
+                    left: -1200,
+                    top: 40,
+                    right: -400,
+                    bottom: 640,
+                },
+                maximized: true,
+                monitor: Some(r"\\.\DISPLAY2".into()),
+            },
+        );
+        settings
+            .confirmations
+            .insert("WinAPI madness/close".into(), Answer::Yes);
+
+        settings.save_to(&path).unwrap();
+        assert_eq!(Settings::load_from(&path).unwrap(), settings);
+
+        fs::write(&path, "windows = 3").unwrap();
+        assert!(matches!(
+            Settings::load_from(&path),
+            Err(Error::Config { .. })
+        ));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,367 @@
+use tiny_skia::{
+    FillRule, FilterQuality, LineCap, LineJoin, Paint, Pattern, Pixmap, Shader, SpreadMode, Stroke,
+    Transform,
+};
+
+use crate::canvas::*;
+use crate::dpi::Dpi;
+use crate::{Error, Result};
+
+/// CPU backend: renders into an RGBA pixmap with antialiasing, by default
+/// one device independent pixel per pixel.
+pub struct SoftwareCanvas {
+    pixmap: Pixmap,
+    /* `matrix` followed by the DPI scale. */
+    transform: Transform,
+    matrix: Transform,
+    dpi: Dpi,
+}
+
+impl SoftwareCanvas {
+    pub fn new(width: u32, height: u32) -> Result<Self> {
+        Ok(SoftwareCanvas {
+            pixmap: Self::create_pixmap(width, height)?,
+            transform: Transform::identity(),
+            matrix: Transform::identity(),
+            dpi: Dpi::DEFAULT,
+        })
+    }
+
+    fn update_transform(&mut self) {
+        let scale = self.dpi.scale();
+        self.transform = self.matrix.post_scale(scale, scale);
+    }
+
+    fn create_pixmap(width: u32, height: u32) -> Result<Pixmap> {
+        Pixmap::new(width, height).ok_or(Error::InvalidSize { width, height })
+    }
+
+    pub fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    /// Pixels as straight-alpha BGRA rows, top to bottom, the layout of a
+    /// 32bpp top-down DIB.
+    pub fn to_bgra(&self) -> Vec<u8> {
+        self.pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.blue(), color.green(), color.red(), color.alpha()]
+            })
+            .collect()
+    }
+
+    /// Pixels as straight-alpha RGBA rows, top to bottom.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect()
+    }
+
+    fn paint(brush: &Shader<'static>) -> Paint<'static> {
+        Paint {
+            shader: brush.clone(),
+            anti_alias: true,
+            ..Default::default()
+        }
+    }
+
+    /* Direct2D defaults: flat caps, miter joins with a limit of 10. */
+    fn stroke(stroke_width: f32) -> Stroke {
+        Stroke {
+            width: stroke_width,
+            miter_limit: 10.,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            dash: None,
+        }
+    }
+
+    fn stroke_path(&mut self, path: &tiny_skia::Path, brush: &Shader<'static>, stroke_width: f32) {
+        self.pixmap.stroke_path(
+            path,
+            &Self::paint(brush),
+            &Self::stroke(stroke_width),
+            self.transform,
+            None,
+        );
+    }
+
+    fn fill_path(&mut self, path: &tiny_skia::Path, brush: &Shader<'static>) {
+        self.pixmap.fill_path(
+            path,
+            &Self::paint(brush),
+            FillRule::EvenOdd,
+            self.transform,
+            None,
+        );
+    }
+
+    fn ellipse_path(ellipse: &Ellipse) -> Option<tiny_skia::Path> {
+        let Ellipse {
+            point,
+            radius_x,
+            radius_y,
+        } = *ellipse;
+
+        tiny_skia::Rect::from_ltrb(
+            point.x - radius_x,
+            point.y - radius_y,
+            point.x + radius_x,
+            point.y + radius_y,
+        )
+        .and_then(tiny_skia::PathBuilder::from_oval)
+    }
+}
+
+fn color(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba(
+        color.r.clamp(0., 1.),
+        color.g.clamp(0., 1.),
+        color.b.clamp(0., 1.),
+        color.a.clamp(0., 1.),
+    )
+    .unwrap()
+}
+
+fn point(point: Point) -> tiny_skia::Point {
+    tiny_skia::Point::from_xy(point.x, point.y)
+}
+
+fn transform(matrix: &Matrix) -> Transform {
+    Transform::from_row(
+        matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m31, matrix.m32,
+    )
+}
+
+impl Canvas for SoftwareCanvas {
+    type Brush = Shader<'static>;
+    type Geometry = tiny_skia::Path;
+    type Bitmap = Pixmap;
+
+    fn create_solid_brush(&mut self, color: Color) -> Result<Self::Brush> {
+        Ok(Shader::SolidColor(self::color(color)))
+    }
+
+    fn create_radial_gradient_brush(&mut self, gradient: &RadialGradient) -> Result<Self::Brush> {
+        let stops = gradient
+            .stops
+            .iter()
+            .map(|stop| tiny_skia::GradientStop::new(stop.position, color(stop.color)))
+            .collect();
+
+        let center = gradient.center;
+        let origin = Point {
+            x: center.x + gradient.gradient_origin_offset.x,
+            y: center.y + gradient.gradient_origin_offset.y,
+        };
+
+        /* tiny-skia only knows circular gradients; squash the circle of radius
+        `radius_x` into the requested ellipse around the center. */
+        let squash = Transform::from_translate(-center.x, -center.y)
+            .post_scale(1., gradient.radius_y / gradient.radius_x)
+            .post_translate(center.x, center.y);
+
+        tiny_skia::RadialGradient::new(
+            point(origin),
+            point(center),
+            gradient.radius_x,
+            stops,
+            SpreadMode::Pad,
+            squash,
+        )
+        .ok_or_else(|| Error::Image("degenerate radial gradient".into()))
+    }
+
+    fn create_geometry(&mut self, path: &Path) -> Result<Self::Geometry> {
+        let mut builder = tiny_skia::PathBuilder::new();
+
+        for figure in &path.figures {
+            builder.move_to(figure.start.x, figure.start.y);
+            let mut current = figure.start;
+
+            for segment in &figure.segments {
+                let beziers = match segment {
+                    Segment::Line(to) => {
+                        builder.line_to(to.x, to.y);
+                        current = *to;
+                        continue;
+                    }
+                    Segment::Bezier(bezier) => vec![*bezier],
+                    Segment::Arc(arc) => arc_to_beziers(current, arc),
+                };
+
+                for bezier in beziers {
+                    let BezierSegment {
+                        point1,
+                        point2,
+                        point3,
+                    } = bezier;
+
+                    builder.cubic_to(point1.x, point1.y, point2.x, point2.y, point3.x, point3.y);
+                    current = point3;
+                }
+            }
+
+            if figure.end == FigureEnd::Closed {
+                builder.close();
+            }
+        }
+
+        builder
+            .finish()
+            .ok_or_else(|| Error::Image("empty geometry".into()))
+    }
+
+    fn load_bitmap(&mut self, path: &std::path::Path) -> Result<Self::Bitmap> {
+        Pixmap::load_png(path).map_err(|error| Error::Image(format!("{}: {error}", path.display())))
+    }
+
+    fn bitmap_size(&self, bitmap: &Self::Bitmap) -> Size {
+        Size {
+            width: bitmap.width() as f32,
+            height: bitmap.height() as f32,
+        }
+    }
+
+    fn size(&self) -> Size {
+        Size {
+            width: self.dpi.to_dips(self.pixmap.width() as f32),
+            height: self.dpi.to_dips(self.pixmap.height() as f32),
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.pixmap = Self::create_pixmap(width, height)?;
+        Ok(())
+    }
+
+    fn set_dpi(&mut self, dpi: Dpi) {
+        self.dpi = dpi;
+        self.update_transform();
+    }
+
+    fn begin_draw(&mut self) {}
+
+    fn end_draw(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /* The pixmap cannot be lost. */
+    fn recreate_target(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_transform(&mut self, matrix: &Matrix) {
+        self.matrix = transform(matrix);
+        self.update_transform();
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.pixmap.fill(self::color(color));
+    }
+
+    fn draw_line(&mut self, p0: Point, p1: Point, brush: &Self::Brush, stroke_width: f32) {
+        let mut builder = tiny_skia::PathBuilder::new();
+        builder.move_to(p0.x, p0.y);
+        builder.line_to(p1.x, p1.y);
+
+        if let Some(path) = builder.finish() {
+            self.stroke_path(&path, brush, stroke_width);
+        }
+    }
+
+    fn fill_rectangle(&mut self, rect: &Rect, brush: &Self::Brush) {
+        if let Some(rect) = tiny_skia::Rect::from_ltrb(rect.left, rect.top, rect.right, rect.bottom)
+        {
+            self.pixmap
+                .fill_rect(rect, &Self::paint(brush), self.transform, None);
+        }
+    }
+
+    fn fill_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush) {
+        self.fill_path(geometry, brush);
+    }
+
+    fn draw_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush, stroke_width: f32) {
+        self.stroke_path(geometry, brush, stroke_width);
+    }
+
+    fn fill_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush) {
+        if let Some(path) = Self::ellipse_path(ellipse) {
+            self.fill_path(&path, brush);
+        }
+    }
+
+    fn draw_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush, stroke_width: f32) {
+        if let Some(path) = Self::ellipse_path(ellipse) {
+            self.stroke_path(&path, brush, stroke_width);
+        }
+    }
+
+    fn draw_bitmap(
+        &mut self,
+        bitmap: &Self::Bitmap,
+        destination: Option<&Rect>,
+        opacity: f32,
+        source: Option<&Rect>,
+    ) {
+        let full = Rect {
+            left: 0.,
+            top: 0.,
+            right: bitmap.width() as f32,
+            bottom: bitmap.height() as f32,
+        };
+
+        let source = source.copied().unwrap_or(full);
+        let destination = destination.copied().unwrap_or(Rect {
+            right: source.width(),
+            bottom: source.height(),
+            ..Default::default()
+        });
+
+        let Some(target) = tiny_skia::Rect::from_ltrb(
+            destination.left,
+            destination.top,
+            destination.right,
+            destination.bottom,
+        ) else {
+            return;
+        };
+
+        /* Maps the source rectangle of the bitmap onto the destination. */
+        let (scale_x, scale_y) = (
+            destination.width() / source.width(),
+            destination.height() / source.height(),
+        );
+        let pattern_transform = Transform::from_row(
+            scale_x,
+            0.,
+            0.,
+            scale_y,
+            destination.left - source.left * scale_x,
+            destination.top - source.top * scale_y,
+        );
+
+        let paint = Paint {
+            shader: Pattern::new(
+                bitmap.as_ref(),
+                SpreadMode::Pad,
+                FilterQuality::Bilinear,
+                opacity,
+                pattern_transform,
+            ),
+            anti_alias: true,
+            ..Default::default()
+        };
+
+        self.pixmap.fill_rect(target, &paint, self.transform, None);
+    }
+}
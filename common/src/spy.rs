@@ -0,0 +1,593 @@
+use std::fs::File;
+use std::io::{self, BufWriter, LineWriter, Write};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, InstantClock};
+use crate::win32::*;
+use crate::{Error, Result};
+
+pub const USAGE: &str = "--spy <file.jsonl|-> [--spy-filter <names>]";
+
+/* The MK_* flags of mouse messages. */
+const MOUSE_KEYS: &[(usize, &str)] = &[
+    (MK_LBUTTON, "left"),
+    (MK_RBUTTON, "right"),
+    (MK_SHIFT, "shift"),
+    (MK_CONTROL, "ctrl"),
+    (MK_MBUTTON, "middle"),
+    (MK_XBUTTON1, "back"),
+    (MK_XBUTTON2, "forward"),
+];
+
+fn lookup<K: Ord + Copy>(table: &[(K, &'static str)], key: K) -> Option<&'static str> {
+    table
+        .binary_search_by_key(&key, |&(key, _)| key)
+        .ok()
+        .map(|index| table[index].1)
+}
+
+/// The `WM_*` name of a message, `WM_USER+n` or `WM_APP+n` for private
+/// ones, or the number in hex for registered and unknown ones.
+pub fn message_name(message: u32) -> String {
+    if let Some(name) = lookup(MESSAGES, message) {
+        return name.to_owned();
+    }
+
+    match message {
+        WM_USER..WM_APP => format!("WM_USER+{}", message - WM_USER),
+        WM_APP..FIRST_REGISTERED => format!("WM_APP+{}", message - WM_APP),
+        _ => format!("0x{message:04X}"),
+    }
+}
+
+/// The `VK_*` name of a virtual key code; letters and digits as themselves.
+pub fn key_name(virtual_key: u32) -> String {
+    match virtual_key {
+        0x30..=0x39 | 0x41..=0x5A => char::from(virtual_key as u8).to_string(),
+        VK_NUMPAD0..=VK_NUMPAD9 => format!("VK_NUMPAD{}", virtual_key - VK_NUMPAD0),
+        VK_F1..=VK_F24 => format!("VK_F{}", virtual_key - VK_F1 + 1),
+        _ => lookup(VIRTUAL_KEYS, virtual_key)
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("0x{virtual_key:02X}")),
+    }
+}
+
+fn low_word(value: usize) -> u16 {
+    (value & 0xFFFF) as u16
+}
+
+fn high_word(value: usize) -> u16 {
+    ((value >> 16) & 0xFFFF) as u16
+}
+
+/* Signed, like GET_X_LPARAM / GET_Y_LPARAM. */
+fn point(lparam: isize) -> (i32, i32) {
+    let lparam = lparam as usize;
+    (
+        low_word(lparam) as i16 as i32,
+        high_word(lparam) as i16 as i32,
+    )
+}
+
+fn mouse_keys(wparam: usize) -> Vec<String> {
+    MOUSE_KEYS
+        .iter()
+        .filter(|&&(flag, _)| wparam & flag != 0)
+        .map(|&(_, name)| name.to_owned())
+        .collect()
+}
+
+/// What the parameters of a message mean, for the messages that carry
+/// values rather than pointers.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Details {
+    /// `WM_SIZE`: the new client size and why it changed.
+    Size {
+        state: String,
+        width: u32,
+        height: u32,
+    },
+    /// `WM_MOVE`: the new position of the client area, in screen
+    /// coordinates.
+    Move { x: i32, y: i32 },
+    /// Mouse messages: the cursor position, in client coordinates except
+    /// for the non-client ones, and the buttons and keys held.
+    Mouse { x: i32, y: i32, keys: Vec<String> },
+    /// `WM_MOUSEWHEEL` and `WM_MOUSEHWHEEL`, in notches, at a screen
+    /// position.
+    Wheel {
+        delta: f32,
+        x: i32,
+        y: i32,
+        keys: Vec<String>,
+    },
+    /// Key messages, with the bit fields of lparam spelled out.
+    Key {
+        key: String,
+        repeat_count: u16,
+        scan_code: u8,
+        extended: bool,
+        was_down: bool,
+        released: bool,
+    },
+    /// `WM_CHAR` and friends: a UTF-16 code unit.
+    Char { char: String, code: u32 },
+    /// `WM_ACTIVATE`.
+    Activate { state: String, minimized: bool },
+    /// `WM_ACTIVATEAPP`, `WM_NCACTIVATE`, `WM_SHOWWINDOW` and `WM_ENABLE`.
+    Flag { value: bool },
+    /// `WM_TIMER`.
+    Timer { id: usize },
+    /// `WM_COMMAND`: a menu item, an accelerator or a notification from a
+    /// control.
+    Command {
+        id: u16,
+        source: String,
+        notification: u16,
+    },
+    /// `WM_SYSCOMMAND`.
+    SystemCommand { command: String },
+    /// `WM_DPICHANGED`.
+    Dpi { dpi: u16 },
+}
+
+/// Decodes the parameters of `message`; `None` when they are pointers,
+/// handles or nothing at all.
+pub fn decode(message: u32, wparam: usize, lparam: isize) -> Option<Details> {
+    let (x, y) = point(lparam);
+
+    /* Unnamed numbers in the ranges below are no messages at all. */
+    lookup(MESSAGES, message)?;
+
+    let details = match message {
+        WM_SIZE => Details::Size {
+            state: match wparam {
+                0 => "restored",
+                1 => "minimized",
+                2 => "maximized",
+                3 => "other_restored",
+                4 => "other_maximized",
+                _ => "unknown",
+            }
+            .to_owned(),
+            width: low_word(lparam as usize) as u32,
+            height: high_word(lparam as usize) as u32,
+        },
+        WM_MOVE => Details::Move { x, y },
+        WM_MOUSEWHEEL | WM_MOUSEHWHEEL => Details::Wheel {
+            delta: high_word(wparam) as i16 as f32 / WHEEL_DELTA,
+            x,
+            y,
+            keys: mouse_keys(low_word(wparam) as usize),
+        },
+        WM_MOUSEMOVE | WM_MOUSEHOVER => Details::Mouse {
+            x,
+            y,
+            keys: mouse_keys(wparam),
+        },
+        WM_NCMOUSEMOVE..=WM_NCXBUTTONDBLCLK => {
+            /* wparam is the hit test code here, not key flags. */
+            Details::Mouse {
+                x,
+                y,
+                keys: Vec::new(),
+            }
+        }
+        WM_LBUTTONDOWN..=WM_XBUTTONDBLCLK => Details::Mouse {
+            x,
+            y,
+            keys: mouse_keys(wparam),
+        },
+        WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => {
+            let flags = lparam as usize;
+
+            Details::Key {
+                key: key_name(wparam as u32),
+                repeat_count: low_word(flags),
+                scan_code: ((flags >> 16) & 0xFF) as u8,
+                extended: flags & (1 << 24) != 0,
+                was_down: flags & (1 << 30) != 0,
+                released: flags & (1 << 31) != 0,
+            }
+        }
+        WM_CHAR | WM_SYSCHAR | WM_DEADCHAR | WM_SYSDEADCHAR => Details::Char {
+            char: char::decode_utf16([wparam as u16])
+                .map(|char| char.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+            code: wparam as u32,
+        },
+        WM_ACTIVATE => Details::Activate {
+            state: match low_word(wparam) {
+                0 => "inactive",
+                1 => "active",
+                2 => "click_active",
+                _ => "unknown",
+            }
+            .to_owned(),
+            minimized: high_word(wparam) != 0,
+        },
+        WM_ACTIVATEAPP | WM_NCACTIVATE | WM_SHOWWINDOW | WM_ENABLE => {
+            Details::Flag { value: wparam != 0 }
+        }
+        WM_TIMER => Details::Timer { id: wparam },
+        WM_COMMAND => Details::Command {
+            id: low_word(wparam),
+            source: match (high_word(wparam), lparam) {
+                (0, 0) => "menu",
+                (1, 0) => "accelerator",
+                _ => "control",
+            }
+            .to_owned(),
+            notification: high_word(wparam),
+        },
+        WM_SYSCOMMAND => Details::SystemCommand {
+            /* The low four bits are used by the system. */
+            command: lookup(SYSTEM_COMMANDS, wparam & 0xFFF0)
+                .map(str::to_owned)
+                .unwrap_or_else(|| format!("0x{wparam:04X}")),
+        },
+        WM_DPICHANGED => Details::Dpi {
+            dpi: low_word(wparam),
+        },
+        _ => return None,
+    };
+
+    Some(details)
+}
+
+/// One line of a trace.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the trace started.
+    pub time: f64,
+    pub message: u32,
+    pub name: String,
+    pub wparam: usize,
+    pub lparam: isize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Details>,
+}
+
+impl Entry {
+    pub fn new(time: f64, message: u32, wparam: usize, lparam: isize) -> Self {
+        Entry {
+            time,
+            message,
+            name: message_name(message),
+            wparam,
+            lparam,
+            details: decode(message, wparam, lparam),
+        }
+    }
+}
+
+/// Which messages a trace keeps, by name: a comma separated list where `*`
+/// at the end of a name matches any rest and `!` in front leaves messages
+/// out. `WM_KEY*,WM_SIZE` keeps only those, `!WM_NCHITTEST,!WM_SETCURSOR`
+/// everything else. The empty filter keeps everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Filter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Filter::default();
+
+        for pattern in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match pattern.strip_prefix('!') {
+                Some(pattern) => filter.exclude.push(pattern.to_owned()),
+                None => filter.include.push(pattern.to_owned()),
+            }
+        }
+
+        filter
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let matching = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        };
+
+        (self.include.is_empty() || self.include.iter().any(matching))
+            && !self.exclude.iter().any(matching)
+    }
+}
+
+/// A message trace requested on the command line with `--spy <file>`,
+/// `-` for stdout, and optionally `--spy-filter <names>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpyOptions {
+    pub path: PathBuf,
+    pub filter: Filter,
+}
+
+impl SpyOptions {
+    /// Returns `Ok(None)` when `--spy` is not present.
+    pub fn from_args<I>(args: I) -> Result<Option<Self>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut path = None;
+        let mut filter = Filter::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Error::Usage(format!("{arg} needs a value; usage: {USAGE}")))
+            };
+
+            match arg.as_str() {
+                "--spy" => path = Some(PathBuf::from(value()?)),
+                "--spy-filter" => filter = Filter::parse(&value()?),
+                _ => {}
+            }
+        }
+
+        Ok(path.map(|path| SpyOptions { path, filter }))
+    }
+}
+
+/// Writes the messages of a window procedure as JSON lines, one [`Entry`]
+/// each.
+pub struct Spy {
+    writer: Box<dyn Write>,
+    filter: Filter,
+    clock: Box<dyn Clock>,
+}
+
+impl Spy {
+    pub fn new(writer: Box<dyn Write>, filter: Filter, clock: Box<dyn Clock>) -> Self {
+        Spy {
+            writer,
+            filter,
+            clock,
+        }
+    }
+
+    /// Opens the file of `options`, timed from now. Stdout is written line
+    /// by line, to watch along.
+    pub fn create(options: &SpyOptions) -> Result<Self> {
+        let writer: Box<dyn Write> = match options.path.to_str() {
+            Some("-") => Box::new(LineWriter::new(io::stdout())),
+            _ => Box::new(BufWriter::new(File::create(&options.path)?)),
+        };
+
+        Ok(Spy::new(
+            writer,
+            options.filter.clone(),
+            Box::new(InstantClock::new()),
+        ))
+    }
+
+    /// Call at the top of the window procedure.
+    pub fn record(&mut self, message: u32, wparam: usize, lparam: isize) -> Result<()> {
+        let name = message_name(message);
+
+        if !self.filter.matches(&name) {
+            return Ok(());
+        }
+
+        self.clock.update()?;
+        let entry = Entry::new(self.clock.elapsed(), message, wparam, lparam);
+
+        serde_json::to_writer(&mut self.writer, &entry).map_err(|error| Error::Io(error.into()))?;
+        Ok(self.writer.write_all(b"\n")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn make_lparam(low: i16, high: i16) -> isize {
+        ((high as u16 as u32) << 16 | low as u16 as u32) as i32 as isize
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(message_name(0x000F), "WM_PAINT");
+        assert_eq!(message_name(0x02E0), "WM_DPICHANGED");
+        assert_eq!(message_name(0x0405), "WM_USER+5");
+        assert_eq!(message_name(0x8001), "WM_APP+1");
+        assert_eq!(message_name(0xC0A4), "0xC0A4");
+        assert_eq!(message_name(0x0093), "0x0093");
+
+        assert_eq!(key_name(0x41), "A");
+        assert_eq!(key_name(0x72), "VK_F3");
+        assert_eq!(key_name(0x1B), "VK_ESCAPE");
+        assert_eq!(key_name(0x63), "VK_NUMPAD3");
+        assert_eq!(key_name(0xFF), "0xFF");
+    }
+
+    #[test]
+    fn sizes_and_points() {
+        assert_eq!(
+            decode(0x0005, 2, make_lparam(1400, 600)),
+            Some(Details::Size {
+                state: "maximized".into(),
+                width: 1400,
+                height: 600
+            })
+        );
+        assert_eq!(
+            decode(0x0003, 0, make_lparam(-1912, 8)),
+            Some(Details::Move { x: -1912, y: 8 })
+        );
+        assert_eq!(
+            decode(0x0201, 0x0009, make_lparam(10, -3)),
+            Some(Details::Mouse {
+                x: 10,
+                y: -3,
+                keys: vec!["left".into(), "ctrl".into()]
+            })
+        );
+        assert_eq!(
+            decode(0x00A1, 2, make_lparam(300, 5)),
+            Some(Details::Mouse {
+                x: 300,
+                y: 5,
+                keys: vec![]
+            }),
+            "hit test code, not keys"
+        );
+        assert_eq!(
+            decode(0x020A, (-240i16 as u16 as usize) << 16 | 0x0004, 0),
+            Some(Details::Wheel {
+                delta: -2.,
+                x: 0,
+                y: 0,
+                keys: vec!["shift".into()]
+            })
+        );
+    }
+
+    #[test]
+    fn keys_and_characters() {
+        /* Alt+F4 released: transition, previous state and context bits. */
+        assert_eq!(
+            decode(0x0105, 0x73, 0xE03E_0001u32 as i32 as isize),
+            Some(Details::Key {
+                key: "VK_F4".into(),
+                repeat_count: 1,
+                scan_code: 0x3E,
+                extended: false,
+                was_down: true,
+                released: true
+            })
+        );
+        assert_eq!(
+            decode(0x0102, 'é' as usize, 0),
+            Some(Details::Char {
+                char: "é".into(),
+                code: 0xE9
+            })
+        );
+    }
+
+    #[test]
+    fn activation_commands_and_the_rest() {
+        assert_eq!(
+            decode(0x0006, 0x0001_0002, 0),
+            Some(Details::Activate {
+                state: "click_active".into(),
+                minimized: true
+            })
+        );
+        assert_eq!(decode(0x001C, 1, 0), Some(Details::Flag { value: true }));
+        assert_eq!(decode(0x0113, 7, 0), Some(Details::Timer { id: 7 }));
+        assert_eq!(
+            decode(0x0111, 0x0001_0065, 0),
+            Some(Details::Command {
+                id: 101,
+                source: "accelerator".into(),
+                notification: 1
+            })
+        );
+        assert_eq!(
+            decode(0x0112, 0xF063, 0),
+            Some(Details::SystemCommand {
+                command: "SC_CLOSE".into()
+            })
+        );
+        assert_eq!(
+            decode(0x02E0, 0x0090_0090, 0),
+            Some(Details::Dpi { dpi: 144 })
+        );
+        assert_eq!(decode(0x000F, 0, 0), None);
+        assert_eq!(decode(0x0405, 1, 2), None);
+        assert_eq!(
+            decode(0x0122, 1, 0),
+            None,
+            "WM_MENURBUTTONUP is no mouse message"
+        );
+    }
+
+    #[test]
+    fn filters() {
+        let all = Filter::parse("");
+        assert!(all.matches("WM_PAINT"));
+
+        let keys = Filter::parse("WM_KEY*, WM_SIZE");
+        assert!(keys.matches("WM_KEYDOWN"));
+        assert!(keys.matches("WM_SIZE"));
+        assert!(!keys.matches("WM_SIZING"));
+
+        let quiet = Filter::parse("!WM_NCHITTEST,!WM_SETCURSOR,!WM_MOUSE*");
+        assert!(quiet.matches("WM_PAINT"));
+        assert!(!quiet.matches("WM_MOUSEMOVE"));
+        assert!(!quiet.matches("WM_SETCURSOR"));
+    }
+
+    #[test]
+    fn options() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            SpyOptions::from_args(args("--config a.toml")).unwrap(),
+            None
+        );
+        assert_eq!(
+            SpyOptions::from_args(args("--spy - --spy-filter WM_SIZE"))
+                .unwrap()
+                .unwrap(),
+            SpyOptions {
+                path: "-".into(),
+                filter: Filter::parse("WM_SIZE")
+            }
+        );
+        assert!(SpyOptions::from_args(args("--spy")).is_err());
+    }
+
+    #[test]
+    fn writes_filtered_json_lines() {
+        #[derive(Clone, Default)]
+        struct Shared(Rc<RefCell<Vec<u8>>>);
+
+        impl Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.borrow_mut().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let output = Shared::default();
+        let mut clock = ManualClock::new();
+        clock.set(1.5);
+
+        let mut spy = Spy::new(
+            Box::new(output.clone()),
+            Filter::parse("!WM_NCHITTEST"),
+            Box::new(clock),
+        );
+
+        spy.record(0x0084, 0, 0).unwrap();
+        spy.record(0x0005, 0, make_lparam(800, 600)).unwrap();
+        spy.record(0x000F, 0, 0).unwrap();
+
+        let text = String::from_utf8(output.0.borrow().clone()).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            r#"{"time":1.5,"message":5,"name":"WM_SIZE","wparam":0,"lparam":39322400,"details":{"kind":"size","state":"restored","width":800,"height":600}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Entry>(lines[1]).unwrap(),
+            Entry::new(1.5, 0x000F, 0, 0)
+        );
+    }
+}
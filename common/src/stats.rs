@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Rolling statistics over the most recent frame times.
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    frame_times: VecDeque<Duration>,
+    capacity: usize,
+    frame_count: u64,
+}
+
+impl FrameStats {
+    /// Two seconds worth of frames at 60 FPS.
+    pub const DEFAULT_CAPACITY: usize = 120;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "frame statistics need room for one frame");
+
+        FrameStats {
+            frame_times: VecDeque::with_capacity(capacity),
+            capacity,
+            frame_count: 0,
+        }
+    }
+
+    /// Adds the time between the start of the previous frame and this one.
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == self.capacity {
+            self.frame_times.pop_front();
+        }
+
+        self.frame_times.push_back(frame_time);
+        self.frame_count += 1;
+    }
+
+    /// Frames recorded since creation, including those that dropped out of
+    /// the window.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn last(&self) -> Option<Duration> {
+        self.frame_times.back().copied()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        let total: Duration = self.frame_times.iter().sum();
+        let count = u32::try_from(self.frame_times.len()).ok()?;
+
+        total.checked_div(count)
+    }
+
+    pub fn fps(&self) -> Option<f64> {
+        let average = self.average()?.as_secs_f64();
+        (average > 0.).then(|| 1. / average)
+    }
+
+    /// Nearest-rank percentile, `percent` in `0..=100`; e.g. 99 gives the
+    /// frame time that only 1% of recent frames exceeded.
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.frame_times.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let rank = (percent.clamp(0., 100.) / 100. * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    #[test]
+    fn empty_stats_have_no_values() {
+        let stats = FrameStats::new();
+
+        assert_eq!(stats.frame_count(), 0);
+        assert_eq!(stats.last(), None);
+        assert_eq!(stats.average(), None);
+        assert_eq!(stats.fps(), None);
+        assert_eq!(stats.percentile(50.), None);
+    }
+
+    #[test]
+    fn average_and_fps() {
+        let mut stats = FrameStats::new();
+        stats.record(ms(10));
+        stats.record(ms(30));
+
+        assert_eq!(stats.last(), Some(ms(30)));
+        assert_eq!(stats.average(), Some(ms(20)));
+        assert!((stats.fps().unwrap() - 50.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn old_frames_drop_out() {
+        let mut stats = FrameStats::with_capacity(2);
+        stats.record(ms(100));
+        stats.record(ms(10));
+        stats.record(ms(20));
+
+        assert_eq!(stats.frame_count(), 3);
+        assert_eq!(stats.average(), Some(ms(15)));
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let mut stats = FrameStats::new();
+
+        for milliseconds in (1..=100).rev() {
+            stats.record(ms(milliseconds));
+        }
+
+        assert_eq!(stats.percentile(0.), Some(ms(1)));
+        assert_eq!(stats.percentile(50.), Some(ms(50)));
+        assert_eq!(stats.percentile(99.), Some(ms(99)));
+        assert_eq!(stats.percentile(100.), Some(ms(100)));
+    }
+}
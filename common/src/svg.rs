@@ -0,0 +1,641 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use base64::Engine;
+
+use crate::canvas::*;
+use crate::dpi::Dpi;
+use crate::{Error, Result};
+
+pub const USAGE: &str = "--svg <file.svg> [--time SECONDS]";
+
+/// Single frame export requested on the command line with
+/// `--svg <file.svg> [--time SECONDS]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    pub path: PathBuf,
+    /// Animation time of the exported frame.
+    pub time: f64,
+}
+
+impl SvgOptions {
+    /// Returns `Ok(None)` when `--svg` is not present.
+    pub fn from_args<I>(args: I) -> Result<Option<Self>>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut path = None;
+        let mut time = 0.;
+
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Error::Usage(format!("{arg} needs a value; usage: {USAGE}")))
+            };
+
+            match arg.as_str() {
+                "--svg" => path = Some(PathBuf::from(value()?)),
+                "--time" => {
+                    let value = value()?;
+
+                    time = match value.parse::<f64>() {
+                        Ok(time) if time >= 0. && time.is_finite() => time,
+                        _ => {
+                            return Err(Error::Usage(format!(
+                                "--time expects seconds, got {value:?}; usage: {USAGE}"
+                            )))
+                        }
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        Ok(path.map(|path| SvgOptions { path, time }))
+    }
+}
+
+/// Paint of a shape: a color or a reference to a gradient in `<defs>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgBrush {
+    paint: String,
+    opacity: f32,
+}
+
+/// PNG embedded as a data URI.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgBitmap {
+    href: String,
+    width: u32,
+    height: u32,
+}
+
+/// Vector backend: records the drawing calls of a frame as SVG elements, so
+/// paths, arcs and gradients keep their exact geometry. Units are device
+/// independent pixels.
+pub struct SvgCanvas {
+    width: u32,
+    height: u32,
+    dpi: Dpi,
+    transform: Matrix,
+    defs: String,
+    body: String,
+    next_id: usize,
+}
+
+impl SvgCanvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        SvgCanvas {
+            width,
+            height,
+            dpi: Dpi::DEFAULT,
+            transform: Matrix::identity(),
+            defs: String::new(),
+            body: String::new(),
+            next_id: 0,
+        }
+    }
+
+    /// The frame drawn since the last `begin_draw`, as a standalone document.
+    pub fn to_svg(&self) -> String {
+        let Size { width, height } = self.size();
+        let mut svg = String::new();
+
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )
+        .unwrap();
+
+        if !self.defs.is_empty() {
+            writeln!(svg, "<defs>\n{}</defs>", self.defs).unwrap();
+        }
+
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> Result<()> {
+        Ok(fs::write(path, self.to_svg())?)
+    }
+
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{}", self.next_id)
+    }
+
+    /* SVG `matrix(a b c d e f)` maps x' = a x + c y + e, y' = b x + d y + f,
+    the same numbers as the row-vector `Matrix`. */
+    fn transform_attribute(&self) -> String {
+        if self.transform == Matrix::identity() {
+            return String::new();
+        }
+
+        let Matrix {
+            m11,
+            m12,
+            m21,
+            m22,
+            m31,
+            m32,
+        } = self.transform;
+
+        format!(r#" transform="matrix({m11} {m12} {m21} {m22} {m31} {m32})""#)
+    }
+
+    fn push(&mut self, element: &str, attributes: &str, paint: &str) {
+        let transform = self.transform_attribute();
+        writeln!(self.body, "<{element} {attributes}{paint}{transform}/>").unwrap();
+    }
+}
+
+fn hex(color: Color) -> String {
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+fn fill(brush: &SvgBrush) -> String {
+    let mut attributes = format!(r#" fill="{}""#, brush.paint);
+
+    if brush.opacity < 1. {
+        write!(attributes, r#" fill-opacity="{}""#, brush.opacity).unwrap();
+    }
+
+    attributes
+}
+
+/* Direct2D strokes: flat caps, miter joins with a limit of 10. */
+fn stroke(brush: &SvgBrush, stroke_width: f32) -> String {
+    let mut attributes = format!(
+        r#" fill="none" stroke="{}" stroke-width="{stroke_width}" stroke-miterlimit="10""#,
+        brush.paint
+    );
+
+    if brush.opacity < 1. {
+        write!(attributes, r#" stroke-opacity="{}""#, brush.opacity).unwrap();
+    }
+
+    attributes
+}
+
+/// Path data of `path`; arcs stay arcs.
+pub fn path_data(path: &Path) -> String {
+    let mut data = String::new();
+
+    for figure in &path.figures {
+        let Point { x, y } = figure.start;
+        write!(data, "M{x} {y}").unwrap();
+
+        for segment in &figure.segments {
+            match segment {
+                Segment::Line(Point { x, y }) => write!(data, " L{x} {y}"),
+                Segment::Bezier(bezier) => write!(
+                    data,
+                    " C{} {} {} {} {} {}",
+                    bezier.point1.x,
+                    bezier.point1.y,
+                    bezier.point2.x,
+                    bezier.point2.y,
+                    bezier.point3.x,
+                    bezier.point3.y
+                ),
+                /* A positive-angle sweep is clockwise with y pointing down. */
+                Segment::Arc(arc) => write!(
+                    data,
+                    " A{} {} {} {} {} {} {}",
+                    arc.size.width,
+                    arc.size.height,
+                    arc.rotation_angle,
+                    (arc.arc_size == ArcSize::Large) as u8,
+                    (arc.sweep_direction == SweepDirection::Clockwise) as u8,
+                    arc.point.x,
+                    arc.point.y
+                ),
+            }
+            .unwrap();
+        }
+
+        if figure.end == FigureEnd::Closed {
+            data.push_str(" Z");
+        }
+    }
+
+    data
+}
+
+impl Canvas for SvgCanvas {
+    type Brush = SvgBrush;
+    type Geometry = String;
+    type Bitmap = SvgBitmap;
+
+    fn create_solid_brush(&mut self, color: Color) -> Result<Self::Brush> {
+        Ok(SvgBrush {
+            paint: hex(color),
+            opacity: color.a,
+        })
+    }
+
+    /* The unit circle stretched onto the ellipse; the focus is the gradient
+    origin. Stops interpolate in sRGB and pad, like the Direct2D brush. */
+    fn create_radial_gradient_brush(&mut self, gradient: &RadialGradient) -> Result<Self::Brush> {
+        let id = self.id("gradient");
+        let RadialGradient {
+            center,
+            radius_x,
+            radius_y,
+            gradient_origin_offset: offset,
+            ..
+        } = gradient;
+
+        writeln!(
+            self.defs,
+            r#"<radialGradient id="{id}" gradientUnits="userSpaceOnUse" cx="0" cy="0" r="1" fx="{}" fy="{}" gradientTransform="matrix({radius_x} 0 0 {radius_y} {} {})">"#,
+            offset.x / radius_x,
+            offset.y / radius_y,
+            center.x,
+            center.y
+        )
+        .unwrap();
+
+        for stop in &gradient.stops {
+            write!(
+                self.defs,
+                r#"<stop offset="{}" stop-color="{}""#,
+                stop.position,
+                hex(stop.color)
+            )
+            .unwrap();
+
+            if stop.color.a < 1. {
+                write!(self.defs, r#" stop-opacity="{}""#, stop.color.a).unwrap();
+            }
+
+            self.defs.push_str("/>\n");
+        }
+
+        self.defs.push_str("</radialGradient>\n");
+
+        Ok(SvgBrush {
+            paint: format!("url(#{id})"),
+            opacity: 1.,
+        })
+    }
+
+    fn create_geometry(&mut self, path: &Path) -> Result<Self::Geometry> {
+        Ok(path_data(path))
+    }
+
+    fn load_bitmap(&mut self, path: &std::path::Path) -> Result<Self::Bitmap> {
+        let image_error =
+            |error: &dyn std::fmt::Display| Error::Image(format!("{}: {error}", path.display()));
+
+        let png = fs::read(path).map_err(|error| image_error(&error))?;
+        let pixmap = tiny_skia::Pixmap::decode_png(&png).map_err(|error| image_error(&error))?;
+
+        Ok(SvgBitmap {
+            href: format!(
+                "data:image/png;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(&png)
+            ),
+            width: pixmap.width(),
+            height: pixmap.height(),
+        })
+    }
+
+    fn bitmap_size(&self, bitmap: &Self::Bitmap) -> Size {
+        Size {
+            width: bitmap.width as f32,
+            height: bitmap.height as f32,
+        }
+    }
+
+    fn size(&self) -> Size {
+        Size {
+            width: self.dpi.to_dips(self.width as f32),
+            height: self.dpi.to_dips(self.height as f32),
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    fn set_dpi(&mut self, dpi: Dpi) {
+        self.dpi = dpi;
+    }
+
+    /* Gradients are created ahead of the frame and stay defined. */
+    fn begin_draw(&mut self) {
+        self.body.clear();
+        self.transform = Matrix::identity();
+    }
+
+    fn end_draw(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /* Nothing to lose. */
+    fn recreate_target(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_transform(&mut self, transform: &Matrix) {
+        self.transform = *transform;
+    }
+
+    /* Covers everything drawn so far, so that can go. */
+    fn clear(&mut self, color: Color) {
+        self.body.clear();
+
+        let Size { width, height } = self.size();
+        let brush = SvgBrush {
+            paint: hex(color),
+            opacity: color.a,
+        };
+
+        writeln!(
+            self.body,
+            r#"<rect width="{width}" height="{height}"{}/>"#,
+            fill(&brush)
+        )
+        .unwrap();
+    }
+
+    fn draw_line(&mut self, p0: Point, p1: Point, brush: &Self::Brush, stroke_width: f32) {
+        let attributes = format!(r#"x1="{}" y1="{}" x2="{}" y2="{}""#, p0.x, p0.y, p1.x, p1.y);
+        self.push("line", &attributes, &stroke(brush, stroke_width));
+    }
+
+    fn draw_polyline(&mut self, points: &[Point], brush: &Self::Brush, stroke_width: f32) {
+        let mut attributes = String::from(r#"points=""#);
+
+        for (i, Point { x, y }) in points.iter().enumerate() {
+            let separator = if i == 0 { "" } else { " " };
+            write!(attributes, "{separator}{x},{y}").unwrap();
+        }
+
+        attributes.push('"');
+        self.push("polyline", &attributes, &stroke(brush, stroke_width));
+    }
+
+    fn fill_rectangle(&mut self, rect: &Rect, brush: &Self::Brush) {
+        let attributes = format!(
+            r#"x="{}" y="{}" width="{}" height="{}""#,
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height()
+        );
+        self.push("rect", &attributes, &fill(brush));
+    }
+
+    fn fill_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush) {
+        self.push("path", &format!(r#"d="{geometry}""#), &fill(brush));
+    }
+
+    fn draw_geometry(&mut self, geometry: &Self::Geometry, brush: &Self::Brush, stroke_width: f32) {
+        let attributes = format!(r#"d="{geometry}""#);
+        self.push("path", &attributes, &stroke(brush, stroke_width));
+    }
+
+    fn fill_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush) {
+        let attributes = ellipse_attributes(ellipse);
+        self.push("ellipse", &attributes, &fill(brush));
+    }
+
+    fn draw_ellipse(&mut self, ellipse: &Ellipse, brush: &Self::Brush, stroke_width: f32) {
+        let attributes = ellipse_attributes(ellipse);
+        self.push("ellipse", &attributes, &stroke(brush, stroke_width));
+    }
+
+    /* The whole image is placed so that `source` lands on `destination`, and
+    clipped to `destination`. */
+    fn draw_bitmap(
+        &mut self,
+        bitmap: &Self::Bitmap,
+        destination: Option<&Rect>,
+        opacity: f32,
+        source: Option<&Rect>,
+    ) {
+        let size = self.bitmap_size(bitmap);
+        let whole = Rect {
+            left: 0.,
+            top: 0.,
+            right: size.width,
+            bottom: size.height,
+        };
+        let source = source.copied().unwrap_or(whole);
+        let destination = destination.copied().unwrap_or(Rect {
+            left: 0.,
+            top: 0.,
+            right: source.width(),
+            bottom: source.height(),
+        });
+
+        let scale_x = destination.width() / source.width();
+        let scale_y = destination.height() / source.height();
+
+        let mut attributes = format!(
+            r#"x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none""#,
+            destination.left - source.left * scale_x,
+            destination.top - source.top * scale_y,
+            size.width * scale_x,
+            size.height * scale_y
+        );
+
+        if source != whole {
+            let id = self.id("clip");
+
+            writeln!(
+                self.defs,
+                r#"<clipPath id="{id}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+                destination.left,
+                destination.top,
+                destination.width(),
+                destination.height()
+            )
+            .unwrap();
+
+            write!(attributes, r#" clip-path="url(#{id})""#).unwrap();
+        }
+
+        if opacity < 1. {
+            write!(attributes, r#" opacity="{opacity}""#).unwrap();
+        }
+
+        write!(attributes, r#" href="{}""#, bitmap.href).unwrap();
+        self.push("image", &attributes, "");
+    }
+}
+
+fn ellipse_attributes(ellipse: &Ellipse) -> String {
+    format!(
+        r#"cx="{}" cy="{}" rx="{}" ry="{}""#,
+        ellipse.point.x, ellipse.point.y, ellipse.radius_x, ellipse.radius_y
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_options() {
+        assert_eq!(SvgOptions::from_args(args(&["--gif"])).unwrap(), None);
+        assert_eq!(
+            SvgOptions::from_args(args(&["--svg", "frame.svg", "--time", "1.5"])).unwrap(),
+            Some(SvgOptions {
+                path: PathBuf::from("frame.svg"),
+                time: 1.5
+            })
+        );
+        assert!(SvgOptions::from_args(args(&["--svg"])).is_err());
+        assert!(SvgOptions::from_args(args(&["--svg", "a.svg", "--time", "-1"])).is_err());
+    }
+
+    #[test]
+    fn paths_keep_arcs_and_curves() {
+        let mut builder = PathBuilder::new();
+        builder.begin_figure(Point { x: 0., y: 10. });
+        builder.add_line(Point { x: 5., y: 10. });
+        builder.add_bezier(BezierSegment {
+            point1: Point { x: 6., y: 0. },
+            point2: Point { x: 8., y: 0. },
+            point3: Point { x: 9., y: 10. },
+        });
+        builder.add_arc(ArcSegment {
+            point: Point { x: 20., y: 10. },
+            size: Size {
+                width: 5.5,
+                height: 4.,
+            },
+            rotation_angle: 0.,
+            sweep_direction: SweepDirection::Clockwise,
+            arc_size: ArcSize::Small,
+        });
+        builder.end_figure(FigureEnd::Closed);
+
+        assert_eq!(
+            path_data(&builder.close()),
+            "M0 10 L5 10 C6 0 8 0 9 10 A5.5 4 0 0 1 20 10 Z"
+        );
+    }
+
+    #[test]
+    fn frame_document() {
+        let mut canvas = SvgCanvas::new(200, 100);
+        let gradient = canvas
+            .create_radial_gradient_brush(&RadialGradient {
+                center: Point { x: 50., y: 40. },
+                radius_x: 20.,
+                radius_y: 10.,
+                gradient_origin_offset: Point { x: -10., y: 0. },
+                stops: vec![
+                    GradientStop {
+                        position: 0.,
+                        color: Color::rgb(1., 1., 1.),
+                    },
+                    GradientStop {
+                        position: 1.,
+                        color: Color {
+                            a: 0.5,
+                            ..Color::rgb(0., 0., 0.)
+                        },
+                    },
+                ],
+            })
+            .unwrap();
+        let pen = canvas.create_solid_brush(Color::rgb(1., 0., 0.)).unwrap();
+
+        canvas.begin_draw();
+        canvas.clear(Color::rgb(0., 0., 1.));
+        canvas.set_transform(&Matrix::translation(5., 6.));
+        canvas.fill_ellipse(
+            &Ellipse {
+                point: Point { x: 50., y: 40. },
+                radius_x: 20.,
+                radius_y: 10.,
+            },
+            &gradient,
+        );
+        canvas.set_transform(&Matrix::identity());
+        canvas.draw_polyline(
+            &[
+                Point { x: 0., y: 0. },
+                Point { x: 10., y: 5. },
+                Point { x: 20., y: 0. },
+            ],
+            &pen,
+            1.5,
+        );
+        canvas.end_draw().unwrap();
+
+        assert_eq!(
+            canvas.to_svg(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100" viewBox="0 0 200 100">
+<defs>
+<radialGradient id="gradient1" gradientUnits="userSpaceOnUse" cx="0" cy="0" r="1" fx="-0.5" fy="0" gradientTransform="matrix(20 0 0 10 50 40)">
+<stop offset="0" stop-color="#ffffff"/>
+<stop offset="1" stop-color="#000000" stop-opacity="0.5"/>
+</radialGradient>
+</defs>
+<rect width="200" height="100" fill="#0000ff"/>
+<ellipse cx="50" cy="40" rx="20" ry="10" fill="url(#gradient1)" transform="matrix(1 0 0 1 5 6)"/>
+<polyline points="0,0 10,5 20,0" fill="none" stroke="#ff0000" stroke-width="1.5" stroke-miterlimit="10"/>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn sprites_are_clipped_images() {
+        let mut canvas = SvgCanvas::new(100, 100);
+        let bitmap = SvgBitmap {
+            href: "data:image/png;base64,".to_string(),
+            width: 40,
+            height: 10,
+        };
+
+        canvas.begin_draw();
+        canvas.draw_bitmap(
+            &bitmap,
+            Some(&Rect {
+                left: 50.,
+                top: 50.,
+                right: 60.,
+                bottom: 70.,
+            }),
+            0.7,
+            Some(&Rect {
+                left: 10.,
+                top: 0.,
+                right: 20.,
+                bottom: 10.,
+            }),
+        );
+
+        let svg = canvas.to_svg();
+
+        assert!(svg.contains(
+            r#"<clipPath id="clip1"><rect x="50" y="50" width="10" height="20"/></clipPath>"#
+        ));
+        assert!(svg.contains(
+            r#"<image x="40" y="50" width="40" height="20" preserveAspectRatio="none" clip-path="url(#clip1)" opacity="0.7" href="data:image/png;base64,"/>"#
+        ));
+    }
+}
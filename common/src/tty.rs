@@ -0,0 +1,97 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use termion::cursor::{self, HideCursor};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::IntoRawMode;
+use termion::screen::IntoAlternateScreen;
+
+use crate::braille::BrailleCanvas;
+use crate::input::{self, InputEvent, Modifiers};
+use crate::playback::Playback;
+use crate::Result;
+
+pub const USAGE: &str = "--tty";
+
+/// Whether `--tty` asks for the terminal renderer instead of a window.
+pub fn requested<I>(args: I) -> bool
+where
+    I: IntoIterator<Item = String>,
+{
+    args.into_iter().any(|arg| arg == USAGE)
+}
+
+/// Animates in the terminal until `q`, Esc or Ctrl+C: calls `draw` with a
+/// cleared canvas the size of the terminal and the frame number, `fps`
+/// times per second. The [`Playback`] keys pause, step and change the speed
+/// of the frame count. The terminal is restored on return.
+pub fn animate<F>(fps: u32, mut draw: F) -> Result<()>
+where
+    F: FnMut(&mut BrailleCanvas, u32),
+{
+    let period = Duration::from_secs_f64(1. / fps.max(1) as f64);
+    let mut keys = termion::async_stdin().keys();
+    let mut screen = HideCursor::from(io::stdout().into_raw_mode()?.into_alternate_screen()?);
+    let mut canvas = BrailleCanvas::new(0, 0);
+    /* Frames, fractional when slowed down. */
+    let mut frames = 0.;
+    let mut playback = Playback::new(1);
+
+    loop {
+        let start = Instant::now();
+
+        for key in keys.by_ref() {
+            match key? {
+                Key::Char('q' | 'Q') | Key::Esc | Key::Ctrl('c') => return Ok(()),
+                key => {
+                    if let Some(event) = playback_key(key) {
+                        playback.on_input(&event);
+                    }
+                }
+            }
+        }
+
+        let (columns, rows) = termion::terminal_size()?;
+
+        if (columns as usize, rows as usize) != (canvas.columns(), canvas.rows()) {
+            canvas = BrailleCanvas::new(columns as usize, rows as usize);
+        }
+
+        canvas.clear();
+        draw(&mut canvas, frames as u32);
+        frames += playback.advance(1.);
+
+        /* Raw mode: no implicit carriage returns. */
+        write!(screen, "{}", cursor::Goto(1, 1))?;
+
+        for (row, line) in canvas.lines().enumerate() {
+            if row > 0 {
+                write!(screen, "\r\n")?;
+            }
+
+            write!(screen, "{line}")?;
+        }
+
+        screen.flush()?;
+        thread::sleep(period.saturating_sub(start.elapsed()));
+    }
+}
+
+/* Terminals send characters rather than key codes; `=` is `+` without
+Shift. */
+fn playback_key(key: Key) -> Option<InputEvent> {
+    let key = match key {
+        Key::Char(' ') => input::Key::Space,
+        Key::Char('=') => input::Key::Char('+'),
+        Key::Char(char @ ('+' | '-' | '.')) => input::Key::Char(char),
+        _ => return None,
+    };
+
+    Some(InputEvent::KeyDown {
+        key,
+        modifiers: Modifiers::default(),
+        repeat: false,
+    })
+}
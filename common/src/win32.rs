@@ -0,0 +1,401 @@
+/* Message, virtual key and flag numbers from WinUser.h, spelled out so that
+decoding, naming and replaying messages work and are tested on every
+platform. Defined once here, and checked against the SDK on Windows. */
+
+/* Each constant, and a table of them with their names, sorted by number for
+looking names up. */
+macro_rules! numbers {
+    ($table:ident: $type:ty { $($name:ident = $value:literal,)* }) => {
+        $(pub(crate) const $name: $type = $value;)*
+
+        pub(crate) const $table: &[($type, &str)] = &[$(($name, stringify!($name)),)*];
+    };
+}
+
+/* Messages with a name of their own, up to WM_USER. */
+numbers! {
+    MESSAGES: u32 {
+        WM_NULL = 0x0000,
+        WM_CREATE = 0x0001,
+        WM_DESTROY = 0x0002,
+        WM_MOVE = 0x0003,
+        WM_SIZE = 0x0005,
+        WM_ACTIVATE = 0x0006,
+        WM_SETFOCUS = 0x0007,
+        WM_KILLFOCUS = 0x0008,
+        WM_ENABLE = 0x000A,
+        WM_SETREDRAW = 0x000B,
+        WM_SETTEXT = 0x000C,
+        WM_GETTEXT = 0x000D,
+        WM_GETTEXTLENGTH = 0x000E,
+        WM_PAINT = 0x000F,
+        WM_CLOSE = 0x0010,
+        WM_QUERYENDSESSION = 0x0011,
+        WM_QUIT = 0x0012,
+        WM_QUERYOPEN = 0x0013,
+        WM_ERASEBKGND = 0x0014,
+        WM_SYSCOLORCHANGE = 0x0015,
+        WM_ENDSESSION = 0x0016,
+        WM_SHOWWINDOW = 0x0018,
+        WM_SETTINGCHANGE = 0x001A,
+        WM_DEVMODECHANGE = 0x001B,
+        WM_ACTIVATEAPP = 0x001C,
+        WM_FONTCHANGE = 0x001D,
+        WM_TIMECHANGE = 0x001E,
+        WM_CANCELMODE = 0x001F,
+        WM_SETCURSOR = 0x0020,
+        WM_MOUSEACTIVATE = 0x0021,
+        WM_CHILDACTIVATE = 0x0022,
+        WM_QUEUESYNC = 0x0023,
+        WM_GETMINMAXINFO = 0x0024,
+        WM_PAINTICON = 0x0026,
+        WM_ICONERASEBKGND = 0x0027,
+        WM_NEXTDLGCTL = 0x0028,
+        WM_SPOOLERSTATUS = 0x002A,
+        WM_DRAWITEM = 0x002B,
+        WM_MEASUREITEM = 0x002C,
+        WM_DELETEITEM = 0x002D,
+        WM_VKEYTOITEM = 0x002E,
+        WM_CHARTOITEM = 0x002F,
+        WM_SETFONT = 0x0030,
+        WM_GETFONT = 0x0031,
+        WM_SETHOTKEY = 0x0032,
+        WM_GETHOTKEY = 0x0033,
+        WM_QUERYDRAGICON = 0x0037,
+        WM_COMPAREITEM = 0x0039,
+        WM_GETOBJECT = 0x003D,
+        WM_COMPACTING = 0x0041,
+        WM_COMMNOTIFY = 0x0044,
+        WM_WINDOWPOSCHANGING = 0x0046,
+        WM_WINDOWPOSCHANGED = 0x0047,
+        WM_POWER = 0x0048,
+        WM_COPYDATA = 0x004A,
+        WM_CANCELJOURNAL = 0x004B,
+        WM_NOTIFY = 0x004E,
+        WM_INPUTLANGCHANGEREQUEST = 0x0050,
+        WM_INPUTLANGCHANGE = 0x0051,
+        WM_TCARD = 0x0052,
+        WM_HELP = 0x0053,
+        WM_USERCHANGED = 0x0054,
+        WM_NOTIFYFORMAT = 0x0055,
+        WM_CONTEXTMENU = 0x007B,
+        WM_STYLECHANGING = 0x007C,
+        WM_STYLECHANGED = 0x007D,
+        WM_DISPLAYCHANGE = 0x007E,
+        WM_GETICON = 0x007F,
+        WM_SETICON = 0x0080,
+        WM_NCCREATE = 0x0081,
+        WM_NCDESTROY = 0x0082,
+        WM_NCCALCSIZE = 0x0083,
+        WM_NCHITTEST = 0x0084,
+        WM_NCPAINT = 0x0085,
+        WM_NCACTIVATE = 0x0086,
+        WM_GETDLGCODE = 0x0087,
+        WM_SYNCPAINT = 0x0088,
+        WM_NCMOUSEMOVE = 0x00A0,
+        WM_NCLBUTTONDOWN = 0x00A1,
+        WM_NCLBUTTONUP = 0x00A2,
+        WM_NCLBUTTONDBLCLK = 0x00A3,
+        WM_NCRBUTTONDOWN = 0x00A4,
+        WM_NCRBUTTONUP = 0x00A5,
+        WM_NCRBUTTONDBLCLK = 0x00A6,
+        WM_NCMBUTTONDOWN = 0x00A7,
+        WM_NCMBUTTONUP = 0x00A8,
+        WM_NCMBUTTONDBLCLK = 0x00A9,
+        WM_NCXBUTTONDOWN = 0x00AB,
+        WM_NCXBUTTONUP = 0x00AC,
+        WM_NCXBUTTONDBLCLK = 0x00AD,
+        WM_INPUT_DEVICE_CHANGE = 0x00FE,
+        WM_INPUT = 0x00FF,
+        WM_KEYDOWN = 0x0100,
+        WM_KEYUP = 0x0101,
+        WM_CHAR = 0x0102,
+        WM_DEADCHAR = 0x0103,
+        WM_SYSKEYDOWN = 0x0104,
+        WM_SYSKEYUP = 0x0105,
+        WM_SYSCHAR = 0x0106,
+        WM_SYSDEADCHAR = 0x0107,
+        WM_UNICHAR = 0x0109,
+        WM_IME_STARTCOMPOSITION = 0x010D,
+        WM_IME_ENDCOMPOSITION = 0x010E,
+        WM_IME_COMPOSITION = 0x010F,
+        WM_INITDIALOG = 0x0110,
+        WM_COMMAND = 0x0111,
+        WM_SYSCOMMAND = 0x0112,
+        WM_TIMER = 0x0113,
+        WM_HSCROLL = 0x0114,
+        WM_VSCROLL = 0x0115,
+        WM_INITMENU = 0x0116,
+        WM_INITMENUPOPUP = 0x0117,
+        WM_GESTURE = 0x0119,
+        WM_GESTURENOTIFY = 0x011A,
+        WM_MENUSELECT = 0x011F,
+        WM_MENUCHAR = 0x0120,
+        WM_ENTERIDLE = 0x0121,
+        WM_MENURBUTTONUP = 0x0122,
+        WM_MENUDRAG = 0x0123,
+        WM_MENUGETOBJECT = 0x0124,
+        WM_UNINITMENUPOPUP = 0x0125,
+        WM_MENUCOMMAND = 0x0126,
+        WM_CHANGEUISTATE = 0x0127,
+        WM_UPDATEUISTATE = 0x0128,
+        WM_QUERYUISTATE = 0x0129,
+        WM_CTLCOLORMSGBOX = 0x0132,
+        WM_CTLCOLOREDIT = 0x0133,
+        WM_CTLCOLORLISTBOX = 0x0134,
+        WM_CTLCOLORBTN = 0x0135,
+        WM_CTLCOLORDLG = 0x0136,
+        WM_CTLCOLORSCROLLBAR = 0x0137,
+        WM_CTLCOLORSTATIC = 0x0138,
+        WM_MOUSEMOVE = 0x0200,
+        WM_LBUTTONDOWN = 0x0201,
+        WM_LBUTTONUP = 0x0202,
+        WM_LBUTTONDBLCLK = 0x0203,
+        WM_RBUTTONDOWN = 0x0204,
+        WM_RBUTTONUP = 0x0205,
+        WM_RBUTTONDBLCLK = 0x0206,
+        WM_MBUTTONDOWN = 0x0207,
+        WM_MBUTTONUP = 0x0208,
+        WM_MBUTTONDBLCLK = 0x0209,
+        WM_MOUSEWHEEL = 0x020A,
+        WM_XBUTTONDOWN = 0x020B,
+        WM_XBUTTONUP = 0x020C,
+        WM_XBUTTONDBLCLK = 0x020D,
+        WM_MOUSEHWHEEL = 0x020E,
+        WM_PARENTNOTIFY = 0x0210,
+        WM_ENTERMENULOOP = 0x0211,
+        WM_EXITMENULOOP = 0x0212,
+        WM_NEXTMENU = 0x0213,
+        WM_SIZING = 0x0214,
+        WM_CAPTURECHANGED = 0x0215,
+        WM_MOVING = 0x0216,
+        WM_POWERBROADCAST = 0x0218,
+        WM_DEVICECHANGE = 0x0219,
+        WM_ENTERSIZEMOVE = 0x0231,
+        WM_EXITSIZEMOVE = 0x0232,
+        WM_DROPFILES = 0x0233,
+        WM_TOUCH = 0x0240,
+        WM_POINTERUPDATE = 0x0245,
+        WM_POINTERDOWN = 0x0246,
+        WM_POINTERUP = 0x0247,
+        WM_POINTERENTER = 0x0249,
+        WM_POINTERLEAVE = 0x024A,
+        WM_IME_SETCONTEXT = 0x0281,
+        WM_IME_NOTIFY = 0x0282,
+        WM_IME_CONTROL = 0x0283,
+        WM_IME_COMPOSITIONFULL = 0x0284,
+        WM_IME_SELECT = 0x0285,
+        WM_IME_CHAR = 0x0286,
+        WM_IME_REQUEST = 0x0288,
+        WM_IME_KEYDOWN = 0x0290,
+        WM_IME_KEYUP = 0x0291,
+        WM_NCMOUSEHOVER = 0x02A0,
+        WM_MOUSEHOVER = 0x02A1,
+        WM_NCMOUSELEAVE = 0x02A2,
+        WM_MOUSELEAVE = 0x02A3,
+        WM_WTSSESSION_CHANGE = 0x02B1,
+        WM_DPICHANGED = 0x02E0,
+        WM_DPICHANGED_BEFOREPARENT = 0x02E2,
+        WM_DPICHANGED_AFTERPARENT = 0x02E3,
+        WM_GETDPISCALEDSIZE = 0x02E4,
+        WM_CUT = 0x0300,
+        WM_COPY = 0x0301,
+        WM_PASTE = 0x0302,
+        WM_CLEAR = 0x0303,
+        WM_UNDO = 0x0304,
+        WM_RENDERFORMAT = 0x0305,
+        WM_RENDERALLFORMATS = 0x0306,
+        WM_DESTROYCLIPBOARD = 0x0307,
+        WM_DRAWCLIPBOARD = 0x0308,
+        WM_QUERYNEWPALETTE = 0x030F,
+        WM_PALETTEISCHANGING = 0x0310,
+        WM_PALETTECHANGED = 0x0311,
+        WM_HOTKEY = 0x0312,
+        WM_PRINT = 0x0317,
+        WM_PRINTCLIENT = 0x0318,
+        WM_APPCOMMAND = 0x0319,
+        WM_THEMECHANGED = 0x031A,
+        WM_CLIPBOARDUPDATE = 0x031D,
+        WM_DWMCOMPOSITIONCHANGED = 0x031E,
+        WM_DWMNCRENDERINGCHANGED = 0x031F,
+        WM_DWMCOLORIZATIONCOLORCHANGED = 0x0320,
+        WM_DWMWINDOWMAXIMIZEDCHANGE = 0x0321,
+        WM_DWMSENDICONICTHUMBNAIL = 0x0323,
+        WM_DWMSENDICONICLIVEPREVIEWBITMAP = 0x0326,
+        WM_GETTITLEBARINFOEX = 0x033F,
+    }
+}
+
+/* The SC_* commands of WM_SYSCOMMAND, in the high bits of wparam. */
+numbers! {
+    SYSTEM_COMMANDS: usize {
+        SC_SIZE = 0xF000,
+        SC_MOVE = 0xF010,
+        SC_MINIMIZE = 0xF020,
+        SC_MAXIMIZE = 0xF030,
+        SC_NEXTWINDOW = 0xF040,
+        SC_PREVWINDOW = 0xF050,
+        SC_CLOSE = 0xF060,
+        SC_VSCROLL = 0xF070,
+        SC_HSCROLL = 0xF080,
+        SC_MOUSEMENU = 0xF090,
+        SC_KEYMENU = 0xF100,
+        SC_RESTORE = 0xF120,
+        SC_TASKLIST = 0xF130,
+        SC_SCREENSAVE = 0xF140,
+        SC_HOTKEY = 0xF150,
+        SC_DEFAULT = 0xF160,
+        SC_MONITORPOWER = 0xF170,
+        SC_CONTEXTHELP = 0xF180,
+    }
+}
+
+/* Virtual keys that are not letters, digits, keypad digits or F keys. */
+numbers! {
+    VIRTUAL_KEYS: u32 {
+        VK_LBUTTON = 0x01,
+        VK_RBUTTON = 0x02,
+        VK_CANCEL = 0x03,
+        VK_MBUTTON = 0x04,
+        VK_BACK = 0x08,
+        VK_TAB = 0x09,
+        VK_CLEAR = 0x0C,
+        VK_RETURN = 0x0D,
+        VK_SHIFT = 0x10,
+        VK_CONTROL = 0x11,
+        VK_MENU = 0x12,
+        VK_PAUSE = 0x13,
+        VK_CAPITAL = 0x14,
+        VK_ESCAPE = 0x1B,
+        VK_SPACE = 0x20,
+        VK_PRIOR = 0x21,
+        VK_NEXT = 0x22,
+        VK_END = 0x23,
+        VK_HOME = 0x24,
+        VK_LEFT = 0x25,
+        VK_UP = 0x26,
+        VK_RIGHT = 0x27,
+        VK_DOWN = 0x28,
+        VK_SNAPSHOT = 0x2C,
+        VK_INSERT = 0x2D,
+        VK_DELETE = 0x2E,
+        VK_LWIN = 0x5B,
+        VK_RWIN = 0x5C,
+        VK_APPS = 0x5D,
+        VK_MULTIPLY = 0x6A,
+        VK_ADD = 0x6B,
+        VK_SEPARATOR = 0x6C,
+        VK_SUBTRACT = 0x6D,
+        VK_DECIMAL = 0x6E,
+        VK_DIVIDE = 0x6F,
+        VK_NUMLOCK = 0x90,
+        VK_SCROLL = 0x91,
+        VK_LSHIFT = 0xA0,
+        VK_RSHIFT = 0xA1,
+        VK_LCONTROL = 0xA2,
+        VK_RCONTROL = 0xA3,
+        VK_LMENU = 0xA4,
+        VK_RMENU = 0xA5,
+        VK_OEM_1 = 0xBA,
+        VK_OEM_PLUS = 0xBB,
+        VK_OEM_COMMA = 0xBC,
+        VK_OEM_MINUS = 0xBD,
+        VK_OEM_PERIOD = 0xBE,
+        VK_OEM_2 = 0xBF,
+        VK_OEM_3 = 0xC0,
+        VK_OEM_4 = 0xDB,
+        VK_OEM_5 = 0xDC,
+        VK_OEM_6 = 0xDD,
+        VK_OEM_7 = 0xDE,
+    }
+}
+
+pub(crate) const WM_USER: u32 = 0x0400;
+pub(crate) const WM_APP: u32 = 0x8000;
+/* `RegisterWindowMessage` hands out numbers from here up to 0xFFFF. */
+pub(crate) const FIRST_REGISTERED: u32 = 0xC000;
+
+pub(crate) const VK_NUMPAD0: u32 = 0x60;
+pub(crate) const VK_NUMPAD9: u32 = 0x69;
+pub(crate) const VK_F1: u32 = 0x70;
+pub(crate) const VK_F24: u32 = 0x87;
+
+/* The MK_* flags of mouse messages, in wparam. */
+pub(crate) const MK_LBUTTON: usize = 0x0001;
+pub(crate) const MK_RBUTTON: usize = 0x0002;
+pub(crate) const MK_SHIFT: usize = 0x0004;
+pub(crate) const MK_CONTROL: usize = 0x0008;
+pub(crate) const MK_MBUTTON: usize = 0x0010;
+pub(crate) const MK_XBUTTON1: usize = 0x0020;
+pub(crate) const MK_XBUTTON2: usize = 0x0040;
+
+/* Which X button, in the high word of wparam. */
+pub(crate) const XBUTTON1: u16 = 0x0001;
+pub(crate) const XBUTTON2: u16 = 0x0002;
+
+pub(crate) const WHEEL_DELTA: f32 = 120.;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_are_sorted() {
+        assert!(MESSAGES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(SYSTEM_COMMANDS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(VIRTUAL_KEYS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn constants_match_the_sdk() {
+        use windows::Win32::System::SystemServices as ss;
+        use windows::Win32::UI::Input::KeyboardAndMouse as km;
+        use windows::Win32::UI::WindowsAndMessaging as wm;
+
+        for (ours, sdk) in [
+            (WM_NULL, wm::WM_NULL),
+            (WM_SIZE, wm::WM_SIZE),
+            (WM_PAINT, wm::WM_PAINT),
+            (WM_KILLFOCUS, wm::WM_KILLFOCUS),
+            (WM_NCHITTEST, wm::WM_NCHITTEST),
+            (WM_SYSKEYUP, wm::WM_SYSKEYUP),
+            (WM_COMMAND, wm::WM_COMMAND),
+            (WM_SYSCOMMAND, wm::WM_SYSCOMMAND),
+            (WM_TIMER, wm::WM_TIMER),
+            (WM_MOUSEMOVE, wm::WM_MOUSEMOVE),
+            (WM_LBUTTONDOWN, wm::WM_LBUTTONDOWN),
+            (WM_XBUTTONUP, wm::WM_XBUTTONUP),
+            (WM_MOUSEHWHEEL, wm::WM_MOUSEHWHEEL),
+            (WM_DPICHANGED, wm::WM_DPICHANGED),
+            (WM_GETTITLEBARINFOEX, wm::WM_GETTITLEBARINFOEX),
+            (WM_USER, wm::WM_USER),
+            (WM_APP, wm::WM_APP),
+        ] {
+            assert_eq!(ours, sdk);
+        }
+
+        assert_eq!(SC_SIZE, wm::SC_SIZE as usize);
+        assert_eq!(SC_CLOSE, wm::SC_CLOSE as usize);
+        assert_eq!(SC_CONTEXTHELP, wm::SC_CONTEXTHELP as usize);
+
+        for (ours, sdk) in [
+            (VK_BACK, km::VK_BACK),
+            (VK_ESCAPE, km::VK_ESCAPE),
+            (VK_DOWN, km::VK_DOWN),
+            (VK_NUMPAD0, km::VK_NUMPAD0),
+            (VK_DECIMAL, km::VK_DECIMAL),
+            (VK_F1, km::VK_F1),
+            (VK_F24, km::VK_F24),
+            (VK_RMENU, km::VK_RMENU),
+            (VK_OEM_PLUS, km::VK_OEM_PLUS),
+            (VK_OEM_7, km::VK_OEM_7),
+        ] {
+            assert_eq!(ours, sdk.0 as u32);
+        }
+
+        assert_eq!(MK_XBUTTON2 as u32, ss::MK_XBUTTON2.0);
+        assert_eq!(XBUTTON2 as u32, wm::XBUTTON2.0);
+        assert_eq!(WHEEL_DELTA as u32, wm::WHEEL_DELTA);
+    }
+}
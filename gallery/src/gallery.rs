@@ -0,0 +1,416 @@
+use common::canvas::{Canvas, Color, Rect};
+use common::demo::Demo;
+use common::input::{InputEvent, Key, MouseButton};
+use common::playback::Playback;
+use common::software::SoftwareCanvas;
+use common::Result;
+
+const COLUMNS: usize = 3;
+const MARGIN: f32 = 24.;
+const BORDER: f32 = 3.;
+
+const BACKGROUND: Color = Color::rgb(0.15, 0.15, 0.18);
+const FRAME: Color = Color::rgb(0.4, 0.4, 0.45);
+const HOVERED_FRAME: Color = Color::rgb(1., 0.75, 0.2);
+
+/// The launcher: a page of live thumbnails, and one demo at a time filling
+/// the window. Keys 1 to 9 show a demo, 0 and Escape go back to the
+/// thumbnails. Only the shown demo advances; the others wait where they
+/// were. Each demo keeps its own [`Playback`] state.
+pub struct Gallery {
+    demos: Vec<Box<dyn Demo>>,
+    playback: Vec<Playback>,
+    shown: Option<usize>,
+    hovered: Option<usize>,
+    canvas: SoftwareCanvas,
+    /* The thumbnail page needs drawing again. */
+    stale: bool,
+}
+
+impl Gallery {
+    pub fn new(demos: Vec<Box<dyn Demo>>, width: u32, height: u32) -> Result<Self> {
+        Ok(Gallery {
+            playback: demos
+                .iter()
+                .map(|demo| Playback::new(demo.steps_per_second()))
+                .collect(),
+            demos,
+            shown: None,
+            hovered: None,
+            canvas: SoftwareCanvas::new(width, height)?,
+            stale: true,
+        })
+    }
+
+    pub fn title(&self) -> String {
+        match self.shown {
+            Some(index) => {
+                self.playback[index].title(&format!("{} - Demo gallery", self.demos[index].name()))
+            }
+            None => format!("Demo gallery - press 1 to {}", self.demos.len()),
+        }
+    }
+
+    /// The frame to present.
+    pub fn canvas(&self) -> &SoftwareCanvas {
+        match self.shown {
+            Some(index) => self.demos[index].canvas(),
+            None => &self.canvas,
+        }
+    }
+
+    /// Shows the demo at `index`, or the thumbnails for `None`.
+    pub fn show(&mut self, index: Option<usize>) {
+        let index = index.filter(|&index| index < self.demos.len());
+
+        if index == self.shown {
+            return;
+        }
+
+        /* A held button or key would otherwise stay held. */
+        if let Some(shown) = self.shown {
+            self.demos[shown].on_input(InputEvent::FocusLost);
+        }
+
+        self.shown = index;
+        self.stale = true;
+    }
+
+    /// Advances the shown demo by `dt` seconds and draws the frame.
+    pub fn frame(&mut self, dt: f64) -> Result<()> {
+        let (width, height) = self.pixel_size();
+
+        match self.shown {
+            Some(index) => {
+                let demo = &mut self.demos[index];
+                fit(demo.as_mut(), width, height)?;
+                demo.update(self.playback[index].advance(dt));
+                demo.render()
+            }
+            None if self.stale => self.draw_thumbnails(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.canvas.resize(width, height)?;
+        self.stale = true;
+        Ok(())
+    }
+
+    pub fn on_input(&mut self, event: InputEvent) {
+        if let Some(index) = self.shown {
+            if self.playback[index].on_input(&event) {
+                return;
+            }
+        }
+
+        match (self.shown, event) {
+            (
+                _,
+                InputEvent::KeyDown {
+                    key: Key::Char(digit @ '1'..='9'),
+                    ..
+                },
+            ) => self.show(Some(digit as usize - '1' as usize)),
+            (
+                Some(_),
+                InputEvent::KeyDown {
+                    key: Key::Char('0') | Key::Escape,
+                    ..
+                },
+            ) => self.show(None),
+            (Some(index), event) => self.demos[index].on_input(event),
+            (None, InputEvent::MouseMove { x, y }) => {
+                let hovered = self.thumbnail_at(x as f32, y as f32);
+                self.stale |= hovered != self.hovered;
+                self.hovered = hovered;
+            }
+            (
+                None,
+                InputEvent::MouseDown {
+                    button: MouseButton::Left,
+                    x,
+                    y,
+                },
+            ) => self.show(self.thumbnail_at(x as f32, y as f32)),
+            _ => {}
+        }
+    }
+
+    fn pixel_size(&self) -> (u32, u32) {
+        (self.canvas.pixmap().width(), self.canvas.pixmap().height())
+    }
+
+    /* Cells of a grid, `COLUMNS` wide, over the whole window. */
+    fn cell(&self, index: usize) -> Rect {
+        let size = self.canvas.size();
+        let rows = self.demos.len().div_ceil(COLUMNS).max(1);
+        let (width, height) = (size.width / COLUMNS as f32, size.height / rows as f32);
+        let (column, row) = ((index % COLUMNS) as f32, (index / COLUMNS) as f32);
+
+        Rect {
+            left: column * width + MARGIN,
+            top: row * height + MARGIN,
+            right: (column + 1.) * width - MARGIN,
+            bottom: (row + 1.) * height - MARGIN,
+        }
+    }
+
+    /* A demo frame scaled into its cell, keeping the aspect ratio. */
+    fn thumbnail(&self, index: usize) -> Rect {
+        let cell = self.cell(index);
+        let frame = self.demos[index].canvas().size();
+        let scale = (cell.width() / frame.width).min(cell.height() / frame.height);
+        let (width, height) = (frame.width * scale, frame.height * scale);
+        let left = cell.left + (cell.width() - width) / 2.;
+        let top = cell.top + (cell.height() - height) / 2.;
+
+        Rect {
+            left,
+            top,
+            right: left + width,
+            bottom: top + height,
+        }
+    }
+
+    fn thumbnail_at(&self, x: f32, y: f32) -> Option<usize> {
+        (0..self.demos.len()).find(|&index| {
+            let rect = self.thumbnail(index);
+            (rect.left..rect.right).contains(&x) && (rect.top..rect.bottom).contains(&y)
+        })
+    }
+
+    /* Every demo draws its current state at the window size, without
+    advancing, and the page shows the frames side by side. */
+    fn draw_thumbnails(&mut self) -> Result<()> {
+        let (width, height) = self.pixel_size();
+
+        for demo in &mut self.demos {
+            fit(demo.as_mut(), width, height)?;
+            demo.render()?;
+        }
+
+        let frame = self.canvas.create_solid_brush(FRAME)?;
+        let hovered_frame = self.canvas.create_solid_brush(HOVERED_FRAME)?;
+
+        self.canvas.begin_draw();
+        self.canvas.clear(BACKGROUND);
+
+        for index in 0..self.demos.len() {
+            let rect = self.thumbnail(index);
+            let border = Rect {
+                left: rect.left - BORDER,
+                top: rect.top - BORDER,
+                right: rect.right + BORDER,
+                bottom: rect.bottom + BORDER,
+            };
+            let brush = match self.hovered == Some(index) {
+                true => &hovered_frame,
+                false => &frame,
+            };
+
+            self.canvas.fill_rectangle(&border, brush);
+            self.canvas
+                .draw_bitmap(self.demos[index].canvas().pixmap(), Some(&rect), 1., None);
+        }
+
+        self.stale = false;
+        self.canvas.end_draw()
+    }
+}
+
+/* Demos render at the size of the window they are shown in. */
+fn fit(demo: &mut dyn Demo, width: u32, height: u32) -> Result<()> {
+    let pixmap = demo.canvas().pixmap();
+
+    if (pixmap.width(), pixmap.height()) != (width, height) {
+        demo.resize(width, height)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /* Records what happens to it; draws nothing. */
+    struct Probe {
+        name: String,
+        canvas: SoftwareCanvas,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Demo for Probe {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn update(&mut self, dt: f64) {
+            self.log
+                .borrow_mut()
+                .push(format!("{} update {dt}", self.name));
+        }
+
+        fn render(&mut self) -> Result<()> {
+            self.log.borrow_mut().push(format!("{} render", self.name));
+            Ok(())
+        }
+
+        fn canvas(&self) -> &SoftwareCanvas {
+            &self.canvas
+        }
+
+        fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+            self.canvas.resize(width, height)
+        }
+
+        fn steps_per_second(&self) -> u32 {
+            4
+        }
+
+        fn on_input(&mut self, event: InputEvent) {
+            self.log
+                .borrow_mut()
+                .push(format!("{} {event:?}", self.name));
+        }
+    }
+
+    fn gallery(count: usize) -> (Gallery, Rc<RefCell<Vec<String>>>) {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let demos = (0..count)
+            .map(|index| {
+                Box::new(Probe {
+                    name: format!("demo{}", index + 1),
+                    canvas: SoftwareCanvas::new(1, 1).unwrap(),
+                    log: log.clone(),
+                }) as Box<dyn Demo>
+            })
+            .collect();
+
+        (Gallery::new(demos, 900, 600).unwrap(), log)
+    }
+
+    fn key(key: Key) -> InputEvent {
+        InputEvent::KeyDown {
+            key,
+            modifiers: Default::default(),
+            repeat: false,
+        }
+    }
+
+    fn take(log: &Rc<RefCell<Vec<String>>>) -> Vec<String> {
+        log.borrow_mut().drain(..).collect()
+    }
+
+    #[test]
+    fn only_the_shown_demo_advances() {
+        let (mut gallery, log) = gallery(3);
+
+        gallery.frame(0.5).unwrap();
+        assert_eq!(take(&log), ["demo1 render", "demo2 render", "demo3 render"]);
+        assert_eq!(gallery.canvas().pixmap().width(), 900);
+
+        gallery.on_input(key(Key::Char('2')));
+        gallery.frame(0.25).unwrap();
+        gallery.on_input(key(Key::Char('3')));
+        gallery.frame(0.125).unwrap();
+
+        assert_eq!(
+            take(&log),
+            [
+                "demo2 update 0.25",
+                "demo2 render",
+                "demo2 FocusLost",
+                "demo3 update 0.125",
+                "demo3 render"
+            ]
+        );
+        assert_eq!(gallery.title(), "demo3 - Demo gallery");
+        assert_eq!(
+            gallery.canvas().pixmap().width(),
+            900,
+            "resized to the window"
+        );
+    }
+
+    #[test]
+    fn escape_and_zero_go_back() {
+        let (mut gallery, log) = gallery(2);
+
+        gallery.on_input(key(Key::Char('9')));
+        assert_eq!(gallery.shown, None, "no ninth demo");
+
+        gallery.on_input(key(Key::Char('1')));
+        gallery.on_input(key(Key::Char('A')));
+        gallery.on_input(key(Key::Escape));
+        assert_eq!(gallery.shown, None);
+
+        gallery.on_input(key(Key::Char('2')));
+        gallery.on_input(key(Key::Char('0')));
+        assert_eq!(gallery.shown, None);
+
+        let log = take(&log);
+        assert!(log[0].starts_with("demo1 KeyDown"), "{log:?}");
+        assert_eq!(log[1..], ["demo1 FocusLost", "demo2 FocusLost"]);
+    }
+
+    #[test]
+    fn every_demo_has_its_own_playback() {
+        let (mut gallery, log) = gallery(2);
+
+        gallery.on_input(key(Key::Char('1')));
+        gallery.on_input(key(Key::Space));
+        gallery.frame(0.5).unwrap();
+        assert_eq!(gallery.title(), "demo1 - Demo gallery (paused at speed x1)");
+
+        gallery.on_input(key(Key::Char('2')));
+        gallery.on_input(key(Key::Char('-')));
+        gallery.frame(0.5).unwrap();
+
+        assert_eq!(
+            take(&log),
+            [
+                "demo1 update 0",
+                "demo1 render",
+                "demo1 FocusLost",
+                "demo2 update 0.25",
+                "demo2 render"
+            ],
+            "playback keys do not reach the demo"
+        );
+    }
+
+    #[test]
+    fn steps_follow_the_demo() {
+        let (mut gallery, log) = gallery(1);
+
+        gallery.on_input(key(Key::Char('1')));
+        gallery.on_input(key(Key::Char('.')));
+        gallery.frame(0.5).unwrap();
+
+        assert_eq!(take(&log), ["demo1 update 0.25", "demo1 render"]);
+    }
+
+    #[test]
+    fn clicking_a_thumbnail_shows_it() {
+        let (mut gallery, _) = gallery(6);
+        gallery.frame(0.).unwrap();
+
+        let click = |x, y| InputEvent::MouseDown {
+            button: MouseButton::Left,
+            x,
+            y,
+        };
+
+        /* Cells are 300 x 300 with a 24 pixel margin. */
+        gallery.on_input(click(10, 10));
+        assert_eq!(gallery.shown, None, "margin");
+
+        gallery.on_input(click(450, 450));
+        assert_eq!(gallery.shown, Some(4));
+    }
+}
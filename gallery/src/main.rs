@@ -0,0 +1,54 @@
+/* Without a window there is nothing to launch; the gallery still builds and
+its tests run. */
+#![cfg_attr(all(not(windows), not(feature = "software-window")), allow(dead_code))]
+
+mod gallery;
+#[cfg(all(not(windows), feature = "software-window"))]
+mod soft_window;
+#[cfg(windows)]
+mod window;
+
+use common::demo::Demo;
+
+const WIDTH: u32 = 1400;
+const HEIGHT: u32 = 600;
+
+fn boxed<D: Demo + 'static>(demo: common::Result<D>) -> common::Result<Box<dyn Demo>> {
+    Ok(Box::new(demo?))
+}
+
+/* Every demo with its default configuration. The clock loads its sprites
+from the working directory like the clock window does, and is left out when
+they are not there. */
+fn demos() -> Vec<Box<dyn Demo>> {
+    [
+        boxed(simple_window::demo::SimpleWindowDemo::new(WIDTH, HEIGHT)),
+        boxed(ferris_follow_mouse::demo::FerrisDemo::new(WIDTH, HEIGHT)),
+        boxed(bouncing_balls::demo::BallsDemo::new(Default::default())),
+        boxed(plot_3d::demo::PlotDemo::new(Default::default())),
+        boxed(monster::demo::MonsterDemo::new(Default::default())),
+        boxed(clock::demo::ClockDemo::new(Default::default())),
+    ]
+    .into_iter()
+    .filter_map(|demo| {
+        demo.map_err(|error| eprintln!("gallery: leaving out a demo: {error}"))
+            .ok()
+    })
+    .collect()
+}
+
+#[cfg(windows)]
+fn main() -> windows::core::Result<()> {
+    window::run(gallery::Gallery::new(demos(), WIDTH, HEIGHT)?)
+}
+
+#[cfg(all(not(windows), feature = "software-window"))]
+fn main() -> common::Result<()> {
+    soft_window::run(gallery::Gallery::new(demos(), WIDTH, HEIGHT)?)
+}
+
+#[cfg(all(not(windows), not(feature = "software-window")))]
+fn main() {
+    eprintln!("gallery needs Windows or the software-window feature to open a window");
+    std::process::exit(2);
+}
@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use common::input::InputEvent;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::Result;
+use softapp::{App, Window, WindowBuilder};
+
+use crate::gallery::Gallery;
+
+struct Launcher {
+    gallery: Gallery,
+    last_frame: Instant,
+    title: String,
+}
+
+impl App for Launcher {
+    fn canvas(&self) -> &SoftwareCanvas {
+        self.gallery.canvas()
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f64();
+        self.last_frame = now;
+
+        self.gallery.frame(dt)?;
+
+        let title = self.gallery.title();
+
+        if title != self.title {
+            window.set_title(&title);
+            self.title = title;
+        }
+
+        Ok(())
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        self.gallery.resize(width, height)
+    }
+
+    fn on_input(&mut self, _window: &Window, event: InputEvent) {
+        self.gallery.on_input(event);
+    }
+}
+
+pub fn run(gallery: Gallery) -> Result<()> {
+    WindowBuilder::new(&gallery.title())
+        .client_size(crate::WIDTH as i32, crate::HEIGHT as i32)
+        .target_fps(60)
+        .run(|_| {
+            Ok(Launcher {
+                title: gallery.title(),
+                gallery,
+                last_frame: Instant::now(),
+            })
+        })
+}
@@ -0,0 +1,103 @@
+use std::time::Instant;
+
+use common::input::InputEvent;
+use common::stats::FrameStats;
+use winapp::{blit, App, Pacing, Window, WindowBuilder};
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::{BeginPaint, EndPaint, GetDC, ReleaseDC, PAINTSTRUCT},
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use crate::gallery::Gallery;
+
+struct Launcher {
+    gallery: Gallery,
+    last_frame: Instant,
+    title: String,
+}
+
+impl Launcher {
+    fn update_title(&mut self, window: &Window) {
+        let title = self.gallery.title();
+
+        if title != self.title {
+            window.set_title(&title);
+            self.title = title;
+        }
+    }
+}
+
+impl App for Launcher {
+    fn on_message(
+        &mut self,
+        window: &Window,
+        message: u32,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            WM_PAINT => {
+                let mut ps = PAINTSTRUCT::default();
+                let hdc = unsafe { BeginPaint(window.handle(), &mut ps) };
+                blit(hdc, self.gallery.canvas());
+
+                unsafe {
+                    EndPaint(window.handle(), &ps);
+                }
+
+                Some(LRESULT(0))
+            }
+            _ => None,
+        }
+    }
+
+    fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f64();
+        self.last_frame = now;
+
+        self.gallery.frame(dt)?;
+        self.update_title(window);
+
+        unsafe {
+            let hdc = GetDC(window.handle());
+            blit(hdc, self.gallery.canvas());
+            ReleaseDC(window.handle(), hdc);
+        }
+
+        Ok(())
+    }
+
+    fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+        /* Nothing to draw into while the window is minimized. */
+        if width > 0 && height > 0 {
+            self.gallery.resize(width, height)?;
+        }
+
+        Ok(())
+    }
+
+    fn on_input(&mut self, _window: &Window, event: InputEvent) {
+        self.gallery.on_input(event);
+    }
+}
+
+pub fn run(gallery: Gallery) -> Result<()> {
+    WindowBuilder::new("Gallery")
+        .title(&gallery.title())
+        .client_size(crate::WIDTH as i32, crate::HEIGHT as i32)
+        .pacing(Pacing::Continuous {
+            target_fps: 60,
+            vsync: false,
+        })
+        .remember_placement()
+        .run(|_| {
+            Ok(Launcher {
+                title: gallery.title(),
+                gallery,
+                last_frame: Instant::now(),
+            })
+        })
+}
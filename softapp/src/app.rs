@@ -0,0 +1,34 @@
+use common::dpi::Dpi;
+use common::input::InputEvent;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::Result;
+
+use crate::Window;
+
+/// Application logic of a single window. Unlike with `winapp` the app owns
+/// its canvas; the window presents it after every frame.
+pub trait App {
+    /// The frame to present.
+    fn canvas(&self) -> &SoftwareCanvas;
+
+    /// Advances and draws one frame into [`App::canvas`]. `stats` covers the
+    /// frames before this one.
+    fn on_frame(&mut self, _window: &Window, _stats: &FrameStats) -> Result<()> {
+        Ok(())
+    }
+
+    /// New client area size, in physical pixels.
+    fn on_resize(&mut self, _window: &Window, _width: u32, _height: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// The window moved to a monitor with a different scale; `on_resize`
+    /// follows with the new size.
+    fn on_dpi_changed(&mut self, _window: &Window, _dpi: Dpi) -> Result<()> {
+        Ok(())
+    }
+
+    /// Keyboard, mouse and focus input.
+    fn on_input(&mut self, _window: &Window, _event: InputEvent) {}
+}
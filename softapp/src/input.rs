@@ -0,0 +1,292 @@
+use common::input::{InputEvent, Key, Modifiers, MouseButton};
+use winit::event::{self, ElementState, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::platform::scancode::PhysicalKeyExtScancode;
+
+/// Turns winit window events into [`InputEvent`]s. Button and key events
+/// carry neither the cursor position nor the modifiers, so both are tracked
+/// from the events in between.
+#[derive(Debug, Default)]
+pub(crate) struct InputState {
+    modifiers: Modifiers,
+    cursor: (i32, i32),
+}
+
+impl InputState {
+    pub(crate) fn decode(&mut self, event: &WindowEvent) -> Option<InputEvent> {
+        let (x, y) = self.cursor;
+
+        let event = match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = (position.x.round() as i32, position.y.round() as i32);
+                let (x, y) = self.cursor;
+                InputEvent::MouseMove { x, y }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = mouse_button(*button)?;
+
+                match state {
+                    ElementState::Pressed => InputEvent::MouseDown { button, x, y },
+                    ElementState::Released => InputEvent::MouseUp { button, x, y },
+                }
+            }
+            /* Only touchpads on Wayland and macOS report pixels; X11 sends
+            lines. Positive x scrolls left, the opposite of Win32. */
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(dx, dy),
+                ..
+            } => match (*dx, *dy) {
+                (_, dy) if dy != 0. => InputEvent::MouseWheel {
+                    delta: dy,
+                    horizontal: false,
+                },
+                (dx, _) if dx != 0. => InputEvent::MouseWheel {
+                    delta: -dx,
+                    horizontal: true,
+                },
+                _ => return None,
+            },
+            WindowEvent::CursorLeft { .. } => InputEvent::MouseLeave,
+            WindowEvent::ModifiersChanged(modifiers) => {
+                let state = modifiers.state();
+
+                self.modifiers = Modifiers {
+                    shift: state.shift_key(),
+                    ctrl: state.control_key(),
+                    alt: state.alt_key(),
+                };
+
+                return None;
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let key = key(event.physical_key);
+                let modifiers = self.modifiers;
+
+                match event.state {
+                    ElementState::Pressed => InputEvent::KeyDown {
+                        key,
+                        modifiers,
+                        repeat: event.repeat,
+                    },
+                    ElementState::Released => InputEvent::KeyUp { key, modifiers },
+                }
+            }
+            WindowEvent::Focused(true) => InputEvent::FocusGained,
+            WindowEvent::Focused(false) => InputEvent::FocusLost,
+            _ => return None,
+        };
+
+        Some(event)
+    }
+}
+
+fn mouse_button(button: event::MouseButton) -> Option<MouseButton> {
+    match button {
+        event::MouseButton::Left => Some(MouseButton::Left),
+        event::MouseButton::Right => Some(MouseButton::Right),
+        event::MouseButton::Middle => Some(MouseButton::Middle),
+        event::MouseButton::Back => Some(MouseButton::Back),
+        event::MouseButton::Forward => Some(MouseButton::Forward),
+        event::MouseButton::Other(_) => None,
+    }
+}
+
+/* By position on the keyboard, like Win32 virtual keys for these. */
+fn key(physical_key: PhysicalKey) -> Key {
+    let PhysicalKey::Code(code) = physical_key else {
+        return Key::Other(physical_key.to_scancode().unwrap_or(0));
+    };
+
+    let letter = |offset: u8| Key::Char(char::from(b'A' + offset));
+    let digit = |offset: u8| Key::Char(char::from(b'0' + offset));
+
+    match code {
+        KeyCode::Escape => Key::Escape,
+        KeyCode::Enter | KeyCode::NumpadEnter => Key::Enter,
+        KeyCode::Space => Key::Space,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::ArrowLeft => Key::Left,
+        KeyCode::ArrowRight => Key::Right,
+        KeyCode::ArrowUp => Key::Up,
+        KeyCode::ArrowDown => Key::Down,
+        KeyCode::F1 => Key::F(1),
+        KeyCode::F2 => Key::F(2),
+        KeyCode::F3 => Key::F(3),
+        KeyCode::F4 => Key::F(4),
+        KeyCode::F5 => Key::F(5),
+        KeyCode::F6 => Key::F(6),
+        KeyCode::F7 => Key::F(7),
+        KeyCode::F8 => Key::F(8),
+        KeyCode::F9 => Key::F(9),
+        KeyCode::F10 => Key::F(10),
+        KeyCode::F11 => Key::F(11),
+        KeyCode::F12 => Key::F(12),
+        KeyCode::KeyA => letter(0),
+        KeyCode::KeyB => letter(1),
+        KeyCode::KeyC => letter(2),
+        KeyCode::KeyD => letter(3),
+        KeyCode::KeyE => letter(4),
+        KeyCode::KeyF => letter(5),
+        KeyCode::KeyG => letter(6),
+        KeyCode::KeyH => letter(7),
+        KeyCode::KeyI => letter(8),
+        KeyCode::KeyJ => letter(9),
+        KeyCode::KeyK => letter(10),
+        KeyCode::KeyL => letter(11),
+        KeyCode::KeyM => letter(12),
+        KeyCode::KeyN => letter(13),
+        KeyCode::KeyO => letter(14),
+        KeyCode::KeyP => letter(15),
+        KeyCode::KeyQ => letter(16),
+        KeyCode::KeyR => letter(17),
+        KeyCode::KeyS => letter(18),
+        KeyCode::KeyT => letter(19),
+        KeyCode::KeyU => letter(20),
+        KeyCode::KeyV => letter(21),
+        KeyCode::KeyW => letter(22),
+        KeyCode::KeyX => letter(23),
+        KeyCode::KeyY => letter(24),
+        KeyCode::KeyZ => letter(25),
+        KeyCode::Digit0 => digit(0),
+        KeyCode::Digit1 => digit(1),
+        KeyCode::Digit2 => digit(2),
+        KeyCode::Digit3 => digit(3),
+        KeyCode::Digit4 => digit(4),
+        KeyCode::Digit5 => digit(5),
+        KeyCode::Digit6 => digit(6),
+        KeyCode::Digit7 => digit(7),
+        KeyCode::Digit8 => digit(8),
+        KeyCode::Digit9 => digit(9),
+        KeyCode::Equal | KeyCode::NumpadAdd => Key::Char('+'),
+        KeyCode::Minus | KeyCode::NumpadSubtract => Key::Char('-'),
+        KeyCode::Period | KeyCode::NumpadDecimal => Key::Char('.'),
+        _ => Key::Other(physical_key.to_scancode().unwrap_or(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::dpi::PhysicalPosition;
+    use winit::event::DeviceId;
+
+    use super::*;
+
+    fn cursor_moved(x: f64, y: f64) -> WindowEvent {
+        WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new(x, y),
+        }
+    }
+
+    fn mouse_input(state: ElementState, button: event::MouseButton) -> WindowEvent {
+        WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state,
+            button,
+        }
+    }
+
+    #[test]
+    fn buttons_report_the_last_cursor_position() {
+        let mut input = InputState::default();
+
+        assert_eq!(
+            input.decode(&cursor_moved(-12.4, 30.6)),
+            Some(InputEvent::MouseMove { x: -12, y: 31 })
+        );
+        assert_eq!(
+            input.decode(&mouse_input(
+                ElementState::Pressed,
+                event::MouseButton::Left
+            )),
+            Some(InputEvent::MouseDown {
+                button: MouseButton::Left,
+                x: -12,
+                y: 31
+            })
+        );
+        assert_eq!(
+            input.decode(&mouse_input(
+                ElementState::Released,
+                event::MouseButton::Back
+            )),
+            Some(InputEvent::MouseUp {
+                button: MouseButton::Back,
+                x: -12,
+                y: 31
+            })
+        );
+        assert_eq!(
+            input.decode(&mouse_input(
+                ElementState::Pressed,
+                event::MouseButton::Other(9)
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn wheel_matches_win32_directions() {
+        let mut input = InputState::default();
+        let mut wheel = |dx, dy| {
+            input.decode(&WindowEvent::MouseWheel {
+                device_id: DeviceId::dummy(),
+                delta: MouseScrollDelta::LineDelta(dx, dy),
+                phase: event::TouchPhase::Moved,
+            })
+        };
+
+        assert_eq!(
+            wheel(0., 1.),
+            Some(InputEvent::MouseWheel {
+                delta: 1.,
+                horizontal: false
+            })
+        );
+        assert_eq!(
+            wheel(-2., 0.),
+            Some(InputEvent::MouseWheel {
+                delta: 2.,
+                horizontal: true
+            })
+        );
+        assert_eq!(wheel(0., 0.), None);
+    }
+
+    #[test]
+    fn focus_and_leave() {
+        let mut input = InputState::default();
+
+        assert_eq!(
+            input.decode(&WindowEvent::Focused(true)),
+            Some(InputEvent::FocusGained)
+        );
+        assert_eq!(
+            input.decode(&WindowEvent::Focused(false)),
+            Some(InputEvent::FocusLost)
+        );
+        assert_eq!(
+            input.decode(&WindowEvent::CursorLeft {
+                device_id: DeviceId::dummy()
+            }),
+            Some(InputEvent::MouseLeave)
+        );
+        assert_eq!(input.decode(&WindowEvent::CloseRequested), None);
+    }
+
+    #[test]
+    fn keys_by_position() {
+        assert_eq!(key(PhysicalKey::Code(KeyCode::F3)), Key::F(3));
+        assert_eq!(key(PhysicalKey::Code(KeyCode::F12)), Key::F(12));
+        assert_eq!(key(PhysicalKey::Code(KeyCode::KeyQ)), Key::Char('Q'));
+        assert_eq!(key(PhysicalKey::Code(KeyCode::Digit7)), Key::Char('7'));
+        assert_eq!(key(PhysicalKey::Code(KeyCode::NumpadAdd)), Key::Char('+'));
+        assert_eq!(key(PhysicalKey::Code(KeyCode::Period)), Key::Char('.'));
+        assert_eq!(key(PhysicalKey::Code(KeyCode::NumpadEnter)), Key::Enter);
+        assert!(matches!(
+            key(PhysicalKey::Code(KeyCode::Semicolon)),
+            Key::Other(_)
+        ));
+    }
+}
@@ -0,0 +1,16 @@
+//! Window, event loop and presentation of the software backend: the demos
+//! render into a [`SoftwareCanvas`] which is copied into a winit window
+//! through softbuffer. It stands in for `winapp` where Win32 is not
+//! available. A demo implements [`App`] and hands it to
+//! [`WindowBuilder::run`].
+//!
+//! [`SoftwareCanvas`]: common::software::SoftwareCanvas
+
+#![cfg(not(windows))]
+
+mod app;
+mod input;
+mod window;
+
+pub use app::App;
+pub use window::{Window, WindowBuilder};
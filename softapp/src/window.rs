@@ -0,0 +1,390 @@
+use std::cell::Cell;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use common::dpi::Dpi;
+use common::software::SoftwareCanvas;
+use common::stats::FrameStats;
+use common::{Error, Result};
+use softbuffer::{Context, Surface};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::platform::x11::EventLoopBuilderExtX11;
+use winit::window::{WindowAttributes, WindowId};
+
+use crate::input::InputState;
+use crate::App;
+
+fn window_error(error: impl std::fmt::Display) -> Error {
+    Error::Window(error.to_string())
+}
+
+/// Handle to a window created by [`WindowBuilder`].
+#[derive(Clone, Debug)]
+pub struct Window {
+    window: Rc<winit::window::Window>,
+    close_requested: Rc<Cell<bool>>,
+}
+
+impl Window {
+    /// Client area size in physical pixels.
+    pub fn client_size(&self) -> (u32, u32) {
+        let size = self.window.inner_size();
+        (size.width, size.height)
+    }
+
+    /// Asks for a client area of `width` x `height` device independent
+    /// pixels; `App::on_resize` reports the size the window manager grants.
+    pub fn set_client_size(&self, width: i32, height: i32) {
+        let _ = self
+            .window
+            .request_inner_size(LogicalSize::new(width.max(1), height.max(1)));
+    }
+
+    pub fn dpi(&self) -> Dpi {
+        let scale = self.window.scale_factor() * Dpi::DEFAULT.0 as f64;
+        Dpi(scale.round() as u32)
+    }
+
+    pub fn set_title(&self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        self.window.is_minimized().unwrap_or(false)
+    }
+
+    /// Leaves the event loop after the current event.
+    pub fn close(&self) {
+        self.close_requested.set(true);
+    }
+}
+
+/// Creates a window and runs its event loop, presenting the canvas of the
+/// app after every frame.
+pub struct WindowBuilder {
+    title: String,
+    client_size: (i32, i32),
+    resizable: bool,
+    target_fps: u32,
+}
+
+impl WindowBuilder {
+    pub fn new(title: &str) -> Self {
+        WindowBuilder {
+            title: title.to_string(),
+            client_size: (800, 600),
+            resizable: true,
+            target_fps: 60,
+        }
+    }
+
+    /// Client area size in device independent pixels.
+    pub fn client_size(mut self, width: i32, height: i32) -> Self {
+        self.client_size = (width, height);
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    /// How often [`App::on_frame`] is called. The loop sleeps in between and
+    /// pauses while the window is minimized or covered.
+    pub fn target_fps(mut self, target_fps: u32) -> Self {
+        self.target_fps = target_fps;
+        self
+    }
+
+    /// Creates the window, builds the app with `create` and runs frames
+    /// until the window is closed or a callback fails. Needs an X server,
+    /// which may be a virtual one such as Xvfb.
+    pub fn run<A, F>(self, create: F) -> Result<()>
+    where
+        A: App,
+        F: FnOnce(&Window) -> Result<A>,
+    {
+        /* Tests run off the main thread. */
+        let event_loop = EventLoop::builder()
+            .with_any_thread(true)
+            .build()
+            .map_err(window_error)?;
+
+        let (width, height) = self.client_size;
+        let attributes = WindowAttributes::default()
+            .with_title(self.title)
+            .with_inner_size(LogicalSize::new(width.max(1), height.max(1)))
+            .with_resizable(self.resizable);
+
+        let mut handler = Handler {
+            attributes,
+            period: Duration::from_secs_f64(1. / self.target_fps.max(1) as f64),
+            create: Some(create),
+            running: None,
+            error: None,
+            stats: FrameStats::new(),
+            last_frame: None,
+            next_frame: Instant::now(),
+            input: InputState::default(),
+            occluded: false,
+        };
+
+        event_loop.run_app(&mut handler).map_err(window_error)?;
+
+        match handler.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+struct Running<A> {
+    window: Window,
+    surface: Surface<Rc<winit::window::Window>, Rc<winit::window::Window>>,
+    app: A,
+}
+
+struct Handler<A, F> {
+    attributes: WindowAttributes,
+    period: Duration,
+    /* Taken when the event loop first resumes. */
+    create: Option<F>,
+    running: Option<Running<A>>,
+    error: Option<Error>,
+    stats: FrameStats,
+    last_frame: Option<Instant>,
+    next_frame: Instant,
+    input: InputState,
+    occluded: bool,
+}
+
+impl<A, F> Handler<A, F>
+where
+    A: App,
+    F: FnOnce(&Window) -> Result<A>,
+{
+    fn start(&mut self, event_loop: &ActiveEventLoop) -> Result<()> {
+        let Some(create) = self.create.take() else {
+            return Ok(());
+        };
+
+        let window = event_loop
+            .create_window(self.attributes.clone())
+            .map_err(window_error)?;
+        let window = Window {
+            window: Rc::new(window),
+            close_requested: Rc::new(Cell::new(false)),
+        };
+
+        let context = Context::new(window.window.clone()).map_err(window_error)?;
+        let surface = Surface::new(&context, window.window.clone()).map_err(window_error)?;
+        let app = create(&window)?;
+
+        self.running = Some(Running {
+            window,
+            surface,
+            app,
+        });
+
+        Ok(())
+    }
+
+    fn frame(&mut self) -> Result<()> {
+        let Some(running) = self.running.as_mut() else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+
+        if let Some(last_frame) = self.last_frame.replace(now) {
+            self.stats.record(now - last_frame);
+        }
+
+        running.app.on_frame(&running.window, &self.stats)?;
+        present(
+            &mut running.surface,
+            running.window.client_size(),
+            running.app.canvas(),
+        )
+    }
+
+    fn handle(&mut self, event: &WindowEvent) -> Result<()> {
+        let Some(running) = self.running.as_mut() else {
+            return Ok(());
+        };
+
+        let (window, app) = (&running.window, &mut running.app);
+
+        if let Some(input) = self.input.decode(event) {
+            app.on_input(window, input);
+            return Ok(());
+        }
+
+        match event {
+            WindowEvent::CloseRequested => window.close(),
+            /* Nothing to draw into while minimized. */
+            WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+                app.on_resize(window, size.width, size.height)?
+            }
+            WindowEvent::ScaleFactorChanged { .. } => app.on_dpi_changed(window, window.dpi())?,
+            WindowEvent::Occluded(occluded) => self.occluded = *occluded,
+            WindowEvent::RedrawRequested => self.frame()?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /* Remembers the first error and leaves the event loop. */
+    fn check(&mut self, event_loop: &ActiveEventLoop, result: Result<()>) {
+        if let Err(error) = result {
+            self.error.get_or_insert(error);
+            event_loop.exit();
+        }
+    }
+}
+
+impl<A, F> ApplicationHandler for Handler<A, F>
+where
+    A: App,
+    F: FnOnce(&Window) -> Result<A>,
+{
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let result = self.start(event_loop);
+        self.check(event_loop, result);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        let result = self.handle(&event);
+        self.check(event_loop, result);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(running) = self.running.as_ref() else {
+            return;
+        };
+
+        if running.window.close_requested.get() {
+            event_loop.exit();
+            return;
+        }
+
+        /* The time spent paused is not a frame time. */
+        if self.occluded || running.window.is_minimized() {
+            self.last_frame = None;
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+
+        let now = Instant::now();
+
+        if now >= self.next_frame {
+            running.window.window.request_redraw();
+            /* Skip frames that were missed rather than bunching them up. */
+            self.next_frame = (self.next_frame + self.period).max(now);
+        }
+
+        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
+    }
+}
+
+/* Copies the canvas into the window, cropped or padded with black while the
+two sizes disagree during a resize. The frames are opaque, so premultiplied
+channels are the colors themselves. */
+fn present(
+    surface: &mut Surface<Rc<winit::window::Window>, Rc<winit::window::Window>>,
+    (width, height): (u32, u32),
+    canvas: &SoftwareCanvas,
+) -> Result<()> {
+    let (Some(nonzero_width), Some(nonzero_height)) =
+        (NonZeroU32::new(width), NonZeroU32::new(height))
+    else {
+        return Ok(());
+    };
+
+    surface
+        .resize(nonzero_width, nonzero_height)
+        .map_err(window_error)?;
+    let mut buffer = surface.buffer_mut().map_err(window_error)?;
+    buffer.fill(0);
+
+    let pixmap = canvas.pixmap();
+    let columns = width.min(pixmap.width()) as usize;
+
+    for (row, source) in pixmap
+        .pixels()
+        .chunks_exact(pixmap.width() as usize)
+        .take(height as usize)
+        .enumerate()
+    {
+        let start = row * width as usize;
+
+        for (target, pixel) in buffer[start..start + columns].iter_mut().zip(source) {
+            *target =
+                (pixel.red() as u32) << 16 | (pixel.green() as u32) << 8 | pixel.blue() as u32;
+        }
+    }
+
+    buffer.present().map_err(window_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::canvas::{Canvas, Color};
+
+    use super::*;
+
+    struct Frames {
+        canvas: SoftwareCanvas,
+        frames: Rc<Cell<u32>>,
+    }
+
+    impl App for Frames {
+        fn canvas(&self) -> &SoftwareCanvas {
+            &self.canvas
+        }
+
+        fn on_frame(&mut self, window: &Window, _stats: &FrameStats) -> Result<()> {
+            self.canvas.begin_draw();
+            self.canvas.clear(Color::rgb(0.7, 0.7, 1.));
+            self.canvas.end_draw()?;
+
+            self.frames.set(self.frames.get() + 1);
+
+            if self.frames.get() == 10 {
+                window.close();
+            }
+
+            Ok(())
+        }
+
+        fn on_resize(&mut self, _window: &Window, width: u32, height: u32) -> Result<()> {
+            self.canvas.resize(width, height)
+        }
+    }
+
+    #[test]
+    #[ignore = "needs an X server: xvfb-run cargo test -p softapp -- --ignored"]
+    fn runs_frames_until_closed() {
+        let frames = Rc::new(Cell::new(0));
+
+        WindowBuilder::new("softapp test")
+            .client_size(320, 240)
+            .target_fps(120)
+            .run(|window| {
+                let (width, height) = window.client_size();
+
+                Ok(Frames {
+                    canvas: SoftwareCanvas::new(width, height)?,
+                    frames: frames.clone(),
+                })
+            })
+            .unwrap();
+
+        assert_eq!(frames.get(), 10);
+    }
+}
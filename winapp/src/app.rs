@@ -0,0 +1,59 @@
+use common::dpi::Dpi;
+use common::input::InputEvent;
+use common::stats::FrameStats;
+use windows::core::Result;
+use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+use windows::Win32::Graphics::Gdi::HDC;
+
+use crate::Window;
+
+/// Application logic of a single window. Every callback has a default that
+/// does nothing, so an app only implements what it reacts to.
+pub trait App {
+    /// Sees every message first. Returning `Some` skips the built-in
+    /// handling below and `DefWindowProcW`.
+    fn on_message(
+        &mut self,
+        _window: &Window,
+        _message: u32,
+        _wparam: WPARAM,
+        _lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        None
+    }
+
+    /// Advances and draws one frame; how often depends on the [`Pacing`].
+    /// `stats` covers the frames before this one.
+    ///
+    /// [`Pacing`]: crate::Pacing
+    fn on_frame(&mut self, _window: &Window, _stats: &FrameStats) -> Result<()> {
+        Ok(())
+    }
+
+    /// Draws the invalid part of the window on `WM_PAINT`, into `hdc` for
+    /// GDI or on a canvas of its own. An error ends the message loop like one
+    /// of `on_frame`.
+    fn on_paint(&mut self, _window: &Window, _hdc: HDC) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether the window is covered, so that drawing would be wasted.
+    fn is_occluded(&self) -> bool {
+        false
+    }
+
+    /// New client area size, in physical pixels.
+    fn on_resize(&mut self, _window: &Window, _width: u32, _height: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// The window moved to a monitor with a different scale. It is resized
+    /// to the suggested rectangle right after, which `on_resize` reports.
+    fn on_dpi_changed(&mut self, _window: &Window, _dpi: Dpi) -> Result<()> {
+        Ok(())
+    }
+
+    /// Keyboard, mouse and focus input. The message still gets default
+    /// handling afterwards, so that e.g. Alt+F4 keeps working.
+    fn on_input(&mut self, _window: &Window, _event: InputEvent) {}
+}
@@ -0,0 +1,432 @@
+use std::cell::{Cell, RefCell};
+
+use common::confirm::{Answer, Confirm, Control, Navigation, Reaction};
+use common::dpi::Dpi;
+use common::input::{self, InputEvent};
+use common::settings::Settings;
+
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::*,
+    Win32::System::LibraryLoader::GetModuleHandleW,
+    Win32::UI::HiDpi::{AdjustWindowRectExForDpi, SystemParametersInfoForDpi},
+    Win32::UI::Input::KeyboardAndMouse::{EnableWindow, GetFocus, SetFocus},
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use crate::window::{modifiers, Window};
+
+const CLASS_NAME: &str = "winapp confirm";
+
+/* Control IDs: 0 is the text, the buttons count from 1 and the checkbox
+comes after them. */
+const CHECKBOX_ID: usize = 100;
+
+/* BST_CHECKED from Win32_UI_Controls and SS_NOPREFIX from
+Win32_System_SystemServices, without pulling those in. */
+const BST_CHECKED: usize = 1;
+const SS_NOPREFIX: u32 = 0x80;
+
+/* Layout in DIPs, after the spacing of the Windows message box. */
+const MARGIN: i32 = 12;
+const TEXT_WIDTH: i32 = 320;
+const BUTTON_WIDTH: i32 = 88;
+const BUTTON_HEIGHT: i32 = 26;
+const BUTTON_GAP: i32 = 8;
+const CHECKBOX_HEIGHT: i32 = 20;
+
+/// Asks `confirm` in a modal dialog over `owner`: the owner is disabled
+/// until the dialog is answered, while its messages keep flowing. An answer
+/// given before with "Don't ask again" checked comes back without asking.
+/// Fails for a `confirm` that does not [`validate`](Confirm::validate).
+pub fn confirm(owner: &Window, confirm: &Confirm) -> Result<Answer> {
+    confirm.validate()?;
+
+    if let Some(answer) = confirm.remembered(&Settings::load()) {
+        return Ok(answer);
+    }
+
+    let dialog = unsafe { Dialog::create(owner, confirm)? };
+    let answer = unsafe { dialog.run(owner) };
+    dialog.destroy();
+
+    let answer = answer?;
+
+    if dialog.navigation.borrow().is_checked() {
+        let saved = Settings::update(|settings| confirm.remember(settings, answer));
+
+        /* Asking again next time is no reason to fail this one. */
+        if let Err(error) = saved {
+            eprintln!("cannot remember the answer: {error}");
+        }
+    }
+
+    Ok(answer)
+}
+
+/* Lives on the stack of `confirm`, behind GWLP_USERDATA of the dialog. */
+struct Dialog {
+    handle: HWND,
+    font: HFONT,
+    buttons: Vec<HWND>,
+    checkbox: Option<HWND>,
+    navigation: RefCell<Navigation>,
+    answer: Cell<Option<Answer>>,
+}
+
+impl Dialog {
+    unsafe fn create(owner: &Window, confirm: &Confirm) -> Result<Box<Dialog>> {
+        let instance = GetModuleHandleW(None)?;
+        register_class(instance)?;
+
+        let dpi = owner.dpi();
+        let px = |dips: i32| dpi.to_pixels_ceil(dips);
+        let font = message_font(dpi);
+
+        let text_height = text_height(&confirm.text, font, px(TEXT_WIDTH));
+        let buttons = confirm.buttons.len() as i32;
+        let buttons_width = buttons * px(BUTTON_WIDTH) + (buttons - 1) * px(BUTTON_GAP);
+        let width = px(TEXT_WIDTH).max(buttons_width) + 2 * px(MARGIN);
+
+        let checkbox_top = px(MARGIN) + text_height + px(MARGIN);
+        let buttons_top = match confirm.dont_ask_again {
+            Some(_) => checkbox_top + px(CHECKBOX_HEIGHT) + px(MARGIN),
+            None => checkbox_top,
+        };
+        let height = buttons_top + px(BUTTON_HEIGHT) + px(MARGIN);
+
+        let style = WS_POPUP | WS_CAPTION | WS_SYSMENU;
+        let ex_style = WS_EX_DLGMODALFRAME;
+
+        let mut frame = RECT {
+            left: 0,
+            top: 0,
+            right: width,
+            bottom: height,
+        };
+        AdjustWindowRectExForDpi(&mut frame, style, false, ex_style, dpi.0).ok()?;
+
+        /* Centered over the owner. */
+        let mut over = RECT::default();
+        GetWindowRect(owner.handle(), &mut over).ok()?;
+        let (frame_width, frame_height) = (frame.right - frame.left, frame.bottom - frame.top);
+
+        let handle = CreateWindowExW(
+            ex_style,
+            &HSTRING::from(CLASS_NAME),
+            &HSTRING::from(confirm.title.as_str()),
+            style,
+            (over.left + over.right - frame_width) / 2,
+            (over.top + over.bottom - frame_height) / 2,
+            frame_width,
+            frame_height,
+            owner.handle(),
+            None,
+            instance,
+            None,
+        );
+
+        if handle.0 == 0 {
+            DeleteObject(font);
+            return Err(Error::from_win32());
+        }
+
+        let child = |class: &HSTRING, text: &str, style: u32, id: usize, rect: RECT| {
+            let child = CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                class,
+                &HSTRING::from(text),
+                WS_CHILD | WS_VISIBLE | WINDOW_STYLE(style),
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                handle,
+                HMENU(id as isize),
+                instance,
+                None,
+            );
+            SendMessageW(child, WM_SETFONT, WPARAM(font.0 as usize), LPARAM(1));
+            child
+        };
+
+        let rect = |left: i32, top: i32, width: i32, height: i32| RECT {
+            left,
+            top,
+            right: left + width,
+            bottom: top + height,
+        };
+
+        child(
+            w!("STATIC"),
+            &confirm.text,
+            SS_NOPREFIX,
+            0,
+            rect(px(MARGIN), px(MARGIN), px(TEXT_WIDTH), text_height),
+        );
+
+        let checkbox = confirm.dont_ask_again.as_ref().map(|_| {
+            child(
+                w!("BUTTON"),
+                "Don't ask again",
+                BS_AUTOCHECKBOX as u32 | WS_TABSTOP.0,
+                CHECKBOX_ID,
+                rect(
+                    px(MARGIN),
+                    checkbox_top,
+                    px(TEXT_WIDTH),
+                    px(CHECKBOX_HEIGHT),
+                ),
+            )
+        });
+
+        /* Right aligned, like the buttons of a message box. */
+        let left = width - px(MARGIN) - buttons_width;
+        let buttons = confirm
+            .buttons
+            .iter()
+            .enumerate()
+            .map(|(index, button)| {
+                child(
+                    w!("BUTTON"),
+                    &button.label,
+                    BS_PUSHBUTTON as u32 | WS_TABSTOP.0,
+                    index + 1,
+                    rect(
+                        left + index as i32 * (px(BUTTON_WIDTH) + px(BUTTON_GAP)),
+                        buttons_top,
+                        px(BUTTON_WIDTH),
+                        px(BUTTON_HEIGHT),
+                    ),
+                )
+            })
+            .collect();
+
+        let dialog = Box::new(Dialog {
+            handle,
+            font,
+            buttons,
+            checkbox,
+            navigation: RefCell::new(Navigation::new(confirm)),
+            answer: Cell::new(None),
+        });
+
+        SetWindowLongPtrW(handle, GWLP_USERDATA, &*dialog as *const _ as isize);
+        Ok(dialog)
+    }
+
+    /* A message loop of its own until the dialog is answered, like
+    `MessageBoxW` runs. Key presses are taken before the focused control
+    sees them. */
+    unsafe fn run(&self, owner: &Window) -> Result<Answer> {
+        EnableWindow(owner.handle(), false);
+        ShowWindow(self.handle, SW_SHOW);
+        self.show_navigation();
+
+        let mut message = MSG::default();
+
+        let result = loop {
+            if let Some(answer) = self.answer.get() {
+                break Ok(answer);
+            }
+
+            match GetMessageW(&mut message, None, 0, 0) {
+                BOOL(0) => {
+                    /* The quit belongs to the loop of the owner. */
+                    PostQuitMessage(message.wParam.0 as i32);
+                    break Ok(Answer::Cancel);
+                }
+                BOOL(-1) => break Err(Error::from_win32()),
+                BOOL(_) => {}
+            }
+
+            if !self.on_key(&message) {
+                TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+        };
+
+        /* Enabled before the dialog goes, so that the owner is activated
+        again rather than some other application. */
+        EnableWindow(owner.handle(), true);
+        result
+    }
+
+    fn destroy(&self) {
+        unsafe {
+            SetWindowLongPtrW(self.handle, GWLP_USERDATA, 0);
+            DestroyWindow(self.handle);
+            DeleteObject(self.font);
+        }
+    }
+
+    /* Whether the message was a key of the dialog, now handled. */
+    unsafe fn on_key(&self, message: &MSG) -> bool {
+        if message.message != WM_KEYDOWN
+            || message.hwnd != self.handle && !IsChild(self.handle, message.hwnd).as_bool()
+        {
+            return false;
+        }
+
+        let event = input::decode_win32(
+            message.message,
+            message.wParam.0,
+            message.lParam.0,
+            modifiers(),
+        );
+        let Some(InputEvent::KeyDown { key, modifiers, .. }) = event else {
+            return false;
+        };
+
+        /* The mouse may have moved the focus in the meantime. */
+        if let Some(focus) = self.control(GetFocus()) {
+            self.navigation.borrow_mut().set_focus(focus);
+        }
+
+        let reaction = self.navigation.borrow_mut().on_key(key, modifiers);
+
+        match reaction {
+            Reaction::Ignored => return false,
+            Reaction::Changed => self.show_navigation(),
+            Reaction::Answered(answer) => self.answer.set(Some(answer)),
+        }
+
+        true
+    }
+
+    fn on_clicked(&self, id: usize) {
+        let mut navigation = self.navigation.borrow_mut();
+
+        match (id, self.checkbox) {
+            /* An automatic checkbox has already toggled itself. */
+            (CHECKBOX_ID, Some(checkbox)) => {
+                let state = unsafe { SendMessageW(checkbox, BM_GETCHECK, WPARAM(0), LPARAM(0)) };
+                navigation.set_checked(state.0 as usize == BST_CHECKED);
+                navigation.set_focus(Control::Checkbox);
+            }
+            (id, _) if (1..=self.buttons.len()).contains(&id) => {
+                self.answer.set(navigation.press(Control::Button(id - 1)));
+                return;
+            }
+            _ => return,
+        }
+
+        drop(navigation);
+        self.show_navigation();
+    }
+
+    fn control(&self, handle: HWND) -> Option<Control> {
+        match self.buttons.iter().position(|&button| button == handle) {
+            Some(index) => Some(Control::Button(index)),
+            None => (self.checkbox == Some(handle)).then_some(Control::Checkbox),
+        }
+    }
+
+    /* Moves the focus, the check mark and the default button frame to where
+    the navigation has them. */
+    fn show_navigation(&self) {
+        let navigation = self.navigation.borrow();
+
+        unsafe {
+            let focus = match navigation.focus() {
+                Control::Button(index) => self.buttons[index],
+                Control::Checkbox => self.checkbox.unwrap_or(self.handle),
+            };
+            SetFocus(focus);
+
+            if let Some(checkbox) = self.checkbox {
+                let state = WPARAM(navigation.is_checked() as usize * BST_CHECKED);
+                SendMessageW(checkbox, BM_SETCHECK, state, LPARAM(0));
+            }
+
+            for (index, &button) in self.buttons.iter().enumerate() {
+                let style = match index == navigation.default_button() {
+                    true => BS_DEFPUSHBUTTON,
+                    false => BS_PUSHBUTTON,
+                };
+                SendMessageW(button, BM_SETSTYLE, WPARAM(style as usize), LPARAM(1));
+            }
+        }
+    }
+}
+
+unsafe fn register_class(instance: HINSTANCE) -> Result<()> {
+    let class_name = HSTRING::from(CLASS_NAME);
+    let wc = WNDCLASSW {
+        hCursor: LoadCursorW(None, IDC_ARROW)?,
+        hInstance: instance,
+        hbrBackground: GetSysColorBrush(COLOR_BTNFACE),
+        lpszClassName: (&class_name).into(),
+        lpfnWndProc: Some(wndproc),
+        ..Default::default()
+    };
+
+    match RegisterClassW(&wc) {
+        0 if GetLastError() != ERROR_CLASS_ALREADY_EXISTS => Err(Error::from_win32()),
+        _ => Ok(()),
+    }
+}
+
+/* The font of message boxes, in the size for `dpi`. */
+unsafe fn message_font(dpi: Dpi) -> HFONT {
+    let mut metrics = NONCLIENTMETRICSW {
+        cbSize: std::mem::size_of::<NONCLIENTMETRICSW>() as u32,
+        ..Default::default()
+    };
+
+    SystemParametersInfoForDpi(
+        SPI_GETNONCLIENTMETRICS.0,
+        metrics.cbSize,
+        Some(&mut metrics as *mut _ as _),
+        0,
+        dpi.0,
+    );
+
+    CreateFontIndirectW(&metrics.lfMessageFont)
+}
+
+/* Height of `text` wrapped to `width` pixels. */
+unsafe fn text_height(text: &str, font: HFONT, width: i32) -> i32 {
+    let mut text: Vec<u16> = text.encode_utf16().collect();
+    let mut rect = RECT {
+        right: width,
+        ..Default::default()
+    };
+
+    let hdc = GetDC(None);
+    let previous = SelectObject(hdc, font);
+    DrawTextW(
+        hdc,
+        &mut text,
+        &mut rect,
+        DT_CALCRECT | DT_WORDBREAK | DT_NOPREFIX,
+    );
+    SelectObject(hdc, previous);
+    ReleaseDC(None, hdc);
+
+    rect.bottom
+}
+
+extern "system" fn wndproc(window: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe {
+        let dialog = GetWindowLongPtrW(window, GWLP_USERDATA) as *const Dialog;
+
+        if let Some(dialog) = dialog.as_ref() {
+            match message {
+                WM_COMMAND if (wparam.0 >> 16) as u32 & 0xFFFF == BN_CLICKED => {
+                    dialog.on_clicked(wparam.0 & 0xFFFF);
+                    return LRESULT(0);
+                }
+                /* The close box and Alt+F4 cancel rather than destroy. */
+                WM_CLOSE => {
+                    dialog.answer.set(Some(Answer::Cancel));
+                    return LRESULT(0);
+                }
+                _ => {}
+            }
+        }
+
+        DefWindowProcW(window, message, wparam, lparam)
+    }
+}
@@ -0,0 +1,41 @@
+use common::software::SoftwareCanvas;
+use windows::Win32::Graphics::Gdi::{
+    SetDIBitsToDevice, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HDC,
+};
+
+/// Copies a software-rendered frame onto the device context, at the top
+/// left corner.
+pub fn blit(hdc: HDC, canvas: &SoftwareCanvas) {
+    let (width, height) = (canvas.pixmap().width(), canvas.pixmap().height());
+    let pixels = canvas.to_bgra();
+
+    let info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            biHeight: -(height as i32), /* negative height means top-down rows */
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    unsafe {
+        SetDIBitsToDevice(
+            hdc,
+            0,
+            0,
+            width,
+            height,
+            0,
+            0,
+            0,
+            height,
+            pixels.as_ptr() as _,
+            &info,
+            DIB_RGB_COLORS,
+        );
+    }
+}
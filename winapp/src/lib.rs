@@ -0,0 +1,20 @@
+//! Window class registration, the window procedure trampoline and the
+//! message loop shared by the demos. A demo implements [`App`] and hands it
+//! to [`WindowBuilder::run`].
+
+#![cfg(windows)]
+
+mod app;
+mod confirm;
+mod gdi;
+mod menu;
+mod placement;
+mod tray;
+mod window;
+
+pub use app::App;
+pub use confirm::confirm;
+pub use gdi::blit;
+pub use menu::MenuBar;
+pub use tray::TrayIcon;
+pub use window::{Pacing, Window, WindowBuilder};
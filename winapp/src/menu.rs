@@ -0,0 +1,172 @@
+use common::menu::{Item, Menu};
+
+use windows::{core::*, Win32::UI::WindowsAndMessaging::*};
+
+use crate::{tray, Window};
+
+type Handler = Box<dyn FnMut(&Window) -> Result<()>>;
+
+/// A [`Menu`] with what its commands do, for
+/// [`WindowBuilder::menu`](crate::WindowBuilder::menu). The window
+/// translates the accelerators in its message loop and routes `WM_COMMAND`
+/// of menu items and accelerators to the handler of the command, after
+/// [`App::on_message`](crate::App::on_message) has passed on it.
+pub struct MenuBar {
+    menu: Menu,
+    handlers: Vec<(u16, Handler)>,
+}
+
+impl MenuBar {
+    pub fn new(menu: Menu) -> Self {
+        MenuBar {
+            menu,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Runs `handler` for the command `id`. The app is not borrowed
+    /// meanwhile, so the handler may open a modal dialog.
+    pub fn on<F>(mut self, id: u16, handler: F) -> Self
+    where
+        F: FnMut(&Window) -> Result<()> + 'static,
+    {
+        self.handlers.push((id, Box::new(handler)));
+        self
+    }
+
+    /* The menu bar belongs to the window once set, and is destroyed with
+    it; the accelerator table is the caller's. */
+    pub(crate) fn build(&self) -> Result<(HMENU, HACCEL)> {
+        self.menu.validate()?;
+
+        unsafe {
+            let bar = CreateMenu()?;
+
+            if let Err(error) = append(bar, &self.menu.items) {
+                DestroyMenu(bar);
+                return Err(error);
+            }
+
+            let accelerators = self
+                .menu
+                .accelerators()
+                .into_iter()
+                .map(|(accelerator, id)| {
+                    let mut flags = FVIRTKEY;
+
+                    for (held, flag) in [
+                        (accelerator.modifiers.ctrl, FCONTROL),
+                        (accelerator.modifiers.shift, FSHIFT),
+                        (accelerator.modifiers.alt, FALT),
+                    ] {
+                        if held {
+                            flags |= flag;
+                        }
+                    }
+
+                    ACCEL {
+                        fVirt: flags,
+                        key: accelerator.virtual_key().unwrap_or_default() as u16,
+                        cmd: id,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let table = match accelerators.is_empty() {
+                true => HACCEL(0),
+                false => CreateAcceleratorTableW(&accelerators)?,
+            };
+
+            Ok((bar, table))
+        }
+    }
+
+    /* The same items as a context menu. */
+    pub(crate) fn popup(&self) -> Result<HMENU> {
+        self.menu.validate()?;
+
+        unsafe {
+            let popup = CreatePopupMenu()?;
+
+            if let Err(error) = append(popup, &self.menu.items) {
+                DestroyMenu(popup);
+                return Err(error);
+            }
+
+            Ok(popup)
+        }
+    }
+
+    /* Whether `id` is one of the commands; None when it is not. */
+    pub(crate) fn dispatch(&mut self, window: &Window, id: u16) -> Option<Result<()>> {
+        let (_, handler) = self
+            .handlers
+            .iter_mut()
+            .find(|(command, _)| *command == id)?;
+
+        Some(handler(window))
+    }
+}
+
+unsafe fn append(menu: HMENU, items: &[Item]) -> Result<()> {
+    for item in items {
+        let appended = match item {
+            Item::Command(command) => {
+                let flags = match command.checked {
+                    true => MF_STRING | MF_CHECKED,
+                    false => MF_STRING,
+                };
+                let text = HSTRING::from(command.text());
+
+                AppendMenuW(menu, flags, command.id as usize, &text)
+            }
+            Item::Separator => AppendMenuW(menu, MF_SEPARATOR, 0, PCWSTR::null()),
+            Item::Submenu { label, items } => {
+                let submenu = CreatePopupMenu()?;
+
+                /* Until appended, the submenu is not destroyed with the
+                menu. */
+                if let Err(error) = append(submenu, items) {
+                    DestroyMenu(submenu);
+                    return Err(error);
+                }
+
+                AppendMenuW(menu, MF_POPUP, submenu.0 as usize, &HSTRING::from(label))
+            }
+        };
+
+        appended.ok()?;
+    }
+
+    Ok(())
+}
+
+impl Window {
+    /* The menu bar and the context menu of the tray icon. */
+    fn menus(&self) -> impl Iterator<Item = HMENU> {
+        let menus = unsafe { [GetMenu(self.handle()), tray::menu(self.handle())] };
+        menus.into_iter().filter(|menu| !menu.is_invalid())
+    }
+
+    /// Puts a check mark next to the menu command `id`, or takes it away,
+    /// in the menu bar and the tray icon menu alike.
+    pub fn set_menu_checked(&self, id: u16, checked: bool) {
+        let check = match checked {
+            true => MF_CHECKED,
+            false => MF_UNCHECKED,
+        };
+
+        for menu in self.menus() {
+            unsafe {
+                CheckMenuItem(menu, id as u32, (MF_BYCOMMAND | check).0);
+            }
+        }
+    }
+
+    pub fn is_menu_checked(&self, id: u16) -> bool {
+        self.menus().any(|menu| {
+            let state = unsafe { GetMenuState(menu, id as u32, MF_BYCOMMAND) };
+            state != u32::MAX && MENU_ITEM_FLAGS(state) & MF_CHECKED == MF_CHECKED
+        })
+    }
+}
@@ -0,0 +1,148 @@
+use common::placement::{Bounds, Monitor, Placement};
+use common::settings::Settings;
+use windows::{
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, MonitorFromWindow, HDC, HMONITOR, MONITORINFO,
+        MONITORINFOEXW, MONITOR_DEFAULTTONEAREST,
+    },
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+/// Restores the placement saved under `key`, if any, moved back on screen
+/// when its monitor is gone. Returns whether there was one.
+pub(crate) fn restore(window: HWND, key: &str, visible: bool) -> bool {
+    let Some(saved) = Settings::load().windows.remove(key) else {
+        return false;
+    };
+
+    let placement = saved.clamp(&monitors());
+
+    let show = match (visible, placement.maximized) {
+        (false, _) => SW_HIDE,
+        (true, true) => SW_SHOWMAXIMIZED,
+        (true, false) => SW_SHOWNORMAL,
+    };
+
+    let placement = WINDOWPLACEMENT {
+        length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+        showCmd: show,
+        rcNormalPosition: rect(placement.bounds),
+        ..Default::default()
+    };
+
+    unsafe { SetWindowPlacement(window, &placement).as_bool() }
+}
+
+/// Saves where `window` is under `key`, for [`restore`] on the next run.
+pub(crate) fn save(window: HWND, key: &str) -> common::Result<()> {
+    let mut placement = WINDOWPLACEMENT {
+        length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+        ..Default::default()
+    };
+
+    unsafe { GetWindowPlacement(window, &mut placement).ok()? };
+
+    /* A minimized window remembers whether it goes back to maximized. */
+    let maximized = placement.showCmd == SW_SHOWMAXIMIZED
+        || placement.showCmd == SW_SHOWMINIMIZED
+            && (placement.flags & WPF_RESTORETOMAXIMIZED) == WPF_RESTORETOMAXIMIZED;
+
+    let monitor = unsafe { MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST) };
+    let saved = Placement {
+        bounds: bounds(placement.rcNormalPosition),
+        maximized,
+        monitor: monitor_info(monitor).map(|info| device_name(&info)),
+    };
+
+    Settings::update(|settings| {
+        settings.windows.insert(key.to_owned(), saved);
+    })
+}
+
+/* Work areas in workspace coordinates, which `WINDOWPLACEMENT` uses: their
+origin is the top left corner of the work area of the primary monitor, not
+of the monitor itself. */
+fn monitors() -> Vec<Monitor> {
+    let mut infos = Vec::<MONITORINFOEXW>::new();
+
+    unsafe extern "system" fn collect(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        infos: LPARAM,
+    ) -> BOOL {
+        let infos = &mut *(infos.0 as *mut Vec<MONITORINFOEXW>);
+        infos.extend(monitor_info(monitor));
+        true.into()
+    }
+
+    unsafe {
+        EnumDisplayMonitors(
+            HDC(0),
+            None,
+            Some(collect),
+            LPARAM(&mut infos as *mut _ as isize),
+        );
+    }
+
+    let (dx, dy) = infos
+        .iter()
+        .map(|info| &info.monitorInfo)
+        .find(|info| info.dwFlags & MONITORINFOF_PRIMARY != 0)
+        .map_or((0, 0), |info| {
+            (
+                info.rcWork.left - info.rcMonitor.left,
+                info.rcWork.top - info.rcMonitor.top,
+            )
+        });
+
+    infos
+        .iter()
+        .map(|info| {
+            let work = bounds(info.monitorInfo.rcWork);
+
+            Monitor {
+                name: device_name(info),
+                work_area: Bounds {
+                    left: work.left - dx,
+                    top: work.top - dy,
+                    right: work.right - dx,
+                    bottom: work.bottom - dy,
+                },
+            }
+        })
+        .collect()
+}
+
+fn monitor_info(monitor: HMONITOR) -> Option<MONITORINFOEXW> {
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+
+    let found =
+        unsafe { GetMonitorInfoW(monitor, &mut info as *mut _ as *mut MONITORINFO).as_bool() };
+    found.then_some(info)
+}
+
+fn device_name(info: &MONITORINFOEXW) -> String {
+    let length = info.szDevice.iter().position(|&c| c == 0);
+    String::from_utf16_lossy(&info.szDevice[..length.unwrap_or(info.szDevice.len())])
+}
+
+fn bounds(rect: RECT) -> Bounds {
+    Bounds {
+        left: rect.left,
+        top: rect.top,
+        right: rect.right,
+        bottom: rect.bottom,
+    }
+}
+
+fn rect(bounds: Bounds) -> RECT {
+    RECT {
+        left: bounds.left,
+        top: bounds.top,
+        right: bounds.right,
+        bottom: bounds.bottom,
+    }
+}
@@ -0,0 +1,174 @@
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::UI::Shell::{
+        Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIF_MESSAGE, NIF_SHOWTIP, NIF_TIP, NIIF_INFO,
+        NIM_ADD, NIM_DELETE, NIM_MODIFY, NIM_SETVERSION, NOTIFYICONDATAW, NOTIFYICON_VERSION_4,
+    },
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use crate::{MenuBar, Window};
+
+/* One icon per window, so the window handle alone identifies it. */
+const ICON_ID: u32 = 1;
+
+/// How the notification area reports clicks on the icon to its window. The
+/// first private message; the demos post none of their own.
+pub(crate) const TRAY_MESSAGE: u32 = WM_APP;
+
+/* The context menu hangs off the window under this property, so that
+`Window` can check its items without holding any state. */
+const MENU_PROPERTY: &str = "winapp tray menu";
+
+/// An icon in the notification area for a window, with a context menu on
+/// right click; a double click shows and activates the window. For
+/// [`WindowBuilder::tray`](crate::WindowBuilder::tray).
+///
+/// The menu items send `WM_COMMAND` like those of a menu bar and run the
+/// handlers of `menu`; its accelerators are not translated.
+pub struct TrayIcon {
+    tip: String,
+    icon: PCWSTR,
+    menu: MenuBar,
+}
+
+impl TrayIcon {
+    /// `tip` shows when the mouse rests on the icon.
+    pub fn new(tip: &str, menu: MenuBar) -> Self {
+        TrayIcon {
+            tip: tip.to_owned(),
+            icon: IDI_APPLICATION,
+            menu,
+        }
+    }
+
+    /// One of the predefined `IDI_*` icons.
+    pub fn icon(mut self, icon: PCWSTR) -> Self {
+        self.icon = icon;
+        self
+    }
+
+    pub(crate) fn menu_mut(&mut self) -> &mut MenuBar {
+        &mut self.menu
+    }
+
+    /* Also after Explorer restarts, which forgets all icons. */
+    pub(crate) fn add(&self, window: &Window) -> Result<()> {
+        unsafe {
+            if menu(window.handle()).is_invalid() {
+                let popup = self.menu.popup()?;
+                SetPropW(
+                    window.handle(),
+                    &HSTRING::from(MENU_PROPERTY),
+                    HANDLE(popup.0),
+                );
+            }
+
+            let mut data = data(window);
+            data.uFlags = NIF_MESSAGE | NIF_ICON | NIF_TIP | NIF_SHOWTIP;
+            data.uCallbackMessage = TRAY_MESSAGE;
+            data.hIcon = LoadIconW(None, self.icon)?;
+            copy(&self.tip, &mut data.szTip);
+            Shell_NotifyIconW(NIM_ADD, &data).ok()?;
+
+            /* Version 4 reports the event in the low word of lparam and
+            where the menu goes in wparam. */
+            data.Anonymous.uVersion = NOTIFYICON_VERSION_4;
+            Shell_NotifyIconW(NIM_SETVERSION, &data).ok()
+        }
+    }
+
+    pub(crate) fn remove(window: &Window) {
+        unsafe {
+            Shell_NotifyIconW(NIM_DELETE, &data(window));
+
+            if let Ok(popup) = RemovePropW(window.handle(), &HSTRING::from(MENU_PROPERTY)) {
+                DestroyMenu(HMENU(popup.0));
+            }
+        }
+    }
+
+    /// Handles a [`TRAY_MESSAGE`].
+    pub(crate) fn on_message(window: &Window, wparam: WPARAM, lparam: LPARAM) {
+        match (lparam.0 & 0xFFFF) as u32 {
+            WM_CONTEXTMENU => unsafe {
+                let (x, y) = (wparam.0 as i16 as i32, (wparam.0 >> 16) as i16 as i32);
+
+                /* Without being in the foreground the window never hears
+                that the menu lost the focus, and the menu stays open. */
+                SetForegroundWindow(window.handle());
+                TrackPopupMenu(
+                    menu(window.handle()),
+                    TPM_RIGHTBUTTON,
+                    x,
+                    y,
+                    0,
+                    window.handle(),
+                    None,
+                );
+                PostMessageW(window.handle(), WM_NULL, WPARAM(0), LPARAM(0));
+            },
+            WM_LBUTTONDBLCLK => window.restore(),
+            _ => {}
+        }
+    }
+}
+
+/// The context menu of the tray icon of `window`, if it has one.
+pub(crate) fn menu(window: HWND) -> HMENU {
+    HMENU(unsafe { GetPropW(window, &HSTRING::from(MENU_PROPERTY)) }.0)
+}
+
+fn data(window: &Window) -> NOTIFYICONDATAW {
+    NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: window.handle(),
+        uID: ICON_ID,
+        ..Default::default()
+    }
+}
+
+/* Truncated to fit, keeping the terminating nul. */
+fn copy(text: &str, buffer: &mut [u16]) {
+    let length = buffer.len() - 1;
+
+    for (slot, unit) in buffer[..length].iter_mut().zip(text.encode_utf16()) {
+        *slot = unit;
+    }
+}
+
+impl Window {
+    /// Shows a balloon notification from the tray icon of the window, which
+    /// newer Windows versions turn into a toast.
+    pub fn notify(&self, title: &str, text: &str) -> Result<()> {
+        let mut data = data(self);
+        data.uFlags = NIF_INFO;
+        data.dwInfoFlags = NIIF_INFO;
+        copy(title, &mut data.szInfoTitle);
+        copy(text, &mut data.szInfo);
+
+        unsafe { Shell_NotifyIconW(NIM_MODIFY, &data).ok() }
+    }
+
+    /// Shows the window if hidden, restores it if minimized, and brings it
+    /// to the foreground.
+    pub fn restore(&self) {
+        let show = match self.is_minimized() {
+            true => SW_RESTORE,
+            false => SW_SHOW,
+        };
+
+        unsafe {
+            ShowWindow(self.handle(), show);
+            SetForegroundWindow(self.handle());
+        }
+    }
+
+    /// Hides the window, e.g. to live on in the tray only.
+    pub fn hide(&self) {
+        unsafe {
+            ShowWindow(self.handle(), SW_HIDE);
+        }
+    }
+}
@@ -0,0 +1,886 @@
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+use common::dpi::Dpi;
+use common::input::{self, InputEvent, Modifiers};
+use common::spy::{Spy, SpyOptions};
+use common::stats::FrameStats;
+
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::{
+        BeginPaint, EndPaint, InvalidateRect, ValidateRect, HBRUSH, PAINTSTRUCT,
+    },
+    Win32::Media::{timeBeginPeriod, timeEndPeriod},
+    Win32::System::LibraryLoader::GetModuleHandleW,
+    Win32::UI::HiDpi::{
+        AdjustWindowRectExForDpi, GetDpiForWindow, SetProcessDpiAwarenessContext,
+        DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+    },
+    Win32::UI::Input::KeyboardAndMouse::{
+        GetKeyState, TrackMouseEvent, TME_LEAVE, TRACKMOUSEEVENT, VIRTUAL_KEY, VK_CONTROL, VK_MENU,
+        VK_SHIFT,
+    },
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+use crate::tray::TRAY_MESSAGE;
+use crate::{placement, App, MenuBar, TrayIcon};
+
+const FRAME_TIMER_ID: usize = 1;
+
+/* Presentation with vsync blocks until the vertical blank anyway; waking up
+a little early lets it absorb the jitter of the sleep. */
+const VSYNC_MARGIN: Duration = Duration::from_millis(2);
+
+/* INFINITE from WinBase.h, without pulling in Win32_System_WindowsProgramming. */
+const INFINITE: u32 = u32::MAX;
+
+/* Nothing tells a window that it is no longer covered, so poll. */
+const OCCLUSION_POLL_MS: u32 = 100;
+
+/// How often [`App::on_frame`] is called.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pacing {
+    /// Never; the app only reacts to messages.
+    Idle,
+    /// From `WM_TIMER`, every this many milliseconds.
+    Timer(u32),
+    /// Continuously, at most `target_fps` times per second. The loop sleeps
+    /// in `MsgWaitForMultipleObjects` between frames and pauses while the
+    /// window is minimized or [`App::is_occluded`]. With `vsync` the app is
+    /// expected to present on the vertical blank.
+    Continuous { target_fps: u32, vsync: bool },
+}
+
+/// Handle to a window created by [`WindowBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Window {
+    handle: HWND,
+    pacing: Pacing,
+}
+
+impl Window {
+    pub fn handle(&self) -> HWND {
+        self.handle
+    }
+
+    pub fn pacing(&self) -> Pacing {
+        self.pacing
+    }
+
+    /// Whether frames should be presented on the vertical blank.
+    pub fn vsync(&self) -> bool {
+        matches!(self.pacing, Pacing::Continuous { vsync: true, .. })
+    }
+
+    pub fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.handle).as_bool() }
+    }
+
+    /// Client area size in physical pixels.
+    pub fn client_size(&self) -> (u32, u32) {
+        let mut rect = RECT::default();
+
+        unsafe {
+            GetClientRect(self.handle, &mut rect);
+        }
+
+        (
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        )
+    }
+
+    /// Resizes the window so that its client area is `width` x `height`
+    /// device independent pixels at the current DPI.
+    pub fn set_client_size(&self, width: i32, height: i32) -> Result<()> {
+        let dpi = self.dpi();
+
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: dpi.to_pixels_ceil(width),
+            bottom: dpi.to_pixels_ceil(height),
+        };
+
+        unsafe {
+            let style = WINDOW_STYLE(GetWindowLongW(self.handle, GWL_STYLE) as u32);
+            let ex_style = WINDOW_EX_STYLE(GetWindowLongW(self.handle, GWL_EXSTYLE) as u32);
+
+            let menu = !GetMenu(self.handle).is_invalid();
+
+            AdjustWindowRectExForDpi(&mut rect, style, menu, ex_style, dpi.0).ok()?;
+
+            SetWindowPos(
+                self.handle,
+                None,
+                0,
+                0,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_NOMOVE | SWP_NOZORDER,
+            )
+            .ok()
+        }
+    }
+
+    pub fn dpi(&self) -> Dpi {
+        Dpi(unsafe { GetDpiForWindow(self.handle) })
+    }
+
+    pub fn set_title(&self, title: &str) {
+        unsafe {
+            SetWindowTextW(self.handle, &HSTRING::from(title));
+        }
+    }
+
+    /// Schedules a `WM_PAINT` for the whole client area, without erasing it.
+    pub fn invalidate(&self) {
+        unsafe {
+            InvalidateRect(self.handle, None, false);
+        }
+    }
+
+    /// Marks the client area as painted.
+    pub fn validate(&self) {
+        unsafe {
+            ValidateRect(self.handle, None);
+        }
+    }
+
+    /// Asks the window to close, as its close box does, so that
+    /// `WM_CLOSE` handlers get their say.
+    pub fn close(&self) {
+        unsafe {
+            PostMessageW(self.handle, WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
+    }
+}
+
+/// Registers a window class, creates a window of that class and runs its
+/// message loop.
+pub struct WindowBuilder {
+    class_name: HSTRING,
+    title: HSTRING,
+    style: WINDOW_STYLE,
+    ex_style: WINDOW_EX_STYLE,
+    size: Option<(i32, i32)>,
+    client_size: Option<(i32, i32)>,
+    cursor: Option<PCWSTR>,
+    background: HBRUSH,
+    pacing: Pacing,
+    remember_placement: bool,
+    spy: Option<Spy>,
+    menu: Option<MenuBar>,
+    tray: Option<TrayIcon>,
+}
+
+impl WindowBuilder {
+    pub fn new(class_name: &str) -> Self {
+        WindowBuilder {
+            class_name: class_name.into(),
+            title: class_name.into(),
+            style: WS_OVERLAPPEDWINDOW | WS_VISIBLE,
+            ex_style: WINDOW_EX_STYLE::default(),
+            size: None,
+            client_size: None,
+            cursor: None,
+            background: HBRUSH(0),
+            pacing: Pacing::Idle,
+            remember_placement: false,
+            spy: None,
+            menu: None,
+            tray: None,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn style(mut self, style: WINDOW_STYLE) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn ex_style(mut self, ex_style: WINDOW_EX_STYLE) -> Self {
+        self.ex_style = ex_style;
+        self
+    }
+
+    /// Outer size of the window, in physical pixels.
+    pub fn size(mut self, width: i32, height: i32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// Client area size in device independent pixels; see
+    /// [`Window::set_client_size`].
+    pub fn client_size(mut self, width: i32, height: i32) -> Self {
+        self.client_size = Some((width, height));
+        self
+    }
+
+    /// One of the predefined `IDC_*` cursors.
+    pub fn cursor(mut self, cursor: PCWSTR) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    pub fn background(mut self, brush: HBRUSH) -> Self {
+        self.background = brush;
+        self
+    }
+
+    pub fn pacing(mut self, pacing: Pacing) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    /// Saves the position, size, maximized state and monitor of the window
+    /// when it is destroyed, under its class name in the user's
+    /// [`Settings`](common::settings::Settings), and opens it that way on
+    /// the next run instead of at the default position and size.
+    pub fn remember_placement(mut self) -> Self {
+        self.remember_placement = true;
+        self
+    }
+
+    /// Records every message the window procedure sees. Without this,
+    /// `--spy <file>` on the command line starts a trace; see
+    /// [`SpyOptions`].
+    pub fn spy(mut self, spy: Spy) -> Self {
+        self.spy = Some(spy);
+        self
+    }
+
+    /// Gives the window a menu bar with accelerators.
+    pub fn menu(mut self, menu: MenuBar) -> Self {
+        self.menu = Some(menu);
+        self
+    }
+
+    /// Puts an icon for the window in the notification area while it
+    /// exists.
+    pub fn tray(mut self, tray: TrayIcon) -> Self {
+        self.tray = Some(tray);
+        self
+    }
+
+    /// Creates the window, builds the app with `create` and pumps messages
+    /// until the window is destroyed or a callback fails.
+    pub fn run<A, F>(mut self, create: F) -> Result<()>
+    where
+        A: App,
+        F: FnOnce(&Window) -> Result<A>,
+    {
+        let spy = match self.spy.take() {
+            Some(spy) => Some(spy),
+            None => match SpyOptions::from_args(std::env::args().skip(1))? {
+                Some(options) => Some(Spy::create(&options)?),
+                None => None,
+            },
+        };
+
+        let (menu, accelerators) = match &self.menu {
+            Some(menu) => menu.build()?,
+            None => (HMENU(0), HACCEL(0)),
+        };
+
+        let state = Box::new(State::<A> {
+            app: RefCell::new(None),
+            error: RefCell::new(None),
+            pacing: self.pacing,
+            stats: RefCell::new(FrameStats::new()),
+            last_frame: Cell::new(None),
+            tracking_mouse: Cell::new(false),
+            placement_key: self.remember_placement.then(|| self.class_name.to_string()),
+            spy: RefCell::new(spy),
+            menu: RefCell::new(self.menu.take()),
+            accelerators,
+            tray: RefCell::new(self.tray.take()),
+            tray_added: Cell::new(false),
+            taskbar_created: unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) },
+        });
+
+        let window = match self.create_window(&state, menu) {
+            Ok(window) => window,
+            Err(error) => {
+                unsafe {
+                    destroy_accelerators(accelerators);
+                }
+
+                return Err(error);
+            }
+        };
+
+        let result = state.run(window, create);
+
+        /* The window must not outlive the state its procedure points to. It
+        is still there after a failing callback, and destroyed without the
+        app seeing WM_DESTROY. */
+        unsafe {
+            if IsWindow(window.handle).as_bool() {
+                state.release(window);
+                SetWindowLongPtrW(window.handle, GWLP_USERDATA, 0);
+                DestroyWindow(window.handle);
+            }
+
+            destroy_accelerators(accelerators);
+        }
+
+        result
+    }
+
+    /* Takes care of `menu` when it fails: no window is left pointing at
+    `state`. */
+    fn create_window<A: App>(&self, state: &State<A>, menu: HMENU) -> Result<Window> {
+        let handle = match self.create_handle(state, menu) {
+            Ok(handle) => handle,
+            Err(error) => {
+                /* Not attached to any window. */
+                unsafe {
+                    DestroyMenu(menu);
+                }
+
+                return Err(error);
+            }
+        };
+
+        let window = Window {
+            handle,
+            pacing: self.pacing,
+        };
+
+        if let Err(error) = self.place(window) {
+            /* The menu goes with the window. */
+            unsafe {
+                SetWindowLongPtrW(handle, GWLP_USERDATA, 0);
+                DestroyWindow(handle);
+            }
+
+            return Err(error);
+        }
+
+        Ok(window)
+    }
+
+    fn create_handle<A: App>(&self, state: &State<A>, menu: HMENU) -> Result<HWND> {
+        unsafe {
+            /* Per-monitor v2 makes Windows send WM_DPICHANGED instead of
+            bitmap-stretching the window. Fails harmlessly when a manifest or
+            an earlier window already set the awareness. */
+            SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+
+            let instance = GetModuleHandleW(None)?;
+
+            let wc = WNDCLASSW {
+                hCursor: match self.cursor {
+                    Some(cursor) => LoadCursorW(None, cursor)?,
+                    None => HCURSOR(0),
+                },
+                hInstance: instance,
+                hbrBackground: self.background,
+                lpszClassName: (&self.class_name).into(),
+                style: CS_HREDRAW | CS_VREDRAW,
+                lpfnWndProc: Some(wndproc::<A>),
+                ..Default::default()
+            };
+
+            if RegisterClassW(&wc) == 0 {
+                return Err(Error::from_win32());
+            }
+
+            let (width, height) = self.size.unwrap_or((CW_USEDEFAULT, CW_USEDEFAULT));
+
+            /* Shown only once in place, rather than jumping there. */
+            let style = match self.remember_placement {
+                true => self.style & !WS_VISIBLE,
+                false => self.style,
+            };
+
+            let handle = CreateWindowExW(
+                self.ex_style,
+                &self.class_name,
+                &self.title,
+                style,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                width,
+                height,
+                None,
+                menu,
+                instance,
+                Some(state as *const _ as _),
+            );
+
+            if handle.0 == 0 {
+                return Err(Error::from_win32());
+            }
+
+            Ok(handle)
+        }
+    }
+
+    /* Moves the new window where it was last time, or sizes its client
+    area, and shows it if hidden for that. */
+    fn place(&self, window: Window) -> Result<()> {
+        let visible = self.style & WS_VISIBLE == WS_VISIBLE;
+
+        if self.remember_placement
+            && placement::restore(window.handle, &self.class_name.to_string(), visible)
+        {
+            return Ok(());
+        }
+
+        if let Some((width, height)) = self.client_size {
+            window.set_client_size(width, height)?;
+        }
+
+        if self.remember_placement && visible {
+            unsafe {
+                ShowWindow(window.handle, SW_SHOW);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/* Lives behind GWLP_USERDATA for as long as the window does. The app sits in
+a RefCell because message handlers re-enter the window procedure, e.g. when
+`DestroyWindow` sends WM_DESTROY; such nested messages get default handling,
+apart from menu commands and those of the tray icon. */
+struct State<A> {
+    app: RefCell<Option<A>>,
+    error: RefCell<Option<Error>>,
+    pacing: Pacing,
+    stats: RefCell<FrameStats>,
+    last_frame: Cell<Option<Instant>>,
+    /* Whether WM_MOUSELEAVE is requested for the current visit of the cursor. */
+    tracking_mouse: Cell<bool>,
+    /* Where the placement is saved, if it is. */
+    placement_key: Option<String>,
+    spy: RefCell<Option<Spy>>,
+    menu: RefCell<Option<MenuBar>>,
+    accelerators: HACCEL,
+    tray: RefCell<Option<TrayIcon>>,
+    tray_added: Cell<bool>,
+    /* Broadcast when Explorer starts again, without the icons it had. */
+    taskbar_created: u32,
+}
+
+impl<A: App> State<A> {
+    fn run<F>(&self, window: Window, create: F) -> Result<()>
+    where
+        F: FnOnce(&Window) -> Result<A>,
+    {
+        /* Marked first, so that half an icon is taken away too. */
+        if let Some(tray) = self.tray.borrow().as_ref() {
+            self.tray_added.set(true);
+            tray.add(&window)?;
+        }
+
+        *self.app.borrow_mut() = Some(create(&window)?);
+
+        let exit_code = unsafe {
+            match self.pacing {
+                Pacing::Idle => self.run_blocking(window),
+                Pacing::Timer(milliseconds) => {
+                    SetTimer(window.handle, FRAME_TIMER_ID, milliseconds, None);
+                    self.run_blocking(window)
+                }
+                Pacing::Continuous { target_fps, vsync } => {
+                    timeBeginPeriod(1);
+                    let exit_code = self.run_continuous(window, target_fps, vsync);
+                    timeEndPeriod(1);
+                    exit_code
+                }
+            }
+        };
+
+        if let Some(error) = self.error.borrow_mut().take() {
+            return Err(error);
+        }
+
+        match exit_code? {
+            WPARAM(0) => Ok(()),
+            WPARAM(_) => Err(Error::from(E_UNEXPECTED)),
+        }
+    }
+
+    unsafe fn run_blocking(&self, window: Window) -> Result<WPARAM> {
+        let mut message = MSG::default();
+
+        loop {
+            match GetMessageW(&mut message, None, 0, 0) {
+                BOOL(0) => return Ok(message.wParam),
+                BOOL(-1) => return Err(Error::from_win32()),
+                BOOL(_) => self.translate_and_dispatch(window, &message),
+            }
+        }
+    }
+
+    unsafe fn run_continuous(
+        &self,
+        window: Window,
+        target_fps: u32,
+        vsync: bool,
+    ) -> Result<WPARAM> {
+        let period = Duration::from_secs_f64(1. / target_fps.max(1) as f64);
+        let margin = if vsync { VSYNC_MARGIN } else { Duration::ZERO };
+        let mut message = MSG::default();
+
+        loop {
+            while PeekMessageW(&mut message, None, 0, 0, PM_REMOVE).as_bool() {
+                if message.message == WM_QUIT {
+                    return Ok(message.wParam);
+                }
+
+                self.translate_and_dispatch(window, &message);
+            }
+
+            if window.is_minimized() {
+                self.pause();
+                wait_for_messages(INFINITE);
+                continue;
+            }
+
+            if self.is_occluded() {
+                self.pause();
+                wait_for_messages(OCCLUSION_POLL_MS);
+                continue;
+            }
+
+            if let Some(last_frame) = self.last_frame.get() {
+                let deadline = (last_frame + period)
+                    .checked_sub(margin)
+                    .unwrap_or(last_frame);
+                let now = Instant::now();
+
+                if now < deadline {
+                    let remaining = deadline - now;
+                    wait_for_messages(remaining.as_micros().div_ceil(1000) as u32);
+                    continue;
+                }
+            }
+
+            self.frame(&window);
+        }
+    }
+
+    /* Accelerators become WM_COMMAND to the window, whichever control has
+    the focus. */
+    unsafe fn translate_and_dispatch(&self, window: Window, message: &MSG) {
+        if !self.accelerators.is_invalid()
+            && TranslateAcceleratorW(window.handle, self.accelerators, message) != 0
+        {
+            return;
+        }
+
+        TranslateMessage(message);
+        DispatchMessageW(message);
+    }
+
+    /* The time spent paused is not a frame time. */
+    fn pause(&self) {
+        self.last_frame.set(None);
+    }
+
+    fn is_occluded(&self) -> bool {
+        match self.app.try_borrow() {
+            Ok(app) => app.as_ref().is_some_and(A::is_occluded),
+            Err(_) => false,
+        }
+    }
+
+    fn frame(&self, window: &Window) {
+        let now = Instant::now();
+
+        if let Some(last_frame) = self.last_frame.replace(Some(now)) {
+            self.stats.borrow_mut().record(now - last_frame);
+        }
+
+        if let Ok(mut app) = self.app.try_borrow_mut() {
+            if let Some(app) = app.as_mut() {
+                self.check(app.on_frame(window, &self.stats.borrow()));
+            }
+        }
+    }
+
+    /* A failing trace stops, the window carries on. */
+    fn spy(&self, message: u32, wparam: WPARAM, lparam: LPARAM) {
+        let Ok(mut spy) = self.spy.try_borrow_mut() else {
+            return;
+        };
+
+        if let Some(Err(error)) = spy
+            .as_mut()
+            .map(|spy| spy.record(message, wparam.0, lparam.0))
+        {
+            eprintln!("message trace stopped: {error}");
+            *spy = None;
+        }
+    }
+
+    /* What the window leaves behind when destroyed: its placement saved and
+    no tray icon. */
+    fn release(&self, window: Window) {
+        if let Some(key) = &self.placement_key {
+            if let Err(error) = placement::save(window.handle, key) {
+                eprintln!("could not save the window placement: {error}");
+            }
+        }
+
+        if self.tray_added.replace(false) {
+            TrayIcon::remove(&window);
+        }
+    }
+
+    /* Remembers the first error and leaves the message loop. */
+    fn check(&self, result: Result<()>) {
+        if let Err(error) = result {
+            self.error.borrow_mut().get_or_insert(error);
+
+            unsafe {
+                PostQuitMessage(1);
+            }
+        }
+    }
+
+    fn track_mouse(&self, window: Window, event: InputEvent) {
+        match event {
+            InputEvent::MouseMove { .. } if !self.tracking_mouse.get() => {
+                let mut track = TRACKMOUSEEVENT {
+                    cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as u32,
+                    dwFlags: TME_LEAVE,
+                    hwndTrack: window.handle,
+                    dwHoverTime: 0,
+                };
+
+                let tracking = unsafe { TrackMouseEvent(&mut track).as_bool() };
+                self.tracking_mouse.set(tracking);
+            }
+            InputEvent::MouseLeave => self.tracking_mouse.set(false),
+            _ => {}
+        }
+    }
+
+    fn dispatch(
+        &self,
+        window: Window,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        if message == WM_DESTROY {
+            self.release(window);
+
+            unsafe {
+                if let Pacing::Timer(_) = self.pacing {
+                    KillTimer(window.handle, FRAME_TIMER_ID);
+                }
+
+                /* Post quit message with status 0 to main process loop */
+                PostQuitMessage(0);
+            }
+        }
+
+        let Ok(mut app_ref) = self.app.try_borrow_mut() else {
+            return self.dispatch_without_app(window, message, wparam, lparam);
+        };
+        let Some(app) = app_ref.as_mut() else {
+            drop(app_ref);
+            return self.dispatch_without_app(window, message, wparam, lparam);
+        };
+
+        if let Some(result) = app.on_message(&window, message, wparam, lparam) {
+            return Some(result);
+        }
+
+        if let Some(event) = input::decode_win32(message, wparam.0, lparam.0, modifiers()) {
+            self.track_mouse(window, event);
+            app.on_input(&window, event);
+            return None;
+        }
+
+        let result = match message {
+            WM_DPICHANGED => {
+                let result = app.on_dpi_changed(&window, Dpi::from_dpi_changed(wparam.0));
+
+                /* Moving into the suggested rectangle sends WM_SIZE right away. */
+                drop(app_ref);
+
+                unsafe {
+                    let suggested = &*(lparam.0 as *const RECT);
+
+                    SetWindowPos(
+                        window.handle,
+                        None,
+                        suggested.left,
+                        suggested.top,
+                        suggested.right - suggested.left,
+                        suggested.bottom - suggested.top,
+                        SWP_NOZORDER | SWP_NOACTIVATE,
+                    );
+                }
+
+                result
+            }
+            WM_PAINT => {
+                let mut ps = PAINTSTRUCT::default();
+
+                unsafe {
+                    let hdc = BeginPaint(window.handle, &mut ps);
+                    let result = app.on_paint(&window, hdc);
+                    EndPaint(window.handle, &ps);
+                    result
+                }
+            }
+            WM_SIZE => {
+                let width = (lparam.0 as u32) & 0xFFFF;
+                let height = ((lparam.0 as u32) >> 16) & 0xFFFF;
+                app.on_resize(&window, width, height)
+            }
+            WM_TIMER if wparam.0 == FRAME_TIMER_ID => {
+                drop(app_ref);
+                self.frame(&window);
+                return Some(LRESULT(0));
+            }
+            WM_DESTROY => Ok(()),
+            _ => {
+                drop(app_ref);
+                return self.dispatch_shell(window, message, wparam, lparam);
+            }
+        };
+
+        self.check(result);
+        Some(LRESULT(0))
+    }
+
+    /* Closing is up to the app, which may want to ask first: a WM_CLOSE
+    that comes while it is busy, e.g. from the tray menu with a confirmation
+    already open, must not reach `DefWindowProcW` and destroy the window. */
+    fn dispatch_without_app(
+        &self,
+        window: Window,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            WM_CLOSE => Some(LRESULT(0)),
+            _ => self.dispatch_shell(window, message, wparam, lparam),
+        }
+    }
+
+    /* Menu commands and the tray icon, which work whether the app is there
+    or not: before it is created, and while it runs a modal loop of its own
+    such as a confirmation dialog. */
+    fn dispatch_shell(
+        &self,
+        window: Window,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        match message {
+            /* Menu items and accelerators; controls send their handle in
+            lparam. */
+            WM_COMMAND if lparam.0 == 0 => {
+                let id = (wparam.0 & 0xFFFF) as u16;
+
+                let bar = self
+                    .menu
+                    .try_borrow_mut()
+                    .ok()
+                    .and_then(|mut menu| menu.as_mut()?.dispatch(&window, id));
+
+                let result = match bar {
+                    Some(result) => result,
+                    None => self
+                        .tray
+                        .try_borrow_mut()
+                        .ok()
+                        .and_then(|mut tray| tray.as_mut()?.menu_mut().dispatch(&window, id))?,
+                };
+
+                self.check(result);
+                Some(LRESULT(0))
+            }
+            TRAY_MESSAGE if self.tray_added.get() => {
+                TrayIcon::on_message(&window, wparam, lparam);
+                Some(LRESULT(0))
+            }
+            _ if message == self.taskbar_created && self.tray_added.get() => {
+                let Ok(tray) = self.tray.try_borrow() else {
+                    return None;
+                };
+
+                if let Some(Err(error)) = tray.as_ref().map(|tray| tray.add(&window)) {
+                    eprintln!("could not add the tray icon again: {error}");
+                }
+
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+extern "system" fn wndproc<A: App>(
+    window: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    unsafe {
+        if message == WM_NCCREATE {
+            let cs = lparam.0 as *const CREATESTRUCTW;
+            SetWindowLongPtrW(window, GWLP_USERDATA, (*cs).lpCreateParams as _);
+        }
+
+        let state = GetWindowLongPtrW(window, GWLP_USERDATA) as *const State<A>;
+
+        if let Some(state) = state.as_ref() {
+            state.spy(message, wparam, lparam);
+
+            let window = Window {
+                handle: window,
+                pacing: state.pacing,
+            };
+
+            if let Some(result) = state.dispatch(window, message, wparam, lparam) {
+                return result;
+            }
+        }
+
+        DefWindowProcW(window, message, wparam, lparam)
+    }
+}
+
+/* Key messages do not carry the modifier state; this is the state as of the
+message being processed. */
+pub(crate) fn modifiers() -> Modifiers {
+    let is_down = |key: VIRTUAL_KEY| unsafe { GetKeyState(key.0.into()) } < 0;
+
+    Modifiers {
+        shift: is_down(VK_SHIFT),
+        ctrl: is_down(VK_CONTROL),
+        alt: is_down(VK_MENU),
+    }
+}
+
+unsafe fn destroy_accelerators(accelerators: HACCEL) {
+    if !accelerators.is_invalid() {
+        DestroyAcceleratorTable(accelerators);
+    }
+}
+
+/* Sleeps until a message arrives or the timeout expires. */
+unsafe fn wait_for_messages(milliseconds: u32) {
+    MsgWaitForMultipleObjects(None, false, milliseconds, QS_ALLINPUT);
+}